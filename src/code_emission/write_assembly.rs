@@ -1,115 +1,286 @@
 
 use crate::code_gen::a_ast::*;
+use crate::resolver::type_checker::*;
 use crate::utilities::error_handler::emission_error;
 use std::fs::File;
 use std::path::Path;
 use std::io::Write;
 use std::io::Error;
 
-pub fn output(ast: AssemFuncDef, name: &str) {
+pub fn output(ast: AssemProgram, name: &str, symbols: &SymbolTable) {
     let path_string = format!("{}.s", name).to_string();
     let path = Path::new(&path_string);
     let display = path.display();
 
-    let mut file = match File::create(&path) {
+    let mut file = match File::create(path) {
         Err(why) => emission_error(&format!("Cannot open file {}: {}", display, why)),
         Ok(file) => file,
     };
 
-    match write_program(&ast, &mut file) {
-        Err(why) => emission_error(&format!("Failed to write Assembly:\n{}", why)),
-        Ok(_) => (),
-    };
+    if let Err(why) = write_program(&ast, &mut file, symbols) {
+        emission_error(&format!("Failed to write Assembly:\n{}", why));
+    }
 
-    match file.write(b".section .note.GNU-stack,\"\",@progbits") {
-        Err(why) => emission_error(&format!("Failed to write to Assembly:\n{}", why)),
-        Ok(_) => (),
-    };
+    if let Err(why) = file.write_all(b".section .note.GNU-stack,\"\",@progbits") {
+        emission_error(&format!("Failed to write to Assembly:\n{}", why));
+    }
+}
+
+fn write_program(ast: &AssemProgram, file: &mut File, symbols: &SymbolTable) -> Result<(), Error> {
+    match ast {
+        AssemProgram::Program(items) => {
+            for item in items.iter() {
+                match item {
+                    AssemTopLevel::Func(function) => write_function(function, file, symbols)?,
+                    AssemTopLevel::StaticVar(name, global, alignment, inits) =>
+                        write_static_var(name, *global, *alignment, inits, file)?,
+                    AssemTopLevel::StaticConst(name, alignment, init) => write_static_const(name, *alignment, init, file)?,
+                }
+            }
+            Ok(())
+        },
+    }
+}
+
+fn write_static_var(name: &str, global: bool, alignment: i32, inits: &[StaticInit], file: &mut File) -> Result<(), Error> {
+    if global {
+        file.write_all(format!("\t.globl {}\n", name).as_bytes())?;
+    }
+
+    let all_zero = inits.iter().all(is_zero_init);
+
+    if all_zero {
+        file.write_all(b"\t.bss\n")?;
+    } else {
+        file.write_all(b"\t.data\n")?;
+    }
+    file.write_all(format!("\t.balign {}\n", alignment).as_bytes())?;
+    file.write_all(format!("{}:\n", name).as_bytes())?;
+
+    if all_zero {
+        let size: i32 = inits.iter().map(|init| get_static_init(init).1).sum();
+        file.write_all(format!("\t.zero {}\n", size).as_bytes())?;
+    } else {
+        for init in inits.iter() {
+            let (value, _, directive) = get_static_init(init);
+            file.write_all(format!("\t{} {}\n", directive, value).as_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_static_const(name: &str, alignment: i32, init: &StaticInit, file: &mut File) -> Result<(), Error> {
+    let (value, size, directive) = get_static_init(init);
+
+    file.write_all(b"\t.section .rodata\n")?;
+    file.write_all(format!("\t.balign {}\n", alignment).as_bytes())?;
+    file.write_all(format!("{}:\n", name).as_bytes())?;
+    file.write_all(format!("\t{} {}\n", directive, value).as_bytes())?;
+    if alignment > size {
+        file.write_all(format!("\t.zero {}\n", alignment - size).as_bytes())?;
+    }
+
+    Ok(())
+}
+
+fn is_zero_init(init: &StaticInit) -> bool {
+    matches!(init, StaticInit::Zero(_)) || get_static_init(init).0 == "0"
+}
+
+//Doubles are written as their raw bit pattern so the value round-trips exactly
+fn get_static_init(init: &StaticInit) -> (String, i32, &'static str) {
+    match init {
+        StaticInit::Char(val) => (val.to_string(), 1, ".byte"),
+        StaticInit::UChar(val) => (val.to_string(), 1, ".byte"),
+        StaticInit::StringInit(bytes, true) => (escape_string(bytes), bytes.len() as i32 + 1, ".asciz"),
+        StaticInit::StringInit(bytes, false) => (escape_string(bytes), bytes.len() as i32, ".ascii"),
+        StaticInit::PointerInit(name, 0) => (name.clone(), 8, ".quad"),
+        StaticInit::PointerInit(name, offset) => (format!("{}{:+}", name, offset), 8, ".quad"),
+        StaticInit::Int(val) => (val.to_string(), 4, ".long"),
+        StaticInit::Long(val) => (val.to_string(), 8, ".quad"),
+        StaticInit::UInt(val) => (val.to_string(), 4, ".long"),
+        StaticInit::ULong(val) => (val.to_string(), 8, ".quad"),
+        StaticInit::Double(val) => (val.to_bits().to_string(), 8, ".quad"),
+        StaticInit::Zero(size) => (size.to_string(), *size, ".zero"),
+    }
+}
+
+fn escape_string(bytes: &[u8]) -> String {
+    let mut escaped = String::from("\"");
+
+    for byte in bytes.iter() {
+        match byte {
+            b'"' | b'\\' => {
+                escaped.push('\\');
+                escaped.push(*byte as char);
+            },
+            b' '..=b'~' => escaped.push(*byte as char),
+            _ => escaped.push_str(&format!("\\{:03o}", byte)),
+        }
+    }
+    escaped.push('"');
+
+    escaped
 }
 
-fn write_program(ast: &AssemFuncDef, file: &mut File) -> Result<(), Error> {
+fn write_function(ast: &AssemFuncDef, file: &mut File, symbols: &SymbolTable) -> Result<(), Error> {
     match ast {
-        AssemFuncDef::Function(name, instructions) => {
-            file.write_all(format!(".globl {}\n", name).as_bytes())?;
+        AssemFuncDef::Function(name, global, instructions) => {
+            if *global {
+                file.write_all(format!("\t.globl {}\n", name).as_bytes())?;
+            }
+            file.write_all(b"\t.text\n")?;
             file.write_all(format!("{}:\n", name).as_bytes())?;
             write_prologue(file)?;
-            write_instructions(instructions, file)?;
+            write_instructions(instructions, file, symbols)?;
             Ok(())
         },
     }
 }
 
-fn write_instructions(instructions: &Vec<AInstr>, file: &mut File) -> Result<(), Error> {
+fn write_instructions(instructions: &[AInstr], file: &mut File, symbols: &SymbolTable) -> Result<(), Error> {
     for instruction in instructions.iter() {
-        write_instruction(instruction, file)?;
+        write_instruction(instruction, file, symbols)?;
     }
 
     Ok(())
 }
 
-fn write_instruction(instruction: &AInstr, file: &mut File) -> Result<(), Error> {
+fn write_instruction(instruction: &AInstr, file: &mut File, symbols: &SymbolTable) -> Result<(), Error> {
     match instruction {
-        AInstr::Mov(left, right) => {
-            let src = get_operand(left);
-            let dst = get_operand(right);
+        AInstr::Mov(ty, left, right) => {
+            let src = get_sized_operand(left, ty);
+            let dst = get_sized_operand(right, ty);
+
+            file.write_all(format!("\tmov{} {}, {}\n", get_suffix(ty), &src, &dst).as_bytes())?;
+        },
+        AInstr::MovZeroExtend(AType::Byte, dst_type, left, right) => {
+            let src = get_sized_operand(left, &AType::Byte);
+            let dst = get_sized_operand(right, dst_type);
+
+            file.write_all(format!("\tmovzb{} {}, {}\n", get_suffix(dst_type), &src, &dst).as_bytes())?;
+        },
+        AInstr::MovZeroExtend(_, _, _, _) => panic!("Zero extension should be replaced before emission"),
+        AInstr::Movsx(src_type, dst_type, left, right) => {
+            let src = get_sized_operand(left, src_type);
+            let dst = get_sized_operand(right, dst_type);
 
-            file.write_all(format!("\tmovl {}, {}\n", &src, &dst).as_bytes())?;
+            file.write_all(format!("\tmovs{}{} {}, {}\n", get_suffix(src_type), get_suffix(dst_type), &src, &dst).as_bytes())?;
         },
-        AInstr::Unary(op, oprnd) => {
+        AInstr::Unary(op, ty, oprnd) => {
             let operator = get_unary_operator(op);
-            let operand = get_operand(oprnd);
+            let operand = get_sized_operand(oprnd, ty);
 
-            file.write_all(format!("\t{} {}\n", &operator, &operand).as_bytes())?;
+            file.write_all(format!("\t{}{} {}\n", &operator, get_suffix(ty), &operand).as_bytes())?;
         },
-        AInstr::Binary(op, left, right) => {
-            let operator = get_binary_operator(op);
+        AInstr::Binary(op, ty, left, right) => {
+            let operator = get_binary_operator(op, ty);
+            let src = match op {
+                ABinaryOp::Sal | ABinaryOp::Sar | ABinaryOp::Shr => get_byte_operand(left),
+                _ => get_sized_operand(left, ty),
+            };
+            let dst = get_sized_operand(right, ty);
+
+            file.write_all(format!("\t{} {}, {}\n", &operator, &src, &dst).as_bytes())?;
+        },
+        AInstr::Cvttsd2si(ty, left, right) => {
             let src = get_operand(left);
+            let dst = get_sized_operand(right, ty);
+
+            file.write_all(format!("\tcvttsd2si{} {}, {}\n", get_suffix(ty), &src, &dst).as_bytes())?;
+        },
+        AInstr::Lea(left, right) => {
+            let src = get_operand(left);
+            let dst = get_quad_operand(right);
+
+            file.write_all(format!("\tleaq {}, {}\n", &src, &dst).as_bytes())?;
+        },
+        AInstr::Cvtsi2sd(ty, left, right) => {
+            let src = get_sized_operand(left, ty);
             let dst = get_operand(right);
-            
-            file.write_all(format!("\t{} {}, {}\n", &operator, &src, &dst).as_bytes())?;
+
+            file.write_all(format!("\tcvtsi2sd{} {}, {}\n", get_suffix(ty), &src, &dst).as_bytes())?;
+        },
+        AInstr::Idiv(ty, op) => {
+            let operand = get_sized_operand(op, ty);
+
+            file.write_all(format!("\tidiv{} {}\n", get_suffix(ty), operand).as_bytes())?;
         },
-        AInstr::Idiv(op) => {
-            let operand = get_operand(op);
+        AInstr::Div(ty, op) => {
+            let operand = get_sized_operand(op, ty);
 
-            file.write_all(format!("\tidivl {}\n", operand).as_bytes())?;
+            file.write_all(format!("\tdiv{} {}\n", get_suffix(ty), operand).as_bytes())?;
         },
-        AInstr::Cdq => {
+        AInstr::Cdq(AType::Longword) => {
             file.write_all("\tcdq\n".as_bytes())?;
         },
+        AInstr::Cdq(_) => {
+            file.write_all("\tcqo\n".as_bytes())?;
+        },
         AInstr::AllocateStack(val) => {
             file.write_all(format!("\tsubq ${}, %rsp\n", val).as_bytes())?;
         },
+        AInstr::DeallocateStack(val) => {
+            file.write_all(format!("\taddq ${}, %rsp\n", val).as_bytes())?;
+        },
+        AInstr::Push(op) => {
+            let operand = get_quad_operand(op);
+            file.write_all(format!("\tpushq {}\n", operand).as_bytes())?;
+        },
+        AInstr::Call(name) => {
+            let target = match symbols.get(name) {
+                Some(Symbol { attrs: IdentAttrs::Fun(true, _), .. }) => name.clone(),
+                _ => format!("{}@PLT", name),
+            };
+            file.write_all(format!("\tcall {}\n", target).as_bytes())?;
+        },
         AInstr::Ret => {
             file.write_all(b"\tmovq %rbp, %rsp\n\tpopq %rbp\n\tret\n")?;
         },
-        AInstr::Cmp(op1, op2) => {
-            let left = get_operand(&op1);
-            let right = get_operand(&op2);
-            file.write_all(format!("\tcmpl {}, {}\n", left, right).as_bytes())?;
+        AInstr::Ud2 => {
+            file.write_all(b"\tud2\n")?;
+        },
+        AInstr::Cmp(ty, op1, op2) => {
+            let left = get_sized_operand(op1, ty);
+            let right = get_sized_operand(op2, ty);
+            let operator = if *ty == AType::Double { "comisd".to_string() } else { format!("cmp{}", get_suffix(ty)) };
+            file.write_all(format!("\t{} {}, {}\n", operator, left, right).as_bytes())?;
         },
         AInstr::Jmp(label) => {
-            let label_out = get_label(&label);
+            let label_out = get_label(label);
             file.write_all(format!("\tjmp {}\n", label_out).as_bytes())?;
         },
+        AInstr::JmpIndirect(op) => {
+            file.write_all(format!("\tjmp *{}\n", get_quad_operand(op)).as_bytes())?;
+        },
+        AInstr::JumpTable(name, targets) => {
+            file.write_all(b"\t.section .rodata\n\t.balign 4\n")?;
+            file.write_all(format!("{}:\n", name).as_bytes())?;
+            for target in targets.iter() {
+                file.write_all(format!("\t.long {}-{}\n", get_label(target), name).as_bytes())?;
+            }
+            file.write_all(b"\t.text\n")?;
+        },
         AInstr::JmpCC(code, label) => {
             let cond_code = get_cond_code(code);
-            let label_out = get_label(&label);
+            let label_out = get_label(label);
             file.write_all(format!("\tj{} {}\n", cond_code, label_out).as_bytes())?;
         },
         AInstr::SetCC(code, op) => {
-            let cond_code = get_cond_code(&code);
-            let op_out = get_byte_operand(&op);
+            let cond_code = get_cond_code(code);
+            let op_out = get_byte_operand(op);
             file.write_all(format!("\tset{} {}\n", cond_code, op_out).as_bytes())?;
         },
         AInstr::Label(label) => {
-            let label_out = get_label(&label);
+            let label_out = get_label(label);
             file.write_all(format!("{}:\n", label_out).as_bytes())?;
         }
     };
     Ok(())
 }
 
-fn get_label(op: &String) -> String {
+fn get_label(op: &str) -> String {
     let mut label = String::from(".L");
     label += op;
     label
@@ -121,28 +292,90 @@ fn get_cond_code(code: &CondCode) -> String {
         CondCode::NE => "ne".to_string(),
         CondCode::L => "l".to_string(),
         CondCode::LE => "le".to_string(),
+        CondCode::A => "a".to_string(),
+        CondCode::AE => "ae".to_string(),
+        CondCode::B => "b".to_string(),
+        CondCode::BE => "be".to_string(),
+        CondCode::P => "p".to_string(),
         CondCode::G => "g".to_string(),
         CondCode::GE => "ge".to_string(),
     }
 }
 
+fn get_suffix(ty: &AType) -> &'static str {
+    match ty {
+        AType::Byte => "b",
+        AType::Longword => "l",
+        AType::Quadword => "q",
+        AType::Double => "sd",
+    }
+}
+
+fn get_sized_operand(op: &AOprnd, ty: &AType) -> String {
+    match ty {
+        AType::Byte => get_byte_operand(op),
+        AType::Longword => get_operand(op),
+        AType::Quadword | AType::Double => get_quad_operand(op),
+    }
+}
+
 fn get_byte_operand(op: &AOprnd) -> String {
     match op {
         AOprnd::Reg(AReg::AX) => "%al".to_string(),
+        AOprnd::Reg(AReg::CX) => "%cl".to_string(),
         AOprnd::Reg(AReg::DX) => "%dl".to_string(),
+        AOprnd::Reg(AReg::DI) => "%dil".to_string(),
+        AOprnd::Reg(AReg::SI) => "%sil".to_string(),
+        AOprnd::Reg(AReg::R8) => "%r8b".to_string(),
+        AOprnd::Reg(AReg::R9) => "%r9b".to_string(),
         AOprnd::Reg(AReg::R10) => "%r10b".to_string(),
         AOprnd::Reg(AReg::R11) => "%r11b".to_string(),
         _ => get_operand(op),
     }
 }
 
+fn get_quad_operand(op: &AOprnd) -> String {
+    match op {
+        AOprnd::Reg(AReg::AX) => "%rax".to_string(),
+        AOprnd::Reg(AReg::CX) => "%rcx".to_string(),
+        AOprnd::Reg(AReg::DX) => "%rdx".to_string(),
+        AOprnd::Reg(AReg::DI) => "%rdi".to_string(),
+        AOprnd::Reg(AReg::SI) => "%rsi".to_string(),
+        AOprnd::Reg(AReg::R8) => "%r8".to_string(),
+        AOprnd::Reg(AReg::R9) => "%r9".to_string(),
+        AOprnd::Reg(AReg::R10) => "%r10".to_string(),
+        AOprnd::Reg(AReg::R11) => "%r11".to_string(),
+        AOprnd::Reg(AReg::SP) => "%rsp".to_string(),
+        _ => get_operand(op),
+    }
+}
+
 fn get_operand(op: &AOprnd) -> String {
     match op {
         AOprnd::Reg(AReg::AX) => "%eax".to_string(),
+        AOprnd::Reg(AReg::CX) => "%ecx".to_string(),
         AOprnd::Reg(AReg::DX) => "%edx".to_string(),
+        AOprnd::Reg(AReg::DI) => "%edi".to_string(),
+        AOprnd::Reg(AReg::SI) => "%esi".to_string(),
+        AOprnd::Reg(AReg::R8) => "%r8d".to_string(),
+        AOprnd::Reg(AReg::R9) => "%r9d".to_string(),
         AOprnd::Reg(AReg::R10) => "%r10d".to_string(),
         AOprnd::Reg(AReg::R11) => "%r11d".to_string(),
+        AOprnd::Reg(AReg::XMM0) => "%xmm0".to_string(),
+        AOprnd::Reg(AReg::XMM1) => "%xmm1".to_string(),
+        AOprnd::Reg(AReg::XMM2) => "%xmm2".to_string(),
+        AOprnd::Reg(AReg::XMM3) => "%xmm3".to_string(),
+        AOprnd::Reg(AReg::XMM4) => "%xmm4".to_string(),
+        AOprnd::Reg(AReg::XMM5) => "%xmm5".to_string(),
+        AOprnd::Reg(AReg::XMM6) => "%xmm6".to_string(),
+        AOprnd::Reg(AReg::XMM7) => "%xmm7".to_string(),
+        AOprnd::Reg(AReg::XMM14) => "%xmm14".to_string(),
+        AOprnd::Reg(AReg::XMM15) => "%xmm15".to_string(),
         AOprnd::Stack(val) => format!("{}(%rbp)", val).to_string(),
+        AOprnd::Memory(reg, offset) => format!("{}({})", offset, get_quad_operand(&AOprnd::Reg(reg.clone()))),
+        AOprnd::Indexed(base, index, scale) => format!("({}, {}, {})",
+            get_quad_operand(&AOprnd::Reg(base.clone())), get_quad_operand(&AOprnd::Reg(index.clone())), scale),
+        AOprnd::Data(name) => format!("{}(%rip)", name),
         AOprnd::Imm(val) => format!("${}", val).to_string(),
         _ => "Should Not Exist".to_string(),
     }
@@ -150,16 +383,27 @@ fn get_operand(op: &AOprnd) -> String {
 
 fn get_unary_operator(op: &AUnaryOp) -> String {
     match op {
-        AUnaryOp::Neg => "negl".to_string(),
-        AUnaryOp::Not => "notl".to_string(),
+        AUnaryOp::Neg => "neg".to_string(),
+        AUnaryOp::Not => "not".to_string(),
+        AUnaryOp::Shr => "shr".to_string(),
     }
 }
 
-fn get_binary_operator(op: &ABinaryOp) -> String {
+fn get_binary_operator(op: &ABinaryOp, ty: &AType) -> String {
+    let suffix = get_suffix(ty);
     match op {
-        ABinaryOp::Add => "addl".to_string(),
-        ABinaryOp::Sub => "subl".to_string(),
-        ABinaryOp::Mult => "imull".to_string(),
+        ABinaryOp::Add => format!("add{}", suffix),
+        ABinaryOp::Sub => format!("sub{}", suffix),
+        ABinaryOp::Mult if *ty == AType::Double => "mulsd".to_string(),
+        ABinaryOp::Mult => format!("imul{}", suffix),
+        ABinaryOp::DivDouble => "divsd".to_string(),
+        ABinaryOp::And => format!("and{}", suffix),
+        ABinaryOp::Or => format!("or{}", suffix),
+        ABinaryOp::Xor if *ty == AType::Double => "xorpd".to_string(),
+        ABinaryOp::Xor => format!("xor{}", suffix),
+        ABinaryOp::Sal => format!("sal{}", suffix),
+        ABinaryOp::Sar => format!("sar{}", suffix),
+        ABinaryOp::Shr => format!("shr{}", suffix),
     }
 }
 
@@ -1,20 +1,52 @@
+use crate::resolver::type_checker::StaticInit;
+
 
 #[derive(Debug)]
 #[derive(Clone)]
 pub enum AReg {
     AX,
-    R10,
+    CX,
     DX,
+    DI,
+    SI,
+    R8,
+    R9,
+    R10,
     R11,
+    SP,
+    XMM0,
+    XMM1,
+    XMM2,
+    XMM3,
+    XMM4,
+    XMM5,
+    XMM6,
+    XMM7,
+    XMM14,
+    XMM15,
 }
 
 #[derive(Debug)]
 #[derive(Clone)]
 pub enum AOprnd {
-    Imm(i32),
+    Imm(i64),
     Reg(AReg),
     Pseudo(String),
     Stack(i32),
+    Memory(AReg, i32),
+    Indexed(AReg, AReg, i32),
+    PseudoMem(String, i32),
+    Data(String),
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum AType {
+    Byte,
+    Longword,
+    Quadword,
+    Double,
 }
 
 #[derive(Debug)]
@@ -26,6 +58,11 @@ pub enum CondCode {
     GE,
     L,
     LE,
+    A,
+    AE,
+    B,
+    BE,
+    P,
 }
 
 #[derive(Debug)]
@@ -33,6 +70,7 @@ pub enum CondCode {
 pub enum AUnaryOp {
     Neg,
     Not,
+    Shr,
 }
 
 #[derive(Debug)]
@@ -41,26 +79,57 @@ pub enum ABinaryOp {
     Add,
     Sub,
     Mult,
+    DivDouble,
+    And,
+    Or,
+    Xor,
+    Sal,
+    Sar,
+    Shr,
 }
 
 #[derive(Debug)]
 #[derive(Clone)]
 pub enum AInstr {
-    Mov(AOprnd, AOprnd),
-    Unary(AUnaryOp, AOprnd),
-    Binary(ABinaryOp, AOprnd, AOprnd),
-    Cmp(AOprnd, AOprnd),
-    Idiv(AOprnd),
-    Cdq,
+    Mov(AType, AOprnd, AOprnd),
+    Movsx(AType, AType, AOprnd, AOprnd),
+    MovZeroExtend(AType, AType, AOprnd, AOprnd),
+    Cvttsd2si(AType, AOprnd, AOprnd),
+    Cvtsi2sd(AType, AOprnd, AOprnd),
+    Lea(AOprnd, AOprnd),
+    Unary(AUnaryOp, AType, AOprnd),
+    Binary(ABinaryOp, AType, AOprnd, AOprnd),
+    Cmp(AType, AOprnd, AOprnd),
+    Idiv(AType, AOprnd),
+    Div(AType, AOprnd),
+    Cdq(AType),
     Jmp(String),
+    JmpIndirect(AOprnd),
     JmpCC(CondCode, String),
     SetCC(CondCode, AOprnd),
     Label(String),
+    JumpTable(String, Vec<String>),
     AllocateStack(i32),
-    Ret
+    DeallocateStack(i32),
+    Push(AOprnd),
+    Call(String),
+    Ret,
+    Ud2,
 }
 
 #[derive(Debug)]
 pub enum AssemFuncDef {
-    Function(String, Vec<AInstr>),
+    Function(String, bool, Vec<AInstr>),
+}
+
+#[derive(Debug)]
+pub enum AssemTopLevel {
+    Func(AssemFuncDef),
+    StaticVar(String, bool, i32, Vec<StaticInit>),
+    StaticConst(String, i32, StaticInit),
+}
+
+#[derive(Debug)]
+pub enum AssemProgram {
+    Program(Vec<AssemTopLevel>),
 }
\ No newline at end of file
@@ -1,110 +1,778 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::code_gen::a_ast::*;
 use crate::code_gen::rep_pseudo_reg::rep_pseudo_regs;
 use crate::code_gen::rep_invalid_instrs::rep_instrs;
 use crate::tacky::t_ast::*;
+use crate::parser::ast::{Const, Type};
+use crate::resolver::type_checker::{struct_def, type_alignment, type_size, StaticInit, Symbol, SymbolTable};
 
-pub fn gen_code(ast: TFuncDef) -> AssemFuncDef {
-    let mut assembly_tree = function_decl(&ast);
-    let stack_size = rep_pseudo_regs(&mut assembly_tree);
-    rep_instrs(&mut assembly_tree, stack_size);
+const ARG_REGS: [AReg; 6] = [AReg::DI, AReg::SI, AReg::DX, AReg::CX, AReg::R8, AReg::R9];
+const DOUBLE_ARG_REGS: [AReg; 8] = [AReg::XMM0, AReg::XMM1, AReg::XMM2, AReg::XMM3, AReg::XMM4, AReg::XMM5,
+    AReg::XMM6, AReg::XMM7];
+const RETURN_REGS: [AReg; 2] = [AReg::AX, AReg::DX];
+const DOUBLE_RETURN_REGS: [AReg; 2] = [AReg::XMM0, AReg::XMM1];
 
-    assembly_tree
+//Where a function returning a structure in memory keeps the address its caller passed in %rdi
+const RETURN_PTR: AOprnd = AOprnd::Stack(-8);
+
+static LABEL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+//Floating point constants referenced by the generated code: (label, value, alignment)
+type DoubleConsts = Vec<(String, f64, i32)>;
+
+//A register's worth of an argument: a whole scalar, or the (offset, size) eightbyte of a structure or union
+type ArgPart = (TVal, Option<(i32, i32)>);
+
+//System V classes of the eightbytes of a structure or union
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+enum ArgClass {
+    Memory,
+    Integer,
+    Sse,
 }
 
-fn function_decl(ast: &TFuncDef) -> AssemFuncDef {
+pub fn gen_code(ast: TProgram, symbols: &SymbolTable) -> AssemProgram {
+    let mut top_level: Vec<AssemTopLevel> = Vec::new();
+    let mut consts: DoubleConsts = Vec::new();
+
     match ast {
-        TFuncDef::Function(identifier, body) => AssemFuncDef::Function(identifier.to_string(), instructions(&body)),
+        TProgram::Program(items) => {
+            for item in items.iter() {
+                match item {
+                    TTopLevel::Func(tfunction) => {
+                        let mut assembly_tree = function_decl(tfunction, symbols, &mut consts);
+                        let TFuncDef::Function(name, _, _, _) = tfunction;
+                        let reserved = if returns_in_memory(name, symbols) { 8 } else { 0 };
+                        let stack_size = rep_pseudo_regs(&mut assembly_tree, reserved, symbols);
+                        rep_instrs(&mut assembly_tree, stack_size);
+                        top_level.push(AssemTopLevel::Func(assembly_tree));
+                    },
+                    TTopLevel::StaticVar(name, global, var_type, inits) => {
+                        let alignment = var_alignment(var_type, symbols);
+                        top_level.push(AssemTopLevel::StaticVar(name.clone(), *global, alignment, inits.clone()));
+                    },
+                    TTopLevel::StaticConst(name, var_type, init) => {
+                        top_level.push(AssemTopLevel::StaticConst(name.clone(), var_alignment(var_type, symbols), init.clone()));
+                    },
+                }
+            }
+        }
     }
+
+    for (name, val, alignment) in consts.into_iter() {
+        top_level.push(AssemTopLevel::StaticConst(name, alignment, StaticInit::Double(val)));
+    }
+
+    AssemProgram::Program(top_level)
 }
 
-fn instructions(body: &Vec<TInstr>) -> Vec<AInstr> {
+fn function_decl(ast: &TFuncDef, symbols: &SymbolTable, consts: &mut DoubleConsts) -> AssemFuncDef {
+    match ast {
+        TFuncDef::Function(identifier, global, params, body) =>
+            AssemFuncDef::Function(identifier.to_string(), *global, instructions(identifier, params, body, symbols, consts)),
+    }
+}
+
+fn instructions(name: &str, params: &[String], body: &[TInstr], symbols: &SymbolTable, consts: &mut DoubleConsts)
+    -> Vec<AInstr> {
     let mut instructions: Vec<AInstr> = Vec::new();
 
+    let hidden_ptr = returns_in_memory(name, symbols);
+    if hidden_ptr {
+        instructions.push(AInstr::Mov(AType::Quadword, AOprnd::Reg(AReg::DI), RETURN_PTR));
+    }
+
+    let param_vals: Vec<TVal> = params.iter().map(|param| TVal::Var(param.clone())).collect();
+    let (int_params, double_params, stack_params) = classify_params(&param_vals, hidden_ptr, symbols);
+
+    for (param, reg) in int_params.iter().zip(ARG_REGS[hidden_ptr as usize..].iter()) {
+        store_part(reg, param, &mut instructions, symbols, consts);
+    }
+    for (param, reg) in double_params.iter().zip(DOUBLE_ARG_REGS.iter()) {
+        instructions.push(AInstr::Mov(AType::Double, AOprnd::Reg(reg.clone()), part_operand(param, consts)));
+    }
+    let mut offset = 16;
+    for param in stack_params.iter() {
+        move_value(param, AOprnd::Stack(offset), operand(param, consts), &mut instructions, symbols);
+        offset += stack_bytes(param, symbols);
+    }
+
     for tinstr in body.iter() {
-        instruction(&tinstr, &mut instructions);
+        instruction(tinstr, &mut instructions, symbols, consts);
     }
 
     instructions
 }
 
-fn instruction(instr: &TInstr, ainstrs: &mut Vec<AInstr>) {
+//A structure or union only goes in registers if all of its eightbytes fit, otherwise it is copied to the stack whole
+fn classify_params(vals: &[TVal], hidden_ptr: bool, symbols: &SymbolTable) -> (Vec<ArgPart>, Vec<ArgPart>, Vec<TVal>) {
+    let int_regs = ARG_REGS.len() - hidden_ptr as usize;
+    let mut int_vals: Vec<ArgPart> = Vec::new();
+    let mut double_vals: Vec<ArgPart> = Vec::new();
+    let mut stack_vals: Vec<TVal> = Vec::new();
+
+    for val in vals.iter() {
+        if is_aggregate(val, symbols) {
+            let classes = aggregate_classes(val, symbols);
+            let (int_parts, double_parts) = eightbyte_parts(val, &classes, symbols);
+            if classes[0] != ArgClass::Memory && int_vals.len() + int_parts.len() <= int_regs
+                && double_vals.len() + double_parts.len() <= DOUBLE_ARG_REGS.len() {
+                int_vals.extend(int_parts);
+                double_vals.extend(double_parts);
+            } else {
+                stack_vals.push(val.clone());
+            }
+        } else if val_type(val, symbols) == AType::Double && double_vals.len() < DOUBLE_ARG_REGS.len() {
+            double_vals.push((val.clone(), None));
+        } else if val_type(val, symbols) != AType::Double && int_vals.len() < int_regs {
+            int_vals.push((val.clone(), None));
+        } else {
+            stack_vals.push(val.clone());
+        }
+    }
+
+    (int_vals, double_vals, stack_vals)
+}
+
+//Structures over 16 bytes go in memory, otherwise each eightbyte is SSE if it holds only doubles and INTEGER if not
+fn aggregate_classes(val: &TVal, symbols: &SymbolTable) -> Vec<ArgClass> {
+    let size = val_size(val, symbols);
+    if size > 16 {
+        return vec![ArgClass::Memory];
+    }
+
+    let mut scalars: Vec<(i32, Type)> = Vec::new();
+    if let TVal::Var(ident) = val && let Some(symbol) = symbols.get(ident) {
+        scalar_members(&symbol.sym_type, 0, symbols, &mut scalars);
+    }
+
+    (0..(size + 7) / 8).map(|eightbyte| {
+        let mut members = scalars.iter().filter(|(offset, _)| offset / 8 == eightbyte).peekable();
+        if members.peek().is_some() && members.all(|(_, member_type)| *member_type == Type::Double) {
+            ArgClass::Sse
+        } else {
+            ArgClass::Integer
+        }
+    }).collect()
+}
+
+//Flattens nested structures, unions and arrays into the offsets and types of their scalar members
+fn scalar_members(var_type: &Type, offset: i32, symbols: &SymbolTable, scalars: &mut Vec<(i32, Type)>) {
+    match var_type {
+        Type::Structure(tag) | Type::Union(tag) => {
+            for member in struct_def(tag, symbols).members.iter() {
+                scalar_members(&member.member_type, offset + member.offset, symbols, scalars);
+            }
+        },
+        Type::Array(elem, count) => {
+            let elem_size = type_size(elem, symbols);
+            for i in 0..*count {
+                scalar_members(elem, offset + i * elem_size, symbols, scalars);
+            }
+        },
+        _ => scalars.push((offset, var_type.clone())),
+    }
+}
+
+//Splits a structure or union passed in registers into its INTEGER and SSE eightbytes
+fn eightbyte_parts(val: &TVal, classes: &[ArgClass], symbols: &SymbolTable) -> (Vec<ArgPart>, Vec<ArgPart>) {
+    let size = val_size(val, symbols);
+    let mut int_parts: Vec<ArgPart> = Vec::new();
+    let mut double_parts: Vec<ArgPart> = Vec::new();
+
+    for (i, class) in classes.iter().enumerate() {
+        let offset = 8 * i as i32;
+        let part = (val.clone(), Some((offset, (size - offset).min(8))));
+        if *class == ArgClass::Sse {
+            double_parts.push(part);
+        } else {
+            int_parts.push(part);
+        }
+    }
+
+    (int_parts, double_parts)
+}
+
+fn returns_in_memory(name: &str, symbols: &SymbolTable) -> bool {
+    match symbols.get(name) {
+        Some(Symbol { sym_type: Type::Function(_, ret_type), .. }) =>
+            matches!(**ret_type, Type::Structure(_) | Type::Union(_)) && type_size(ret_type, symbols) > 16,
+        _ => false,
+    }
+}
+
+fn part_operand(part: &ArgPart, consts: &mut DoubleConsts) -> AOprnd {
+    match part {
+        (val, Some((offset, _))) => offset_operand(&operand(val, consts), *offset),
+        (val, None) => operand(val, consts),
+    }
+}
+
+//The last eightbyte of a structure may be partial, so it is assembled a byte at a time rather than read past the end
+fn load_part(part: &ArgPart, reg: &AReg, ainstrs: &mut Vec<AInstr>, symbols: &SymbolTable, consts: &mut DoubleConsts) {
+    let src = part_operand(part, consts);
+    match part {
+        (_, Some((_, 8))) => ainstrs.push(AInstr::Mov(AType::Quadword, src, AOprnd::Reg(reg.clone()))),
+        (_, Some((_, 4))) => ainstrs.push(AInstr::Mov(AType::Longword, src, AOprnd::Reg(reg.clone()))),
+        (_, Some((_, size))) => {
+            for i in (0..*size).rev() {
+                ainstrs.push(AInstr::Mov(AType::Byte, offset_operand(&src, i), AOprnd::Reg(reg.clone())));
+                if i > 0 {
+                    ainstrs.push(AInstr::Binary(ABinaryOp::Sal, AType::Quadword, AOprnd::Imm(8), AOprnd::Reg(reg.clone())));
+                }
+            }
+        },
+        (val, None) => ainstrs.push(AInstr::Mov(val_type(val, symbols), src, AOprnd::Reg(reg.clone()))),
+    }
+}
+
+fn store_part(reg: &AReg, part: &ArgPart, ainstrs: &mut Vec<AInstr>, symbols: &SymbolTable, consts: &mut DoubleConsts) {
+    let dst = part_operand(part, consts);
+    match part {
+        (_, Some((_, 8))) => ainstrs.push(AInstr::Mov(AType::Quadword, AOprnd::Reg(reg.clone()), dst)),
+        (_, Some((_, 4))) => ainstrs.push(AInstr::Mov(AType::Longword, AOprnd::Reg(reg.clone()), dst)),
+        (_, Some((_, size))) => {
+            for i in 0..*size {
+                ainstrs.push(AInstr::Mov(AType::Byte, AOprnd::Reg(reg.clone()), offset_operand(&dst, i)));
+                if i < size - 1 {
+                    ainstrs.push(AInstr::Binary(ABinaryOp::Shr, AType::Quadword, AOprnd::Imm(8), AOprnd::Reg(reg.clone())));
+                }
+            }
+        },
+        (val, None) => ainstrs.push(AInstr::Mov(val_type(val, symbols), AOprnd::Reg(reg.clone()), dst)),
+    }
+}
+
+//Arguments on the stack each take a multiple of eight bytes
+fn stack_bytes(val: &TVal, symbols: &SymbolTable) -> i32 {
+    if is_aggregate(val, symbols) {
+        (val_size(val, symbols) + 7) / 8 * 8
+    } else {
+        8
+    }
+}
+
+fn instruction(instr: &TInstr, ainstrs: &mut Vec<AInstr>, symbols: &SymbolTable, consts: &mut DoubleConsts) {
     match instr {
+        TInstr::Return(val) if is_aggregate(val, symbols) => {
+            return_aggregate(val, ainstrs, symbols, consts);
+            ainstrs.push(AInstr::Ret);
+        },
         TInstr::Return(val) => {
-            ainstrs.push(AInstr::Mov(operand(&val), AOprnd::Reg(AReg::AX)));
+            let ret_type = val_type(val, symbols);
+            let ret_reg = if ret_type == AType::Double { AReg::XMM0 } else { AReg::AX };
+            ainstrs.push(AInstr::Mov(ret_type, operand(val, consts), AOprnd::Reg(ret_reg)));
             ainstrs.push(AInstr::Ret);
         },
-        TInstr::Unary(op, src, dst) => unary(&op, &src, &dst, ainstrs),
-        TInstr::Binary(op, src1, src2, dst) => binary(&op, &src1, &src2, &dst, ainstrs),
+        TInstr::Unary(op, src, dst) => unary(op, src, dst, ainstrs, symbols, consts),
+        TInstr::Binary(op, src1, src2, dst) => binary(op, src1, src2, dst, ainstrs, symbols, consts),
+        TInstr::JumpIfZero(val, target) if val_type(val, symbols) == AType::Double => {
+            let end = make_label("nan_skip");
+            compare_double_zero(val, ainstrs, consts);
+            ainstrs.push(AInstr::JmpCC(CondCode::P, end.clone()));
+            ainstrs.push(AInstr::JmpCC(CondCode::E, target.clone()));
+            ainstrs.push(AInstr::Label(end));
+        },
+        TInstr::JumpIfNotZero(val, target) if val_type(val, symbols) == AType::Double => {
+            compare_double_zero(val, ainstrs, consts);
+            ainstrs.push(AInstr::JmpCC(CondCode::P, target.clone()));
+            ainstrs.push(AInstr::JmpCC(CondCode::NE, target.clone()));
+        },
         TInstr::JumpIfZero(val, target) => {
-            ainstrs.push(AInstr::Cmp(AOprnd::Imm(0), operand(&val)));
+            ainstrs.push(AInstr::Cmp(val_type(val, symbols), AOprnd::Imm(0), operand(val, consts)));
             ainstrs.push(AInstr::JmpCC(CondCode::E, target.clone()));
         },
         TInstr::JumpIfNotZero(val, target) => {
-            ainstrs.push(AInstr::Cmp(AOprnd::Imm(0), operand(&val)));
+            ainstrs.push(AInstr::Cmp(val_type(val, symbols), AOprnd::Imm(0), operand(val, consts)));
             ainstrs.push(AInstr::JmpCC(CondCode::NE, target.clone()));
         },
         TInstr::Jump(target) => ainstrs.push(AInstr::Jmp(target.clone())),
-        TInstr::Copy(src, dst) => ainstrs.push(AInstr::Mov(operand(&src), operand(&dst))),
-        TInstr::Label(val) => ainstrs.push(AInstr::Label(val.clone()))
+        TInstr::Copy(src, dst) if is_aggregate(src, symbols) =>
+            copy_bytes(operand(src, consts), operand(dst, consts), val_size(src, symbols), ainstrs),
+        TInstr::Copy(src, dst) =>
+            ainstrs.push(AInstr::Mov(val_type(src, symbols), operand(src, consts), operand(dst, consts))),
+        TInstr::Label(val) => ainstrs.push(AInstr::Label(val.clone())),
+        TInstr::FunCall(name, args, dst) => fun_call(name, args, dst, ainstrs, symbols, consts),
+        TInstr::SignExtend(src, dst) => ainstrs.push(AInstr::Movsx(val_type(src, symbols), val_type(dst, symbols),
+            operand(src, consts), operand(dst, consts))),
+        TInstr::Truncate(src, dst) =>
+            ainstrs.push(AInstr::Mov(val_type(dst, symbols), operand(src, consts), operand(dst, consts))),
+        TInstr::ZeroExtend(src, dst) => ainstrs.push(AInstr::MovZeroExtend(val_type(src, symbols), val_type(dst, symbols),
+            operand(src, consts), operand(dst, consts))),
+        TInstr::IntToDouble(src, dst) if val_type(src, symbols) == AType::Byte => {
+            ainstrs.push(AInstr::Movsx(AType::Byte, AType::Longword, operand(src, consts), AOprnd::Reg(AReg::AX)));
+            ainstrs.push(AInstr::Cvtsi2sd(AType::Longword, AOprnd::Reg(AReg::AX), operand(dst, consts)));
+        },
+        TInstr::IntToDouble(src, dst) =>
+            ainstrs.push(AInstr::Cvtsi2sd(val_type(src, symbols), operand(src, consts), operand(dst, consts))),
+        TInstr::DoubleToInt(src, dst) if val_type(dst, symbols) == AType::Byte => {
+            ainstrs.push(AInstr::Cvttsd2si(AType::Longword, operand(src, consts), AOprnd::Reg(AReg::AX)));
+            ainstrs.push(AInstr::Mov(AType::Byte, AOprnd::Reg(AReg::AX), operand(dst, consts)));
+        },
+        TInstr::DoubleToInt(src, dst) =>
+            ainstrs.push(AInstr::Cvttsd2si(val_type(dst, symbols), operand(src, consts), operand(dst, consts))),
+        TInstr::UIntToDouble(src, dst) => uint_to_double(src, dst, ainstrs, symbols, consts),
+        TInstr::GetAddress(src, dst) => ainstrs.push(AInstr::Lea(operand(src, consts), operand(dst, consts))),
+        TInstr::Load(ptr, dst) => {
+            ainstrs.push(AInstr::Mov(AType::Quadword, operand(ptr, consts), AOprnd::Reg(AReg::AX)));
+            move_value(dst, AOprnd::Memory(AReg::AX, 0), operand(dst, consts), ainstrs, symbols);
+        },
+        TInstr::Store(src, ptr) => {
+            ainstrs.push(AInstr::Mov(AType::Quadword, operand(ptr, consts), AOprnd::Reg(AReg::AX)));
+            move_value(src, operand(src, consts), AOprnd::Memory(AReg::AX, 0), ainstrs, symbols);
+        },
+        TInstr::DoubleToUInt(src, dst) => double_to_uint(src, dst, ainstrs, symbols, consts),
+        TInstr::AddPtr(ptr, index, scale, dst) => add_ptr(ptr, index, *scale, dst, ainstrs, consts),
+        TInstr::CopyToOffset(src, dst, offset) => {
+            let dst_mem = AOprnd::PseudoMem(dst.clone(), *offset);
+            move_value(src, operand(src, consts), dst_mem, ainstrs, symbols);
+        },
+        TInstr::CopyFromOffset(src, offset, dst) => {
+            let src_mem = AOprnd::PseudoMem(src.clone(), *offset);
+            move_value(dst, src_mem, operand(dst, consts), ainstrs, symbols);
+        },
+        TInstr::JumpTable(val, min, targets, default) => jump_table(val, *min, targets, default, ainstrs, symbols, consts),
+        TInstr::Trap => ainstrs.push(AInstr::Ud2),
     };
 }
 
-fn unary(op: &TUnaryOp, src: &TVal, dst: &TVal, ainstrs: &mut Vec<AInstr>) {
+//A structure in memory is copied through the caller's pointer, which is also returned in %rax
+fn return_aggregate(val: &TVal, ainstrs: &mut Vec<AInstr>, symbols: &SymbolTable, consts: &mut DoubleConsts) {
+    let classes = aggregate_classes(val, symbols);
+    if classes[0] == ArgClass::Memory {
+        ainstrs.push(AInstr::Mov(AType::Quadword, RETURN_PTR, AOprnd::Reg(AReg::AX)));
+        copy_bytes(operand(val, consts), AOprnd::Memory(AReg::AX, 0), val_size(val, symbols), ainstrs);
+        return;
+    }
+
+    let (int_parts, double_parts) = eightbyte_parts(val, &classes, symbols);
+    for (part, reg) in int_parts.iter().zip(RETURN_REGS.iter()) {
+        load_part(part, reg, ainstrs, symbols, consts);
+    }
+    for (part, reg) in double_parts.iter().zip(DOUBLE_RETURN_REGS.iter()) {
+        ainstrs.push(AInstr::Mov(AType::Double, part_operand(part, consts), AOprnd::Reg(reg.clone())));
+    }
+}
+
+//Table entries hold each target's offset from the table itself, so the jump works in position independent code
+fn jump_table(val: &TVal, min: i64, targets: &[String], default: &str, ainstrs: &mut Vec<AInstr>, symbols: &SymbolTable,
+    consts: &mut DoubleConsts) {
+    let table = format!(".L{}", make_label("jump_table"));
+
+    //Widening to 64 bits first makes values below the minimum wrap around and fail the unsigned bounds check
+    match val_type(val, symbols) {
+        AType::Quadword => ainstrs.push(AInstr::Mov(AType::Quadword, operand(val, consts), AOprnd::Reg(AReg::AX))),
+        _ if is_signed(val, symbols) =>
+            ainstrs.push(AInstr::Movsx(AType::Longword, AType::Quadword, operand(val, consts), AOprnd::Reg(AReg::AX))),
+        _ => ainstrs.push(AInstr::MovZeroExtend(AType::Longword, AType::Quadword, operand(val, consts), AOprnd::Reg(AReg::AX))),
+    }
+    if min != 0 {
+        ainstrs.push(AInstr::Binary(ABinaryOp::Sub, AType::Quadword, AOprnd::Imm(min), AOprnd::Reg(AReg::AX)));
+    }
+    ainstrs.push(AInstr::Cmp(AType::Quadword, AOprnd::Imm(targets.len() as i64 - 1), AOprnd::Reg(AReg::AX)));
+    ainstrs.push(AInstr::JmpCC(CondCode::A, default.to_string()));
+
+    ainstrs.push(AInstr::Lea(AOprnd::Data(table.clone()), AOprnd::Reg(AReg::DX)));
+    ainstrs.push(AInstr::Movsx(AType::Longword, AType::Quadword, AOprnd::Indexed(AReg::DX, AReg::AX, 4), AOprnd::Reg(AReg::AX)));
+    ainstrs.push(AInstr::Binary(ABinaryOp::Add, AType::Quadword, AOprnd::Reg(AReg::DX), AOprnd::Reg(AReg::AX)));
+    ainstrs.push(AInstr::JmpIndirect(AOprnd::Reg(AReg::AX)));
+    ainstrs.push(AInstr::JumpTable(table, targets.to_vec()));
+}
+
+//Moves a value of the type of val, which may be a structure or union too large for a single mov
+fn move_value(val: &TVal, src: AOprnd, dst: AOprnd, ainstrs: &mut Vec<AInstr>, symbols: &SymbolTable) {
+    if is_aggregate(val, symbols) {
+        copy_bytes(src, dst, val_size(val, symbols), ainstrs);
+    } else {
+        ainstrs.push(AInstr::Mov(val_type(val, symbols), src, dst));
+    }
+}
+
+//Copies in the largest chunks that fit, so a 12 byte structure takes one quadword and one longword mov
+fn copy_bytes(src: AOprnd, dst: AOprnd, size: i32, ainstrs: &mut Vec<AInstr>) {
+    let mut offset = 0;
+    while offset < size {
+        let (chunk_type, chunk_size) = match size - offset {
+            remaining if remaining >= 8 => (AType::Quadword, 8),
+            remaining if remaining >= 4 => (AType::Longword, 4),
+            _ => (AType::Byte, 1),
+        };
+        ainstrs.push(AInstr::Mov(chunk_type, offset_operand(&src, offset), offset_operand(&dst, offset)));
+        offset += chunk_size;
+    }
+}
+
+fn offset_operand(oprnd: &AOprnd, offset: i32) -> AOprnd {
+    match oprnd {
+        AOprnd::Pseudo(name) => AOprnd::PseudoMem(name.clone(), offset),
+        AOprnd::PseudoMem(name, base) => AOprnd::PseudoMem(name.clone(), base + offset),
+        AOprnd::Memory(reg, base) => AOprnd::Memory(reg.clone(), base + offset),
+        AOprnd::Stack(base) => AOprnd::Stack(base + offset),
+        _ => panic!("Operand cannot be addressed at an offset"),
+    }
+}
+
+fn add_ptr(ptr: &TVal, index: &TVal, scale: i32, dst: &TVal, ainstrs: &mut Vec<AInstr>, consts: &mut DoubleConsts) {
+    ainstrs.push(AInstr::Mov(AType::Quadword, operand(ptr, consts), AOprnd::Reg(AReg::AX)));
+
+    match operand(index, consts) {
+        AOprnd::Imm(val) => {
+            let offset = (val * scale as i64) as i32;
+            ainstrs.push(AInstr::Lea(AOprnd::Memory(AReg::AX, offset), operand(dst, consts)));
+        },
+        index_oprnd => {
+            ainstrs.push(AInstr::Mov(AType::Quadword, index_oprnd, AOprnd::Reg(AReg::DX)));
+            //Indexed addressing only supports scales of 1, 2, 4 and 8
+            let index_scale = if let 1 | 2 | 4 | 8 = scale {
+                scale
+            } else {
+                ainstrs.push(AInstr::Binary(ABinaryOp::Mult, AType::Quadword, AOprnd::Imm(scale as i64), AOprnd::Reg(AReg::DX)));
+                1
+            };
+            ainstrs.push(AInstr::Lea(AOprnd::Indexed(AReg::AX, AReg::DX, index_scale), operand(dst, consts)));
+        },
+    }
+}
+
+fn compare_double_zero(val: &TVal, ainstrs: &mut Vec<AInstr>, consts: &mut DoubleConsts) {
+    ainstrs.push(AInstr::Binary(ABinaryOp::Xor, AType::Double, AOprnd::Reg(AReg::XMM0), AOprnd::Reg(AReg::XMM0)));
+    ainstrs.push(AInstr::Cmp(AType::Double, operand(val, consts), AOprnd::Reg(AReg::XMM0)));
+}
+
+fn uint_to_double(src: &TVal, dst: &TVal, ainstrs: &mut Vec<AInstr>, symbols: &SymbolTable, consts: &mut DoubleConsts) {
+    let src_type = val_type(src, symbols);
+    if src_type == AType::Byte {
+        ainstrs.push(AInstr::MovZeroExtend(AType::Byte, AType::Longword, operand(src, consts), AOprnd::Reg(AReg::AX)));
+        ainstrs.push(AInstr::Cvtsi2sd(AType::Longword, AOprnd::Reg(AReg::AX), operand(dst, consts)));
+        return;
+    }
+    if src_type == AType::Longword {
+        ainstrs.push(AInstr::MovZeroExtend(AType::Longword, AType::Quadword, operand(src, consts), AOprnd::Reg(AReg::AX)));
+        ainstrs.push(AInstr::Cvtsi2sd(AType::Quadword, AOprnd::Reg(AReg::AX), operand(dst, consts)));
+        return;
+    }
+
+    //Values above the signed range are halved (keeping the low bit for rounding), converted and doubled
+    let out_of_range = make_label("ulong_out_of_range");
+    let end = make_label("ulong_to_double_end");
+    ainstrs.push(AInstr::Cmp(AType::Quadword, AOprnd::Imm(0), operand(src, consts)));
+    ainstrs.push(AInstr::JmpCC(CondCode::L, out_of_range.clone()));
+    ainstrs.push(AInstr::Cvtsi2sd(AType::Quadword, operand(src, consts), operand(dst, consts)));
+    ainstrs.push(AInstr::Jmp(end.clone()));
+    ainstrs.push(AInstr::Label(out_of_range));
+    ainstrs.push(AInstr::Mov(AType::Quadword, operand(src, consts), AOprnd::Reg(AReg::AX)));
+    ainstrs.push(AInstr::Mov(AType::Quadword, AOprnd::Reg(AReg::AX), AOprnd::Reg(AReg::DX)));
+    ainstrs.push(AInstr::Unary(AUnaryOp::Shr, AType::Quadword, AOprnd::Reg(AReg::DX)));
+    ainstrs.push(AInstr::Binary(ABinaryOp::And, AType::Quadword, AOprnd::Imm(1), AOprnd::Reg(AReg::AX)));
+    ainstrs.push(AInstr::Binary(ABinaryOp::Or, AType::Quadword, AOprnd::Reg(AReg::AX), AOprnd::Reg(AReg::DX)));
+    ainstrs.push(AInstr::Cvtsi2sd(AType::Quadword, AOprnd::Reg(AReg::DX), operand(dst, consts)));
+    ainstrs.push(AInstr::Binary(ABinaryOp::Add, AType::Double, operand(dst, consts), operand(dst, consts)));
+    ainstrs.push(AInstr::Label(end));
+}
+
+fn double_to_uint(src: &TVal, dst: &TVal, ainstrs: &mut Vec<AInstr>, symbols: &SymbolTable, consts: &mut DoubleConsts) {
+    let dst_type = val_type(dst, symbols);
+    if dst_type == AType::Byte || dst_type == AType::Longword {
+        ainstrs.push(AInstr::Cvttsd2si(AType::Quadword, operand(src, consts), AOprnd::Reg(AReg::AX)));
+        ainstrs.push(AInstr::Mov(dst_type, AOprnd::Reg(AReg::AX), operand(dst, consts)));
+        return;
+    }
+
+    //Values at or above 2^63 are shifted into the signed range before converting
+    let upper_bound = double_const(9223372036854775808.0, 8, consts);
+    let out_of_range = make_label("double_out_of_range");
+    let end = make_label("double_to_ulong_end");
+    ainstrs.push(AInstr::Cmp(AType::Double, upper_bound.clone(), operand(src, consts)));
+    ainstrs.push(AInstr::JmpCC(CondCode::AE, out_of_range.clone()));
+    ainstrs.push(AInstr::Cvttsd2si(AType::Quadword, operand(src, consts), operand(dst, consts)));
+    ainstrs.push(AInstr::Jmp(end.clone()));
+    ainstrs.push(AInstr::Label(out_of_range));
+    ainstrs.push(AInstr::Mov(AType::Double, operand(src, consts), AOprnd::Reg(AReg::XMM1)));
+    ainstrs.push(AInstr::Binary(ABinaryOp::Sub, AType::Double, upper_bound, AOprnd::Reg(AReg::XMM1)));
+    ainstrs.push(AInstr::Cvttsd2si(AType::Quadword, AOprnd::Reg(AReg::XMM1), operand(dst, consts)));
+    ainstrs.push(AInstr::Mov(AType::Quadword, AOprnd::Imm(i64::MIN), AOprnd::Reg(AReg::AX)));
+    ainstrs.push(AInstr::Binary(ABinaryOp::Add, AType::Quadword, AOprnd::Reg(AReg::AX), operand(dst, consts)));
+    ainstrs.push(AInstr::Label(end));
+}
+
+fn fun_call(name: &str, args: &[TVal], dst: &TVal, ainstrs: &mut Vec<AInstr>, symbols: &SymbolTable,
+    consts: &mut DoubleConsts) {
+    let dst_classes = if is_aggregate(dst, symbols) { aggregate_classes(dst, symbols) } else { Vec::new() };
+    let hidden_ptr = dst_classes.first() == Some(&ArgClass::Memory);
+    let (int_args, double_args, stack_args) = classify_params(args, hidden_ptr, symbols);
+
+    let stack_size: i32 = stack_args.iter().map(|arg| stack_bytes(arg, symbols)).sum();
+    let padding = if stack_size % 16 != 0 { 8 } else { 0 };
+    if padding != 0 {
+        ainstrs.push(AInstr::AllocateStack(padding));
+    }
+
+    if hidden_ptr {
+        ainstrs.push(AInstr::Lea(operand(dst, consts), AOprnd::Reg(AReg::DI)));
+    }
+
+    for (arg, reg) in int_args.iter().zip(ARG_REGS[hidden_ptr as usize..].iter()) {
+        load_part(arg, reg, ainstrs, symbols, consts);
+    }
+
+    for (arg, reg) in double_args.iter().zip(DOUBLE_ARG_REGS.iter()) {
+        ainstrs.push(AInstr::Mov(AType::Double, part_operand(arg, consts), AOprnd::Reg(reg.clone())));
+    }
+
+    for arg in stack_args.iter().rev() {
+        let arg_type = val_type(arg, symbols);
+        match operand(arg, consts) {
+            oprnd if is_aggregate(arg, symbols) => {
+                ainstrs.push(AInstr::AllocateStack(stack_bytes(arg, symbols)));
+                copy_bytes(oprnd, AOprnd::Memory(AReg::SP, 0), val_size(arg, symbols), ainstrs);
+            },
+            oprnd @ (AOprnd::Imm(_) | AOprnd::Reg(_)) => ainstrs.push(AInstr::Push(oprnd)),
+            oprnd if arg_type == AType::Quadword || arg_type == AType::Double => ainstrs.push(AInstr::Push(oprnd)),
+            oprnd => {
+                ainstrs.push(AInstr::Mov(arg_type, oprnd, AOprnd::Reg(AReg::AX)));
+                ainstrs.push(AInstr::Push(AOprnd::Reg(AReg::AX)));
+            },
+        }
+    }
+
+    ainstrs.push(AInstr::Call(name.to_string()));
+
+    let bytes_to_remove = stack_size + padding;
+    if bytes_to_remove != 0 {
+        ainstrs.push(AInstr::DeallocateStack(bytes_to_remove));
+    }
+
+    if hidden_ptr {
+        return;
+    }
+    if !dst_classes.is_empty() {
+        let (int_parts, double_parts) = eightbyte_parts(dst, &dst_classes, symbols);
+        for (part, reg) in int_parts.iter().zip(RETURN_REGS.iter()) {
+            store_part(reg, part, ainstrs, symbols, consts);
+        }
+        for (part, reg) in double_parts.iter().zip(DOUBLE_RETURN_REGS.iter()) {
+            ainstrs.push(AInstr::Mov(AType::Double, AOprnd::Reg(reg.clone()), part_operand(part, consts)));
+        }
+        return;
+    }
+
+    let dst_type = val_type(dst, symbols);
+    let ret_reg = if dst_type == AType::Double { AReg::XMM0 } else { AReg::AX };
+    ainstrs.push(AInstr::Mov(dst_type, AOprnd::Reg(ret_reg), operand(dst, consts)));
+}
+
+fn unary(op: &TUnaryOp, src: &TVal, dst: &TVal, ainstrs: &mut Vec<AInstr>, symbols: &SymbolTable,
+    consts: &mut DoubleConsts) {
+    let src_type = val_type(src, symbols);
+    let dst_type = val_type(dst, symbols);
+
     match op {
+        TUnaryOp::Not if src_type == AType::Double => {
+            let end = make_label("nan_skip");
+            compare_double_zero(src, ainstrs, consts);
+            ainstrs.push(AInstr::Mov(dst_type, AOprnd::Imm(0), operand(dst, consts)));
+            ainstrs.push(AInstr::JmpCC(CondCode::P, end.clone()));
+            ainstrs.push(AInstr::SetCC(CondCode::E, operand(dst, consts)));
+            ainstrs.push(AInstr::Label(end));
+        },
         TUnaryOp::Not => {
-            ainstrs.push(AInstr::Cmp(AOprnd::Imm(0), operand(&src)));
-            ainstrs.push(AInstr::Mov(AOprnd::Imm(0), operand(&dst)));
-            ainstrs.push(AInstr::SetCC(CondCode::E, operand(&dst)));
+            ainstrs.push(AInstr::Cmp(src_type, AOprnd::Imm(0), operand(src, consts)));
+            ainstrs.push(AInstr::Mov(dst_type, AOprnd::Imm(0), operand(dst, consts)));
+            ainstrs.push(AInstr::SetCC(CondCode::E, operand(dst, consts)));
+        },
+        TUnaryOp::Negate if src_type == AType::Double => {
+            let negative_zero = double_const(-0.0, 16, consts);
+            ainstrs.push(AInstr::Mov(AType::Double, operand(src, consts), operand(dst, consts)));
+            ainstrs.push(AInstr::Binary(ABinaryOp::Xor, AType::Double, negative_zero, operand(dst, consts)));
         },
         _ => {
-            ainstrs.push(AInstr::Mov(operand(&src), operand(&dst)));
-            ainstrs.push(AInstr::Unary(unary_op(&op), operand(&dst)));
+            ainstrs.push(AInstr::Mov(src_type.clone(), operand(src, consts), operand(dst, consts)));
+            ainstrs.push(AInstr::Unary(unary_op(op), src_type, operand(dst, consts)));
         }
     }
 }
 
-fn binary(op: &TBinaryOp, src1: &TVal, src2: &TVal, dst: &TVal, ainstrs: &mut Vec<AInstr>) {
+fn binary(op: &TBinaryOp, src1: &TVal, src2: &TVal, dst: &TVal, ainstrs: &mut Vec<AInstr>, symbols: &SymbolTable,
+    consts: &mut DoubleConsts) {
+    let src_type = val_type(src1, symbols);
+
     match op {
-        TBinaryOp::Divide | TBinaryOp::Remainder => binary_div(op, src1, src2, dst, ainstrs),
-        TBinaryOp::GreatThan => conditional(CondCode::G, src1, src2, dst, ainstrs),
-        TBinaryOp::GreatEqual => conditional(CondCode::GE, src1, src2, dst, ainstrs),
-        TBinaryOp::LessThan => conditional(CondCode::L, src1, src2, dst, ainstrs),
-        TBinaryOp::LessEqual => conditional(CondCode::LE, src1, src2, dst, ainstrs),
-        TBinaryOp::Equal => conditional(CondCode::E, src1, src2, dst, ainstrs),
-        TBinaryOp::NotEqual => conditional(CondCode::NE, src1, src2, dst, ainstrs),
+        TBinaryOp::Divide if src_type == AType::Double => {
+            ainstrs.push(AInstr::Mov(AType::Double, operand(src1, consts), operand(dst, consts)));
+            ainstrs.push(AInstr::Binary(ABinaryOp::DivDouble, AType::Double, operand(src2, consts), operand(dst, consts)));
+        },
+        TBinaryOp::Divide | TBinaryOp::Remainder => binary_div(op, src1, src2, dst, ainstrs, symbols, consts),
+        TBinaryOp::GreatThan | TBinaryOp::GreatEqual | TBinaryOp::LessThan | TBinaryOp::LessEqual => {
+            let signed = src_type != AType::Double && is_signed(src1, symbols);
+            let code = relational_code(op, signed);
+            conditional(code, src1, src2, dst, ainstrs, symbols, consts)
+        },
+        TBinaryOp::Equal => conditional(CondCode::E, src1, src2, dst, ainstrs, symbols, consts),
+        TBinaryOp::NotEqual => conditional(CondCode::NE, src1, src2, dst, ainstrs, symbols, consts),
+        TBinaryOp::ShiftLeft | TBinaryOp::ShiftRight => {
+            //Variable shift counts have to be in %cl
+            let shift = match op {
+                TBinaryOp::ShiftRight if is_signed(src1, symbols) => ABinaryOp::Sar,
+                TBinaryOp::ShiftRight => ABinaryOp::Shr,
+                _ => ABinaryOp::Sal,
+            };
+            ainstrs.push(AInstr::Mov(src_type.clone(), operand(src1, consts), operand(dst, consts)));
+            ainstrs.push(AInstr::Mov(val_type(src2, symbols), operand(src2, consts), AOprnd::Reg(AReg::CX)));
+            ainstrs.push(AInstr::Binary(shift, src_type, AOprnd::Reg(AReg::CX), operand(dst, consts)));
+        },
         _ => {
-            ainstrs.push(AInstr::Mov(operand(&src1), operand(&dst)));
-            ainstrs.push(AInstr::Binary(binary_op(&op), operand(&src2), operand(&dst)));
+            ainstrs.push(AInstr::Mov(src_type.clone(), operand(src1, consts), operand(dst, consts)));
+            ainstrs.push(AInstr::Binary(binary_op(op), src_type, operand(src2, consts), operand(dst, consts)));
         }
     }
 }
 
-fn binary_div(op: &TBinaryOp, src1: &TVal, src2: &TVal, dst: &TVal, ainstrs: &mut Vec<AInstr>) {
-    ainstrs.push(AInstr::Mov(operand(&src1), AOprnd::Reg(AReg::AX)));
-    ainstrs.push(AInstr::Cdq);
-    ainstrs.push(AInstr::Idiv(operand(&src2)));
+fn binary_div(op: &TBinaryOp, src1: &TVal, src2: &TVal, dst: &TVal, ainstrs: &mut Vec<AInstr>, symbols: &SymbolTable,
+    consts: &mut DoubleConsts) {
+    let src_type = val_type(src1, symbols);
+    ainstrs.push(AInstr::Mov(src_type.clone(), operand(src1, consts), AOprnd::Reg(AReg::AX)));
+    if is_signed(src1, symbols) {
+        ainstrs.push(AInstr::Cdq(src_type.clone()));
+        ainstrs.push(AInstr::Idiv(src_type.clone(), operand(src2, consts)));
+    } else {
+        ainstrs.push(AInstr::Mov(src_type.clone(), AOprnd::Imm(0), AOprnd::Reg(AReg::DX)));
+        ainstrs.push(AInstr::Div(src_type.clone(), operand(src2, consts)));
+    }
 
     match op {
-        TBinaryOp::Divide => ainstrs.push(AInstr::Mov(AOprnd::Reg(AReg::AX), operand(&dst))),
-        TBinaryOp::Remainder => ainstrs.push(AInstr::Mov(AOprnd::Reg(AReg::DX), operand(&dst))),
+        TBinaryOp::Divide => ainstrs.push(AInstr::Mov(src_type, AOprnd::Reg(AReg::AX), operand(dst, consts))),
+        TBinaryOp::Remainder => ainstrs.push(AInstr::Mov(src_type, AOprnd::Reg(AReg::DX), operand(dst, consts))),
         _ => (),
     }
 }
 
-fn conditional(code: CondCode, src1: &TVal, src2: &TVal, dst: &TVal, ainstrs: &mut Vec<AInstr>) {
-    ainstrs.push(AInstr::Cmp(operand(src2), operand(src1)));
-    ainstrs.push(AInstr::Mov(AOprnd::Imm(0), operand(dst)));
-    ainstrs.push(AInstr::SetCC(code, operand(&dst)));
+fn relational_code(op: &TBinaryOp, signed: bool) -> CondCode {
+    match (op, signed) {
+        (TBinaryOp::GreatThan, true) => CondCode::G,
+        (TBinaryOp::GreatEqual, true) => CondCode::GE,
+        (TBinaryOp::LessThan, true) => CondCode::L,
+        (TBinaryOp::LessEqual, true) => CondCode::LE,
+        (TBinaryOp::GreatThan, false) => CondCode::A,
+        (TBinaryOp::GreatEqual, false) => CondCode::AE,
+        (TBinaryOp::LessThan, false) => CondCode::B,
+        (TBinaryOp::LessEqual, false) => CondCode::BE,
+        _ => panic!("Invalid binary op for relational condition code"),
+    }
+}
+
+fn conditional(code: CondCode, src1: &TVal, src2: &TVal, dst: &TVal, ainstrs: &mut Vec<AInstr>, symbols: &SymbolTable,
+    consts: &mut DoubleConsts) {
+    let src_type = val_type(src1, symbols);
+    let dst_type = val_type(dst, symbols);
+    ainstrs.push(AInstr::Cmp(src_type.clone(), operand(src2, consts), operand(src1, consts)));
+
+    //An unordered comparison sets ZF, CF and PF, so only the parity flag tells a NaN apart from equality
+    match code {
+        CondCode::E | CondCode::B | CondCode::BE if src_type == AType::Double => {
+            let end = make_label("nan_skip");
+            ainstrs.push(AInstr::Mov(dst_type, AOprnd::Imm(0), operand(dst, consts)));
+            ainstrs.push(AInstr::JmpCC(CondCode::P, end.clone()));
+            ainstrs.push(AInstr::SetCC(code, operand(dst, consts)));
+            ainstrs.push(AInstr::Label(end));
+        },
+        CondCode::NE if src_type == AType::Double => {
+            let end = make_label("nan_skip");
+            ainstrs.push(AInstr::Mov(dst_type, AOprnd::Imm(1), operand(dst, consts)));
+            ainstrs.push(AInstr::JmpCC(CondCode::P, end.clone()));
+            ainstrs.push(AInstr::SetCC(code, operand(dst, consts)));
+            ainstrs.push(AInstr::Label(end));
+        },
+        _ => {
+            ainstrs.push(AInstr::Mov(dst_type, AOprnd::Imm(0), operand(dst, consts)));
+            ainstrs.push(AInstr::SetCC(code, operand(dst, consts)));
+        },
+    }
 }
 
-fn operand(val: &TVal) -> AOprnd {
+fn operand(val: &TVal, consts: &mut DoubleConsts) -> AOprnd {
     match val {
-        TVal::Constant(val) => AOprnd::Imm(*val),
+        TVal::Constant(Const::Int(val)) => AOprnd::Imm(*val as i64),
+        TVal::Constant(Const::Long(val)) => AOprnd::Imm(*val),
+        TVal::Constant(Const::UInt(val)) => AOprnd::Imm(*val as i32 as i64),
+        TVal::Constant(Const::ULong(val)) => AOprnd::Imm(*val as i64),
+        TVal::Constant(Const::Double(val)) => double_const(*val, 8, consts),
+        TVal::Constant(Const::Char(val)) => AOprnd::Imm(*val as i64),
         TVal::Var(ident) => AOprnd::Pseudo(ident.to_string()),
     }
 }
 
+fn double_const(val: f64, alignment: i32, consts: &mut DoubleConsts) -> AOprnd {
+    let existing = consts.iter_mut().find(|(_, other, _)| other.to_bits() == val.to_bits());
+    let name = match existing {
+        Some((name, _, align)) => {
+            *align = alignment.max(*align);
+            name.clone()
+        },
+        None => {
+            let name = format!(".L{}", make_label("double"));
+            consts.push((name.clone(), val, alignment));
+            name
+        },
+    };
+
+    AOprnd::Data(name)
+}
+
+fn val_type(val: &TVal, symbols: &SymbolTable) -> AType {
+    match val {
+        TVal::Constant(Const::Char(_)) => AType::Byte,
+        TVal::Constant(Const::Int(_) | Const::UInt(_)) => AType::Longword,
+        TVal::Constant(Const::Long(_) | Const::ULong(_)) => AType::Quadword,
+        TVal::Constant(Const::Double(_)) => AType::Double,
+        TVal::Var(ident) => var_type(ident, symbols),
+    }
+}
+
+fn is_signed(val: &TVal, symbols: &SymbolTable) -> bool {
+    match val {
+        TVal::Constant(Const::Char(_) | Const::Int(_) | Const::Long(_) | Const::Double(_)) => true,
+        TVal::Constant(Const::UInt(_) | Const::ULong(_)) => false,
+        TVal::Var(ident) => symbols.get(ident).is_none_or(|symbol| symbol.sym_type.is_signed()),
+    }
+}
+
+fn is_aggregate(val: &TVal, symbols: &SymbolTable) -> bool {
+    matches!(val, TVal::Var(ident) if matches!(symbols.get(ident),
+        Some(Symbol { sym_type: Type::Structure(_) | Type::Union(_), .. })))
+}
+
+fn val_size(val: &TVal, symbols: &SymbolTable) -> i32 {
+    match val {
+        TVal::Var(ident) => symbols.get(ident).map_or(4, |symbol| type_size(&symbol.sym_type, symbols)),
+        TVal::Constant(_) => panic!("Constants are always scalar"),
+    }
+}
+
+pub fn var_type(name: &str, symbols: &SymbolTable) -> AType {
+    match symbols.get(name) {
+        Some(symbol) if symbol.sym_type == Type::Double => AType::Double,
+        Some(symbol) if type_size(&symbol.sym_type, symbols) == 8 => AType::Quadword,
+        Some(symbol) if type_size(&symbol.sym_type, symbols) == 1 => AType::Byte,
+        _ => AType::Longword,
+    }
+}
+
+//Arrays of 16 bytes or more are 16-byte aligned, as the System V ABI requires
+pub fn var_alignment(var_type: &Type, symbols: &SymbolTable) -> i32 {
+    match var_type {
+        Type::Array(_, _) if type_size(var_type, symbols) >= 16 => 16,
+        _ => type_alignment(var_type, symbols),
+    }
+}
+
+fn make_label(start: &str) -> String {
+    let ident = LABEL_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{}.{}", start, ident)
+}
+
 fn unary_op(op: &TUnaryOp) -> AUnaryOp {
     match op {
         TUnaryOp::Complement => AUnaryOp::Not,
@@ -118,6 +786,9 @@ fn binary_op(op: &TBinaryOp) -> ABinaryOp {
         TBinaryOp::Add => ABinaryOp::Add,
         TBinaryOp::Subtract => ABinaryOp::Sub,
         TBinaryOp::Multiply => ABinaryOp::Mult,
+        TBinaryOp::BitAnd => ABinaryOp::And,
+        TBinaryOp::BitOr => ABinaryOp::Or,
+        TBinaryOp::BitXor => ABinaryOp::Xor,
         _ => panic!("Invalid binary op for assembly binary op"),
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,3 @@
-
 use crate::code_gen::a_ast::*;
 
 pub fn rep_instrs(ast: &mut AssemFuncDef, stack_size: i32) {
@@ -7,28 +6,56 @@ pub fn rep_instrs(ast: &mut AssemFuncDef, stack_size: i32) {
 
 fn check_func(ast: &mut AssemFuncDef, stack_size: i32) {
     match ast {
-        AssemFuncDef::Function(_, instrs) => {
-            *instrs = check_instructions(&instrs);
-            instrs.insert(0, AInstr::AllocateStack(stack_size));
+        AssemFuncDef::Function(_, _, instrs) => {
+            *instrs = check_instructions(instrs);
+            instrs.insert(0, AInstr::AllocateStack(align_stack(stack_size)));
         },
     };
 }
 
-fn check_instructions(instrs: &Vec<AInstr>) -> Vec<AInstr> {
+fn align_stack(stack_size: i32) -> i32 {
+    (stack_size + 15) / 16 * 16
+}
+
+fn is_mem(op: &AOprnd) -> bool {
+    matches!(op, AOprnd::Stack(_) | AOprnd::Memory(_, _) | AOprnd::Indexed(_, _, _) | AOprnd::Data(_))
+}
+
+fn is_large_imm(op: &AOprnd) -> bool {
+    matches!(op, AOprnd::Imm(val) if i32::try_from(*val).is_err())
+}
+
+fn check_instructions(instrs: &[AInstr]) -> Vec<AInstr> {
     let mut new_instrs: Vec<AInstr> = Vec::new();
 
     for instr in instrs.iter() {
         match instr {
-            AInstr::Mov(AOprnd::Stack(src), AOprnd::Stack(dst)) => {
-                new_instrs.push(AInstr::Mov(AOprnd::Stack(*src), AOprnd::Reg(AReg::R10)));
-                new_instrs.push(AInstr::Mov(AOprnd::Reg(AReg::R10), AOprnd::Stack(*dst)));
+            AInstr::Mov(_, _, _) => check_mov(instr, &mut new_instrs),
+            AInstr::Movsx(_, _, _, _) => check_movsx(instr, &mut new_instrs),
+            AInstr::MovZeroExtend(_, _, _, _) => check_zero_extend(instr, &mut new_instrs),
+            AInstr::Cvttsd2si(ty, src, dst) if is_mem(dst) => {
+                new_instrs.push(AInstr::Cvttsd2si(ty.clone(), src.clone(), AOprnd::Reg(AReg::R11)));
+                new_instrs.push(AInstr::Mov(ty.clone(), AOprnd::Reg(AReg::R11), dst.clone()));
+            },
+            AInstr::Cvtsi2sd(_, _, _) => check_cvtsi2sd(instr, &mut new_instrs),
+            AInstr::Lea(src, dst) if is_mem(dst) => {
+                new_instrs.push(AInstr::Lea(src.clone(), AOprnd::Reg(AReg::R11)));
+                new_instrs.push(AInstr::Mov(AType::Quadword, AOprnd::Reg(AReg::R11), dst.clone()));
+            },
+            AInstr::Idiv(ty, AOprnd::Imm(val)) => {
+                new_instrs.push(AInstr::Mov(ty.clone(), AOprnd::Imm(*val), AOprnd::Reg(AReg::R10)));
+                new_instrs.push(AInstr::Idiv(ty.clone(), AOprnd::Reg(AReg::R10)));
+            },
+            AInstr::Div(ty, AOprnd::Imm(val)) => {
+                new_instrs.push(AInstr::Mov(ty.clone(), AOprnd::Imm(*val), AOprnd::Reg(AReg::R10)));
+                new_instrs.push(AInstr::Div(ty.clone(), AOprnd::Reg(AReg::R10)));
             },
-            AInstr::Idiv(AOprnd::Imm(val)) => {
-                new_instrs.push(AInstr::Mov(AOprnd::Imm(*val), AOprnd::Reg(AReg::R10)));
-                new_instrs.push(AInstr::Idiv(AOprnd::Reg(AReg::R10)));
+            AInstr::Binary(_, _, _, _) => check_binary(instr, &mut new_instrs),
+            AInstr::Cmp(_, _, _) => check_cmp(instr, &mut new_instrs),
+            AInstr::Push(op) if is_large_imm(op) => {
+                new_instrs.push(AInstr::Mov(AType::Quadword, op.clone(), AOprnd::Reg(AReg::R10)));
+                new_instrs.push(AInstr::Push(AOprnd::Reg(AReg::R10)));
             },
-            AInstr::Binary(_, _, _) => check_binary(&instr, &mut new_instrs),
-            AInstr::Cmp(_, _) => check_cmp(&instr, &mut new_instrs),
             _ => new_instrs.push(instr.clone()),
         };
     }
@@ -36,39 +63,128 @@ fn check_instructions(instrs: &Vec<AInstr>) -> Vec<AInstr> {
     new_instrs
 }
 
-fn check_binary(instr: &AInstr, new_instrs: &mut Vec<AInstr>) {
+fn check_mov(instr: &AInstr, new_instrs: &mut Vec<AInstr>) {
     match instr {
-        AInstr::Binary(ABinaryOp::Add, AOprnd::Stack(src), AOprnd::Stack(dst)) => {
-            new_instrs.push(AInstr::Mov(AOprnd::Stack(*src), AOprnd::Reg(AReg::R10)));
-            new_instrs.push(AInstr::Binary(ABinaryOp::Add, AOprnd::Reg(AReg::R10), AOprnd::Stack(*dst)));
+        AInstr::Mov(AType::Longword, AOprnd::Imm(val), dst) => {
+            new_instrs.push(AInstr::Mov(AType::Longword, AOprnd::Imm(*val as i32 as i64), dst.clone()));
         },
-        AInstr::Binary(ABinaryOp::Sub, AOprnd::Stack(src), AOprnd::Stack(dst)) => {
-            new_instrs.push(AInstr::Mov(AOprnd::Stack(*src), AOprnd::Reg(AReg::R10)));
-            new_instrs.push(AInstr::Binary(ABinaryOp::Sub, AOprnd::Reg(AReg::R10), AOprnd::Stack(*dst)));
+        AInstr::Mov(AType::Byte, AOprnd::Imm(val), dst) => {
+            new_instrs.push(AInstr::Mov(AType::Byte, AOprnd::Imm(*val as i8 as i64), dst.clone()));
         },
-        AInstr::Binary(ABinaryOp::Mult, src_instr, AOprnd::Stack(dst)) => {
-            new_instrs.push(AInstr::Mov(AOprnd::Stack(*dst), AOprnd::Reg(AReg::R11)));
-            match src_instr {
-                AOprnd::Stack(src) => new_instrs.push(AInstr::Binary(ABinaryOp::Mult, AOprnd::Stack(*src), AOprnd::Reg(AReg::R11))),
-                AOprnd::Imm(val) => new_instrs.push(AInstr::Binary(ABinaryOp::Mult, AOprnd::Imm(*val), AOprnd::Reg(AReg::R11))),
-                _ => panic!("Binary multiplication instruction fix fail"),
-            }
-            new_instrs.push(AInstr::Mov(AOprnd::Reg(AReg::R11), AOprnd::Stack(*dst)));
+        AInstr::Mov(AType::Double, src, dst) if is_mem(src) && is_mem(dst) => {
+            new_instrs.push(AInstr::Mov(AType::Double, src.clone(), AOprnd::Reg(AReg::XMM14)));
+            new_instrs.push(AInstr::Mov(AType::Double, AOprnd::Reg(AReg::XMM14), dst.clone()));
+        },
+        AInstr::Mov(ty, src, dst) if (is_mem(src) || is_large_imm(src)) && is_mem(dst) => {
+            new_instrs.push(AInstr::Mov(ty.clone(), src.clone(), AOprnd::Reg(AReg::R10)));
+            new_instrs.push(AInstr::Mov(ty.clone(), AOprnd::Reg(AReg::R10), dst.clone()));
         },
         _ => new_instrs.push(instr.clone()),
     }
 }
 
-fn check_cmp(instr: &AInstr, new_instrs: &mut Vec<AInstr>) {
+fn check_movsx(instr: &AInstr, new_instrs: &mut Vec<AInstr>) {
+    if let AInstr::Movsx(src_type, dst_type, src, dst) = instr {
+        let mut new_src = src.clone();
+        if let AOprnd::Imm(_) = src {
+            check_mov(&AInstr::Mov(src_type.clone(), src.clone(), AOprnd::Reg(AReg::R10)), new_instrs);
+            new_src = AOprnd::Reg(AReg::R10);
+        }
+
+        if is_mem(dst) {
+            new_instrs.push(AInstr::Movsx(src_type.clone(), dst_type.clone(), new_src, AOprnd::Reg(AReg::R11)));
+            new_instrs.push(AInstr::Mov(dst_type.clone(), AOprnd::Reg(AReg::R11), dst.clone()));
+        } else {
+            new_instrs.push(AInstr::Movsx(src_type.clone(), dst_type.clone(), new_src, dst.clone()));
+        }
+    }
+}
+
+fn check_cvtsi2sd(instr: &AInstr, new_instrs: &mut Vec<AInstr>) {
+    if let AInstr::Cvtsi2sd(ty, src, dst) = instr {
+        let mut new_src = src.clone();
+        if let AOprnd::Imm(_) = src {
+            new_instrs.push(AInstr::Mov(ty.clone(), src.clone(), AOprnd::Reg(AReg::R10)));
+            new_src = AOprnd::Reg(AReg::R10);
+        }
+
+        if is_mem(dst) {
+            new_instrs.push(AInstr::Cvtsi2sd(ty.clone(), new_src, AOprnd::Reg(AReg::XMM15)));
+            new_instrs.push(AInstr::Mov(AType::Double, AOprnd::Reg(AReg::XMM15), dst.clone()));
+        } else {
+            new_instrs.push(AInstr::Cvtsi2sd(ty.clone(), new_src, dst.clone()));
+        }
+    }
+}
+
+//Bytes are extended with movzb, while a 32-bit mov already clears the upper half of a register
+fn check_zero_extend(instr: &AInstr, new_instrs: &mut Vec<AInstr>) {
     match instr {
-        AInstr::Cmp(AOprnd::Stack(src1), AOprnd::Stack(src2)) => {
-            new_instrs.push(AInstr::Mov(AOprnd::Stack(*src1), AOprnd::Reg(AReg::R10)));
-            new_instrs.push(AInstr::Cmp(AOprnd::Reg(AReg::R10), AOprnd::Stack(*src2)));
+        AInstr::MovZeroExtend(AType::Byte, dst_type, src, dst) => {
+            let mut new_src = src.clone();
+            if let AOprnd::Imm(_) = src {
+                check_mov(&AInstr::Mov(AType::Byte, src.clone(), AOprnd::Reg(AReg::R10)), new_instrs);
+                new_src = AOprnd::Reg(AReg::R10);
+            }
+
+            if is_mem(dst) {
+                new_instrs.push(AInstr::MovZeroExtend(AType::Byte, dst_type.clone(), new_src, AOprnd::Reg(AReg::R11)));
+                new_instrs.push(AInstr::Mov(dst_type.clone(), AOprnd::Reg(AReg::R11), dst.clone()));
+            } else {
+                new_instrs.push(AInstr::MovZeroExtend(AType::Byte, dst_type.clone(), new_src, dst.clone()));
+            }
         },
-        AInstr::Cmp(src, AOprnd::Imm(val)) => {
-            new_instrs.push(AInstr::Mov(AOprnd::Imm(*val), AOprnd::Reg(AReg::R11)));
-            new_instrs.push(AInstr::Cmp(src.clone(), AOprnd::Reg(AReg::R11)));
+        AInstr::MovZeroExtend(_, _, src, dst) if is_mem(dst) => {
+            new_instrs.push(AInstr::Mov(AType::Longword, src.clone(), AOprnd::Reg(AReg::R11)));
+            new_instrs.push(AInstr::Mov(AType::Quadword, AOprnd::Reg(AReg::R11), dst.clone()));
         },
-        _ => new_instrs.push(instr.clone()),
+        AInstr::MovZeroExtend(_, _, src, dst) => {
+            check_mov(&AInstr::Mov(AType::Longword, src.clone(), dst.clone()), new_instrs);
+        },
+        _ => (),
+    }
+}
+
+fn check_binary(instr: &AInstr, new_instrs: &mut Vec<AInstr>) {
+    if let AInstr::Binary(op, AType::Double, src, dst) = instr && is_mem(dst) {
+        new_instrs.push(AInstr::Mov(AType::Double, dst.clone(), AOprnd::Reg(AReg::XMM15)));
+        new_instrs.push(AInstr::Binary(op.clone(), AType::Double, src.clone(), AOprnd::Reg(AReg::XMM15)));
+        new_instrs.push(AInstr::Mov(AType::Double, AOprnd::Reg(AReg::XMM15), dst.clone()));
+    } else if let AInstr::Binary(op, ty, src, dst) = instr {
+        let mut new_src = src.clone();
+        if is_large_imm(src) || (is_mem(src) && is_mem(dst)) {
+            new_instrs.push(AInstr::Mov(ty.clone(), src.clone(), AOprnd::Reg(AReg::R10)));
+            new_src = AOprnd::Reg(AReg::R10);
+        }
+
+        match op {
+            ABinaryOp::Mult if is_mem(dst) => {
+                new_instrs.push(AInstr::Mov(ty.clone(), dst.clone(), AOprnd::Reg(AReg::R11)));
+                new_instrs.push(AInstr::Binary(ABinaryOp::Mult, ty.clone(), new_src, AOprnd::Reg(AReg::R11)));
+                new_instrs.push(AInstr::Mov(ty.clone(), AOprnd::Reg(AReg::R11), dst.clone()));
+            },
+            _ => new_instrs.push(AInstr::Binary(op.clone(), ty.clone(), new_src, dst.clone())),
+        }
+    }
+}
+
+fn check_cmp(instr: &AInstr, new_instrs: &mut Vec<AInstr>) {
+    if let AInstr::Cmp(AType::Double, src1, src2) = instr && !matches!(src2, AOprnd::Reg(_)) {
+        new_instrs.push(AInstr::Mov(AType::Double, src2.clone(), AOprnd::Reg(AReg::XMM15)));
+        new_instrs.push(AInstr::Cmp(AType::Double, src1.clone(), AOprnd::Reg(AReg::XMM15)));
+    } else if let AInstr::Cmp(ty, src1, src2) = instr {
+        let mut new_src1 = src1.clone();
+        if is_large_imm(src1) || (is_mem(src1) && is_mem(src2)) {
+            new_instrs.push(AInstr::Mov(ty.clone(), src1.clone(), AOprnd::Reg(AReg::R10)));
+            new_src1 = AOprnd::Reg(AReg::R10);
+        }
+
+        match src2 {
+            AOprnd::Imm(_) => {
+                new_instrs.push(AInstr::Mov(ty.clone(), src2.clone(), AOprnd::Reg(AReg::R11)));
+                new_instrs.push(AInstr::Cmp(ty.clone(), new_src1, AOprnd::Reg(AReg::R11)));
+            },
+            _ => new_instrs.push(AInstr::Cmp(ty.clone(), new_src1, src2.clone())),
+        }
     }
-}
\ No newline at end of file
+}
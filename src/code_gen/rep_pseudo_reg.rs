@@ -2,64 +2,82 @@
 use std::collections::HashMap;
 
 use crate::code_gen::a_ast::*;
+use crate::code_gen::a_tree_builder::var_alignment;
+use crate::resolver::type_checker::*;
 
-pub fn rep_pseudo_regs(ast: &mut AssemFuncDef) -> i32 {
+//Reserved bytes at the top of the frame are left for slots the code generator addresses directly
+pub fn rep_pseudo_regs(ast: &mut AssemFuncDef, reserved: i32, symbols: &SymbolTable) -> i32 {
     let mut var_addresses: HashMap<String, i32> = HashMap::new();
-    let mut depth = 0;
+    let mut depth = -reserved;
 
-    check_func(ast, &mut var_addresses, &mut depth);
+    check_func(ast, &mut var_addresses, &mut depth, symbols);
 
     depth.abs()
 }
 
-fn check_func(ast: &mut AssemFuncDef, var_addresses: &mut HashMap<String, i32>, depth: &mut i32) {
+fn check_func(ast: &mut AssemFuncDef, var_addresses: &mut HashMap<String, i32>, depth: &mut i32, symbols: &SymbolTable) {
     match ast {
-        AssemFuncDef::Function(_, instrs) => check_instructions(instrs, var_addresses, depth),
+        AssemFuncDef::Function(_, _, instrs) => check_instructions(instrs, var_addresses, depth, symbols),
     };
 }
 
-fn check_instructions(instrs: &mut Vec<AInstr>, var_addresses: &mut HashMap<String, i32>, depth: &mut i32) {
+fn check_instructions(instrs: &mut [AInstr], var_addresses: &mut HashMap<String, i32>, depth: &mut i32, symbols: &SymbolTable) {
     for instr in instrs.iter_mut() {
-        check_instruction(instr, var_addresses, depth);
+        check_instruction(instr, var_addresses, depth, symbols);
     }
 }
 
-fn check_instruction(instr: &mut AInstr, var_addresses: &mut HashMap<String, i32>, depth: &mut i32) {
+fn check_instruction(instr: &mut AInstr, var_addresses: &mut HashMap<String, i32>, depth: &mut i32, symbols: &SymbolTable) {
     match instr {
-        AInstr::Mov(src, dst) => {
-            check_operand(src, var_addresses, depth);
-            check_operand(dst, var_addresses, depth);
+        AInstr::Mov(_, src, dst) | AInstr::Movsx(_, _, src, dst) | AInstr::MovZeroExtend(_, _, src, dst)
+            | AInstr::Cvttsd2si(_, src, dst) | AInstr::Cvtsi2sd(_, src, dst) | AInstr::Lea(src, dst) => {
+            check_operand(src, var_addresses, depth, symbols);
+            check_operand(dst, var_addresses, depth, symbols);
         },
-        AInstr::Unary(_, op) => check_operand(op, var_addresses, depth),
-        AInstr::Binary(_, op1, op2) => {
-            check_operand(op1, var_addresses, depth);
-            check_operand(op2, var_addresses, depth);
+        AInstr::Unary(_, _, op) => check_operand(op, var_addresses, depth, symbols),
+        AInstr::Binary(_, _, op1, op2) => {
+            check_operand(op1, var_addresses, depth, symbols);
+            check_operand(op2, var_addresses, depth, symbols);
         },
-        AInstr::Idiv(op) => check_operand(op, var_addresses, depth),
-        AInstr::Cmp(op1, op2) => {
-            check_operand(op1, var_addresses, depth);
-            check_operand(op2, var_addresses, depth);
+        AInstr::Idiv(_, op) | AInstr::Div(_, op) => check_operand(op, var_addresses, depth, symbols),
+        AInstr::Cmp(_, op1, op2) => {
+            check_operand(op1, var_addresses, depth, symbols);
+            check_operand(op2, var_addresses, depth, symbols);
         },
-        AInstr::SetCC(_, op) => check_operand(op, var_addresses, depth),
+        AInstr::SetCC(_, op) => check_operand(op, var_addresses, depth, symbols),
+        AInstr::Push(op) => check_operand(op, var_addresses, depth, symbols),
         _ => (),
     };
 }
 
-fn check_operand(op: &mut AOprnd, var_addresses: &mut HashMap<String, i32>, depth: &mut i32) {
+fn check_operand(op: &mut AOprnd, var_addresses: &mut HashMap<String, i32>, depth: &mut i32, symbols: &SymbolTable) {
     match op {
-        AOprnd::Pseudo(val) => {
-            let val_depth = match var_addresses.get(val) {
-                Some(res) => res,
-                None => {
-                    *depth -= 4;
-                    var_addresses.insert(val.to_string(), *depth);
-
-                    depth
-                }
-            };
-
-            *op = AOprnd::Stack(*val_depth);
+        AOprnd::Pseudo(val) if is_static(val, symbols) => *op = AOprnd::Data(val.clone()),
+        AOprnd::Pseudo(val) => *op = AOprnd::Stack(stack_address(val, var_addresses, depth, symbols)),
+        AOprnd::PseudoMem(val, 0) if is_static(val, symbols) => *op = AOprnd::Data(val.clone()),
+        AOprnd::PseudoMem(val, offset) if is_static(val, symbols) => *op = AOprnd::Data(format!("{}+{}", val, offset)),
+        AOprnd::PseudoMem(val, offset) => {
+            *op = AOprnd::Stack(stack_address(val, var_addresses, depth, symbols) + *offset);
         },
         _ => (),
     };
-}
\ No newline at end of file
+}
+
+fn stack_address(name: &str, var_addresses: &mut HashMap<String, i32>, depth: &mut i32, symbols: &SymbolTable) -> i32 {
+    if let Some(address) = var_addresses.get(name) {
+        return *address;
+    }
+
+    let (size, alignment) = match symbols.get(name) {
+        Some(symbol) => (type_size(&symbol.sym_type, symbols), var_alignment(&symbol.sym_type, symbols)),
+        None => (4, 4),
+    };
+    *depth = (*depth - size).div_euclid(alignment) * alignment;
+    var_addresses.insert(name.to_string(), *depth);
+
+    *depth
+}
+
+fn is_static(name: &str, symbols: &SymbolTable) -> bool {
+    matches!(symbols.get(name), Some(Symbol { attrs: IdentAttrs::Static(_, _) | IdentAttrs::Constant(_), .. }))
+}
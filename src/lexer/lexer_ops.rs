@@ -1,13 +1,15 @@
 
+use std::rc::Rc;
+
 use crate::utilities::error_handler::fmt_lexer_error;
 use crate::lexer::lexer_structs::*;
 
-pub fn lex(characters: Vec<u8>) -> Result<Vec<(Tkn, u32)>, String> {
-    let mut lexer = Lxr::new(characters);
+pub fn lex(characters: Vec<u8>, c23: bool) -> Result<Vec<(Tkn, Location)>, String> {
+    let mut lexer = Lxr::new(characters, c23);
     while !lexer.is_at_end() {
         skip_white_space(&mut lexer)?;
         let token = create_token(&mut lexer)?;
-        lexer.tokens.push((token, lexer.line));
+        lexer.tokens.push((token, lexer.location()));
     }
 
     Ok(lexer.tokens)
@@ -23,6 +25,7 @@ fn skip_white_space(lexer: &mut Lxr) -> Result<(), String> {
             ' ' | '\t' => {
                 lexer.take()?;
             },
+            '#' => line_marker(lexer)?,
             _ => break,
         };
     }
@@ -30,6 +33,50 @@ fn skip_white_space(lexer: &mut Lxr) -> Result<(), String> {
     Ok(())
 }
 
+//The preprocessor writes "# <line> <file>" before text that doesn't follow on from the previous line
+fn line_marker(lexer: &mut Lxr) -> Result<(), String> {
+    lexer.take()?;
+    while lexer.peek()? == ' ' {
+        lexer.take()?;
+    }
+
+    let mut num = String::new();
+    while is_digit(lexer.peek()?) { num.push(lexer.take()?); }
+    let line: u32 = match num.parse() {
+        Ok(line) if line > 0 => line,
+        _ => return Err(fmt_lexer_error(&lexer.file, lexer.line, "Stray '#' in program")),
+    };
+
+    while lexer.peek()? == ' ' {
+        lexer.take()?;
+    }
+    if lexer.peek()? == '"' {
+        lexer.take()?;
+        lexer.file = Rc::from(marker_file(lexer)?);
+    }
+
+    while lexer.peek()? != '\n' {
+        lexer.take()?;
+    }
+    //The newline ending the marker moves on to the line it names
+    lexer.line = line - 1;
+
+    Ok(())
+}
+
+//The file name is quoted like a string literal, with backslashes and quotes escaped
+fn marker_file(lexer: &mut Lxr) -> Result<String, String> {
+    let mut file = String::new();
+    loop {
+        match lexer.take()? {
+            '"' => return Ok(file),
+            '\n' => return Err(fmt_lexer_error(&lexer.file, lexer.line, "Unterminated file name in line marker")),
+            '\\' => file.push(lexer.take()?),
+            c => file.push(c),
+        }
+    }
+}
+
 fn create_token(lexer: &mut Lxr) -> Result<Tkn, String> {
     let current = lexer.take()?;
     match current {
@@ -37,50 +84,247 @@ fn create_token(lexer: &mut Lxr) -> Result<Tkn, String> {
         ')' => Ok(Tkn::RightParen),
         '{' => Ok(Tkn::LeftBrace),
         '}' => Ok(Tkn::RightBrace),
+        '[' => Ok(Tkn::LeftBracket),
+        ']' => Ok(Tkn::RightBracket),
         ';' => Ok(Tkn::Semicolon),
         '~' => Ok(Tkn::Tilde),
-        '/' => Ok(Tkn::Slash),
-        '*' => Ok(Tkn::Star),
-        '%' => Ok(Tkn::Mod),
         '?' => Ok(Tkn::Question),
         ':' => Ok(Tkn::Colon),
+        ',' => Ok(Tkn::Comma),
 
+        '-' if lexer.peek()? == '>' => {
+            lexer.take()?;
+            Ok(Tkn::Arrow)
+        },
+        '-' if lexer.peek()? == '=' => {
+            lexer.take()?;
+            Ok(Tkn::SubtractEqual)
+        },
         '-' => match_dbl(Tkn::Decrement, Tkn::Subtract, '-', lexer),
+        '+' if lexer.peek()? == '=' => {
+            lexer.take()?;
+            Ok(Tkn::PlusEqual)
+        },
         '+' => match_dbl(Tkn::Increment, Tkn::Plus, '+', lexer),
+        '*' => match_dbl(Tkn::StarEqual, Tkn::Star, '=', lexer),
+        '/' => match_dbl(Tkn::SlashEqual, Tkn::Slash, '=', lexer),
+        '%' => match_dbl(Tkn::ModEqual, Tkn::Mod, '=', lexer),
         '!' => match_dbl(Tkn::NotEqual, Tkn::Not, '=', lexer),
         '=' => match_dbl(Tkn::EqualEqual, Tkn::Equal, '=', lexer),
+        '<' if lexer.peek()? == '<' => {
+            lexer.take()?;
+            match_dbl(Tkn::ShiftLeftEqual, Tkn::ShiftLeft, '=', lexer)
+        },
         '<' => match_dbl(Tkn::LessEqual, Tkn::Less, '=', lexer),
+        '>' if lexer.peek()? == '>' => {
+            lexer.take()?;
+            match_dbl(Tkn::ShiftRightEqual, Tkn::ShiftRight, '=', lexer)
+        },
         '>' => match_dbl(Tkn::GreatEqual, Tkn::Great, '=', lexer),
+        '&' if lexer.peek()? == '=' => {
+            lexer.take()?;
+            Ok(Tkn::BAndEqual)
+        },
         '&' => match_dbl(Tkn::And, Tkn::BAnd, '&', lexer),
+        '|' if lexer.peek()? == '=' => {
+            lexer.take()?;
+            Ok(Tkn::BOrEqual)
+        },
         '|' => match_dbl(Tkn::Or, Tkn::BOr, '|', lexer),
+        '^' => match_dbl(Tkn::CaretEqual, Tkn::Caret, '=', lexer),
+        '\'' => char_constant(lexer),
+        '"' => string_literal(lexer),
 
         _ => {
-            if is_digit(current) {
+            if current == '0' && matches!(lexer.peek()?, 'x' | 'X' | 'b' | 'B') {
+                let radix = if lexer.take()?.eq_ignore_ascii_case(&'x') { 16 } else { 2 };
+                let mut num = String::new();
+                while lexer.peek()?.is_digit(radix) { num.push(lexer.take()?); }
+
+                if num.is_empty() {
+                    return Err(fmt_lexer_error(&lexer.file, lexer.line, "Malformed integer constant"));
+                }
+                int_constant(&num, radix, lexer)
+            } else if is_digit(current) || (current == '.' && is_digit(lexer.peek()?)) {
                 let mut num = String::from(current);
                 while is_digit(lexer.peek()?) { num.push(lexer.take()?); }
 
-                if is_alpha(lexer.peek()?) { 
-                    Err(fmt_lexer_error(lexer.line, "Invalid Identifier"))
+                if current == '.' || matches!(lexer.peek()?, '.' | 'e' | 'E') {
+                    return double_constant(num, lexer);
+                }
+
+                //A leading zero makes the constant octal, which the 0 on its own also is
+                if current == '0' {
+                    if !num.chars().all(|c| c.is_digit(8)) {
+                        return Err(fmt_lexer_error(&lexer.file, lexer.line, "Invalid digit in octal constant"));
+                    }
+                    int_constant(&num, 8, lexer)
                 } else {
-                    Ok(Tkn::Constant(num.parse().unwrap()))
+                    int_constant(&num, 10, lexer)
                 }
+            } else if current == '.' {
+                Ok(Tkn::Dot)
             } else if is_alpha(current) {
                 let mut ident = String::from(current);
                 while is_alpha_num(lexer.peek()?) {
                     ident.push(lexer.take()?);
                 }
-                if KEYWORDS.contains(&ident.as_str()) {
-                    Ok(Tkn::Key(find_key(&ident.as_str())))
+                if KEYWORDS.contains(&ident.as_str()) || (lexer.c23 && C23_KEYWORDS.contains(&ident.as_str())) {
+                    Ok(Tkn::Key(find_key(ident.as_str())))
                 } else {
                     Ok(Tkn::Identifier(ident))
                 }
             } else {
-                Err(fmt_lexer_error(lexer.line, &format!("Unexpected Token: {}", current)))
+                Err(fmt_lexer_error(&lexer.file, lexer.line, &format!("Unexpected Token: {}", current)))
             }
         } 
     }
 }
 
+//Picks the first type from C's list for the suffix and base that can represent the value
+fn int_constant(num: &str, radix: u32, lexer: &mut Lxr) -> Result<Tkn, String> {
+    let mut suffix = String::new();
+    while matches!(lexer.peek()?, 'l' | 'L' | 'u' | 'U') {
+        suffix.push(lexer.take()?);
+    }
+
+    if is_alpha_num(lexer.peek()?) || lexer.peek()? == '.' {
+        return Err(fmt_lexer_error(&lexer.file, lexer.line, "Malformed integer constant"));
+    }
+
+    //The two letters of ll have to be the same case, and u can come before or after it
+    let (is_unsigned, is_long) = match suffix.as_str() {
+        "" => (false, false),
+        "u" | "U" => (true, false),
+        "l" | "L" | "ll" | "LL" => (false, true),
+        _ => {
+            let lower = suffix.to_ascii_lowercase();
+            let long_part = lower.trim_start_matches('u').trim_end_matches('u');
+            if lower.matches('u').count() != 1 || !matches!(long_part, "l" | "ll")
+                || suffix.contains("lL") || suffix.contains("Ll") {
+                return Err(fmt_lexer_error(&lexer.file, lexer.line, &format!("Invalid suffix \"{}\" on integer constant", suffix)));
+            }
+            (true, true)
+        },
+    };
+
+    let value = match u64::from_str_radix(num, radix) {
+        Ok(val) => val,
+        Err(_) => return Err(fmt_lexer_error(&lexer.file, lexer.line, "Integer constant too large")),
+    };
+
+    //Decimal constants without a u suffix never become unsigned
+    let allows_unsigned = is_unsigned || radix != 10;
+    let fits_int = value <= i32::MAX as u64;
+    let fits_uint = value <= u32::MAX as u64;
+    let fits_long = value <= i64::MAX as u64;
+
+    match (is_unsigned, is_long) {
+        (false, false) if fits_int => Ok(Tkn::Constant(value)),
+        (false, false) if fits_uint && allows_unsigned => Ok(Tkn::UnsignedConstant(value)),
+        (false, _) if fits_long => Ok(Tkn::LongConstant(value)),
+        (true, false) if fits_uint => Ok(Tkn::UnsignedConstant(value)),
+        _ if allows_unsigned => Ok(Tkn::UnsignedLongConstant(value)),
+        _ => Err(fmt_lexer_error(&lexer.file, lexer.line, "Integer constant too large for its type")),
+    }
+}
+
+fn double_constant(mut num: String, lexer: &mut Lxr) -> Result<Tkn, String> {
+    if lexer.peek()? == '.' {
+        num.push(lexer.take()?);
+    }
+    while is_digit(lexer.peek()?) { num.push(lexer.take()?); }
+
+    if matches!(lexer.peek()?, 'e' | 'E') {
+        num.push(lexer.take()?);
+        if matches!(lexer.peek()?, '+' | '-') {
+            num.push(lexer.take()?);
+        }
+        if !is_digit(lexer.peek()?) {
+            return Err(fmt_lexer_error(&lexer.file, lexer.line, "Malformed floating point constant"));
+        }
+        while is_digit(lexer.peek()?) { num.push(lexer.take()?); }
+    }
+
+    if is_alpha_num(lexer.peek()?) || lexer.peek()? == '.' {
+        return Err(fmt_lexer_error(&lexer.file, lexer.line, "Malformed floating point constant"));
+    }
+
+    match num.parse() {
+        Ok(val) => Ok(Tkn::DoubleConstant(val)),
+        Err(_) => Err(fmt_lexer_error(&lexer.file, lexer.line, "Malformed floating point constant")),
+    }
+}
+
+fn char_constant(lexer: &mut Lxr) -> Result<Tkn, String> {
+    let value = match lexer.take()? {
+        '\'' => return Err(fmt_lexer_error(&lexer.file, lexer.line, "Empty character constant")),
+        '\n' => return Err(fmt_lexer_error(&lexer.file, lexer.line, "Malformed character constant")),
+        '\\' => escape_sequence(lexer)?,
+        c => c as u8,
+    };
+
+    if lexer.take()? != '\'' {
+        return Err(fmt_lexer_error(&lexer.file, lexer.line, "Malformed character constant"));
+    }
+
+    Ok(Tkn::CharConstant(value))
+}
+
+fn string_literal(lexer: &mut Lxr) -> Result<Tkn, String> {
+    let mut bytes: Vec<u8> = Vec::new();
+
+    loop {
+        match lexer.take()? {
+            '"' => break,
+            '\n' => return Err(fmt_lexer_error(&lexer.file, lexer.line, "Unterminated string literal")),
+            '\\' => bytes.push(escape_sequence(lexer)?),
+            c => bytes.push(c as u8),
+        }
+    }
+
+    Ok(Tkn::StringLiteral(bytes))
+}
+
+fn escape_sequence(lexer: &mut Lxr) -> Result<u8, String> {
+    let current = lexer.take()?;
+    match current {
+        '\'' | '"' | '?' | '\\' => Ok(current as u8),
+        'a' => Ok(7),
+        'b' => Ok(8),
+        'f' => Ok(12),
+        'n' => Ok(b'\n'),
+        'r' => Ok(b'\r'),
+        't' => Ok(b'\t'),
+        'v' => Ok(11),
+        '0'..='7' => {
+            let mut value = current.to_digit(8).unwrap();
+            for _ in 0..2 {
+                match lexer.peek()?.to_digit(8) {
+                    Some(digit) => {
+                        lexer.take()?;
+                        value = value * 8 + digit;
+                    },
+                    None => break,
+                }
+            }
+            u8::try_from(value).map_err(|_| fmt_lexer_error(&lexer.file, lexer.line, "Octal escape sequence out of range"))
+        },
+        'x' => {
+            let mut value: u32 = 0;
+            if !lexer.peek()?.is_ascii_hexdigit() {
+                return Err(fmt_lexer_error(&lexer.file, lexer.line, "Invalid escape sequence"));
+            }
+            while let Some(digit) = lexer.peek()?.to_digit(16) {
+                lexer.take()?;
+                value = value.saturating_mul(16).saturating_add(digit);
+            }
+            u8::try_from(value).map_err(|_| fmt_lexer_error(&lexer.file, lexer.line, "Hex escape sequence out of range"))
+        },
+        _ => Err(fmt_lexer_error(&lexer.file, lexer.line, "Invalid escape sequence")),
+    }
+}
+
 fn find_key(val: &str) -> Keyword {
     match val {
         "int" => Keyword::Int,
@@ -88,6 +332,35 @@ fn find_key(val: &str) -> Keyword {
         "return" => Keyword::Return,
         "if" => Keyword::If,
         "else" => Keyword::Else,
+        "do" => Keyword::Do,
+        "while" => Keyword::While,
+        "for" => Keyword::For,
+        "break" => Keyword::Break,
+        "continue" => Keyword::Continue,
+        "static" => Keyword::Static,
+        "extern" => Keyword::Extern,
+        "long" => Keyword::Long,
+        "signed" => Keyword::Signed,
+        "unsigned" => Keyword::Unsigned,
+        "double" => Keyword::Double,
+        "char" => Keyword::Char,
+        "struct" => Keyword::Struct,
+        "union" => Keyword::Union,
+        "switch" => Keyword::Switch,
+        "case" => Keyword::Case,
+        "default" => Keyword::Default,
+        "goto" => Keyword::Goto,
+        "sizeof" => Keyword::Sizeof,
+        "_Alignof" => Keyword::Alignof,
+        "enum" => Keyword::Enum,
+        "typedef" => Keyword::Typedef,
+        "auto" => Keyword::Auto,
+        "register" => Keyword::Register,
+        "_Bool" | "bool" => Keyword::Bool,
+        "_Static_assert" => Keyword::StaticAssert,
+        "const" => Keyword::Const,
+        "true" => Keyword::True,
+        "false" => Keyword::False,
         _ => panic!(),
     }
 }
@@ -102,11 +375,11 @@ fn match_dbl(a: Tkn, b: Tkn, e: char, lexer: &mut Lxr) -> Result<Tkn, String> {
 }
 
 fn is_digit(c: char) -> bool {
-    c >= '0' && c <='9'
+    c.is_ascii_digit()
 }
 
 fn is_alpha(c: char) -> bool {
-    (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z') || c == '_'
+    c.is_ascii_alphabetic() || c == '_'
 }
 
 fn is_alpha_num(c: char) -> bool {
@@ -1,7 +1,15 @@
 
+use std::rc::Rc;
+
 use crate::utilities::error_handler::fmt_lexer_error;
 
-pub static KEYWORDS: [&str; 5] = ["int", "void", "return", "if", "else"];
+pub static KEYWORDS: [&str; 32] = ["int", "void", "return", "if", "else", "do", "while", "for", "break", "continue",
+    "static", "extern", "long", "signed", "unsigned", "double", "char", "struct", "union",
+    "switch", "case", "default", "goto", "sizeof", "_Alignof", "enum", "typedef",
+    "auto", "register", "_Bool", "_Static_assert", "const"];
+
+//Words that are only keywords under -std=c23
+pub static C23_KEYWORDS: [&str; 3] = ["bool", "true", "false"];
 
 #[derive(Debug)]
 #[derive(PartialEq)]
@@ -11,6 +19,35 @@ pub enum Keyword {
     Return,
     If,
     Else,
+    Do,
+    While,
+    For,
+    Break,
+    Continue,
+    Static,
+    Extern,
+    Long,
+    Signed,
+    Unsigned,
+    Double,
+    Char,
+    Struct,
+    Union,
+    Switch,
+    Case,
+    Default,
+    Goto,
+    Sizeof,
+    Alignof,
+    Enum,
+    Typedef,
+    Auto,
+    Register,
+    Bool,
+    StaticAssert,
+    Const,
+    True,
+    False,
 }
 
 #[derive(Debug)]
@@ -21,55 +58,94 @@ pub enum Tkn {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Semicolon,
     Tilde,
     Question,
     Colon,
+    Comma,
+    Dot,
 
     //Single-Double Tokens
     Subtract,
+    SubtractEqual,
     Decrement,
+    Arrow,
     Star,
+    StarEqual,
     Slash,
+    SlashEqual,
     Plus,
+    PlusEqual,
     Increment,
     Mod,
+    ModEqual,
     Not,
     NotEqual,
     BAnd,
+    BAndEqual,
     And,
     BOr,
+    BOrEqual,
     Or,
+    Caret,
+    CaretEqual,
     Equal,
     EqualEqual,
     Less,
     LessEqual,
+    ShiftLeft,
+    ShiftLeftEqual,
     Great,
     GreatEqual,
+    ShiftRight,
+    ShiftRightEqual,
 
     //Words
     Key(Keyword),
     Identifier(String),
-    Constant(i32)
+    Constant(u64),
+    LongConstant(u64),
+    UnsignedConstant(u64),
+    UnsignedLongConstant(u64),
+    DoubleConstant(f64),
+    CharConstant(u8),
+    StringLiteral(Vec<u8>),
+}
+
+//Where a token was written, as named by the preprocessor's line markers
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub struct Location {
+    pub file: Rc<str>,
+    pub line: u32,
 }
 
 pub struct Lxr {
-    pub tokens: Vec<(Tkn, u32)>,
+    pub tokens: Vec<(Tkn, Location)>,
+    pub file: Rc<str>,
     pub line: u32,
+    pub c23: bool,
     characters: Vec<u8>,
     current: usize,
 }
 
 impl Lxr {
-    pub fn new(characters: Vec<u8>) -> Self {
-        Self { characters: characters, current: 0, tokens: Vec::new(), line: 1 }
+    pub fn new(characters: Vec<u8>, c23: bool) -> Self {
+        Self { characters, current: 0, tokens: Vec::new(), file: Rc::from(""), line: 1, c23 }
+    }
+
+    pub fn location(&self) -> Location {
+        Location { file: self.file.clone(), line: self.line }
     }
 
     pub fn peek(&self) -> Result<char, String> {
         if !(self.is_at_end()) {
             Ok(self.characters[self.current] as char)
         } else {
-            Err(fmt_lexer_error(self.line, "No token to peek"))
+            Err(fmt_lexer_error(&self.file, self.line, "No token to peek"))
         }
     }
 
@@ -78,7 +154,7 @@ impl Lxr {
             self.current += 1;
             Ok(self.characters[self.current - 1] as char)
         } else {
-            Err(fmt_lexer_error(self.line, "No token to take"))
+            Err(fmt_lexer_error(&self.file, self.line, "No token to take"))
         }
     }
 
@@ -2,8 +2,9 @@
 use std::env;
 use std::process;
 use std::process::Command;
-use std::fs;
+use std::path::PathBuf;
 
+mod preprocessor;
 mod lexer;
 mod utilities;
 mod parser;
@@ -12,6 +13,7 @@ mod code_gen;
 mod code_emission;
 mod resolver;
 
+use preprocessor::preprocessor_ops::preprocess;
 use lexer::lexer_ops::lex;
 use utilities::file_cleanup::*;
 use utilities::error_handler::*;
@@ -23,80 +25,82 @@ use code_gen::a_tree_builder::gen_code;
 use code_emission::write_assembly::output;
 
 fn args_error()  -> ! {
-    println!("Usage: glasses <filename> --lex? | --parse? | --codegen? | --tacky? | --validate?");
+    println!("Usage: glasses (-I <dir>)* -std=c23? <filename> --preprocess? | --lex? | --parse? | --codegen? | --tacky? | --validate?");
 
     process::exit(1);
 }
 
-fn check_args(args: &Vec<String>) -> Option<&str> {
-    if args.len() < 2 || args.len() > 3 {
-        args_error();
-    } else if args.len() == 3 {
-        match args[2].as_str() {
-            "--lex" => Some("--lex"),
-            "--parse" => Some("--parse"),
-            "--codegen" => Some("--codegen"),
-            "--tacky" => Some("--tacky"),
-            "--validate" => Some("--validate"),
+//Returns the source file, the stage flag, the include search directories and whether to compile as C23
+fn check_args(args: &[String]) -> (&str, Option<&str>, Vec<PathBuf>, bool) {
+    let mut file: Option<&str> = None;
+    let mut flag: Option<&str> = None;
+    let mut include_dirs: Vec<PathBuf> = Vec::new();
+    let mut c23 = false;
+    let mut rest = args.iter().skip(1);
+
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "-I" => match rest.next() {
+                Some(dir) => include_dirs.push(PathBuf::from(dir)),
+                None => args_error(),
+            },
+            dir if dir.starts_with("-I") => include_dirs.push(PathBuf::from(&dir[2..])),
+            "-std=c23" => c23 = true,
+            "-std=c17" => c23 = false,
+            "--preprocess" | "--lex" | "--parse" | "--codegen" | "--tacky" | "--validate" if flag.is_none() =>
+                flag = Some(arg.as_str()),
+            name if !name.starts_with('-') && file.is_none() && name.ends_with(".c") => file = Some(name),
             _ => args_error(),
         }
-    } else {
-        None
     }
-}
 
-fn preprocess(name: &str) {
-    let output = Command::new("gcc")
-        .arg("-E")
-        .arg("-P")
-        .arg(format!("{}.c", name))
-        .arg("-o")
-        .arg(format!("{}.i", name))
-        .output().unwrap_or_else(|e| panic!("Failed to inflect preprocessor: {}", e));
-
-    if !output.status.success() {
-        println!("Failed to execute preprocessor");
-        process::exit(1);
+    match file {
+        Some(file) => (file, flag, include_dirs, c23),
+        None => args_error(),
     }
 }
 
-fn compile(name: &str, flag: Option<&str>) {
+fn compile(file: &str, name: &str, flag: Option<&str>, include_dirs: Vec<PathBuf>, c23: bool) {
 
-    let buffer: Vec<u8> = fs::read(&format!("{}.i", name)).unwrap();
+    let buffer = match preprocess(file, include_dirs, c23) {
+        Ok(res) => res,
+        Err(msg) => stage_error(msg.as_str()),
+    };
+    if flag == Some("--preprocess") {
+        print!("{}", String::from_utf8_lossy(&buffer));
+        process::exit(0);
+    }
 
-    let tokens =  match lex(buffer) {
+    let tokens =  match lex(buffer, c23) {
         Ok(res) => res,
-        Err(msg) => error_and_clean(msg.as_str(), &format!("{}.i", name)),
+        Err(msg) => stage_error(msg.as_str()),
     };
     if flag == Some("--lex") {
         println!("{:?}", tokens);
-        clean_file(&format!("{}.i", name));
         process::exit(0);
     }
 
     let mut program_ast = parse(tokens);
     if flag == Some("--parse") {
         print_ast(program_ast);
-        clean_file(&format!("{}.i", name));
         process::exit(0);
     }
-    clean_file(&format!("{}.i", name));
 
-    resolve(&mut program_ast);
+    let mut symbols = resolve(&mut program_ast);
     if flag == Some("--validate") {
         println!("Program resolution success");
         print_ast(program_ast);
         process::exit(0);
     }
 
-    let tacky_ir = gen_tacky(program_ast);
+    let tacky_ir = gen_tacky(program_ast, &mut symbols);
     if flag == Some("--tacky") {
         println!("{:?}", tacky_ir);
         process::exit(0);
     }
 
-    let assembly_tree = gen_code(tacky_ir);
-    output(assembly_tree, name);
+    let assembly_tree = gen_code(tacky_ir, &symbols);
+    output(assembly_tree, name, &symbols);
     if flag == Some("--codegen") {
         process::exit(0);
     }
@@ -106,10 +110,10 @@ fn assemble(name: &str) {
     let output = Command::new("gcc")
         .arg(format!("{}.s", name))
         .arg("-o")
-        .arg(&name)
+        .arg(name)
         .output().unwrap_or_else(|e| panic!("Failed to inflect assembler: {}", e));
 
-    let _ = fs::remove_file(&format!("{}.s", name).to_string());
+    clean_file(&format!("{}.s", name));
 
     if !output.status.success() {
         println!("Failed to execute assembler");
@@ -121,10 +125,9 @@ fn main() {
     let args: Vec<String> = env::args().collect();
 
 
-    let flag = check_args(&args);
-    let name = &args[1][..&args[1].len() - 2];
+    let (file, flag, include_dirs, c23) = check_args(&args);
+    let name = &file[..file.len() - 2];
 
-    preprocess(&name);
-    compile(&name, flag);
-    assemble(&name);
+    compile(file, name, flag, include_dirs, c23);
+    assemble(name);
 }
@@ -1,10 +1,11 @@
 
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use crate::lexer::lexer_structs::*;
 use crate::utilities::error_handler::*;
 
 #[derive(Debug)]
 #[derive(Clone)]
+#[derive(PartialEq)]
 pub enum UnaryOp {
     Complement,
     Negate,
@@ -20,6 +21,11 @@ pub enum BinaryOp {
     Multiply,
     Divide,
     Remainder,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
     And,
     Or,
     Equal,
@@ -30,37 +36,248 @@ pub enum BinaryOp {
     GreatEqual,
     Condition,
     Assign,
+    Comma,
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum Const {
+    Int(i32),
+    Long(i64),
+    UInt(u32),
+    ULong(u64),
+    Double(f64),
+    Char(i8),
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum Builtin {
+    Expect,
+    Unreachable,
+    Trap,
+    ConstantP,
 }
 
 #[derive(Debug)]
 #[derive(Clone)]
-pub enum Expr {
-    Constant(i32),
+#[derive(PartialEq)]
+pub enum ExprKind {
+    Constant(Const),
+    String(Vec<u8>),
     Unary(UnaryOp, Box<Expr>),
     Binary(BinaryOp, Box<Expr>, Box<Expr>),
     Var(String),
+    Cast(Type, Box<Expr>),
     Assignment(Box<Expr>, Box<Expr>),
+    CompoundAssignment(BinaryOp, Box<Expr>, Box<Expr>),
+    Postfix(BinaryOp, Box<Expr>),
     Conditional(Box<Expr>, Box<Expr>, Box<Expr>),
+    Comma(Box<Expr>, Box<Expr>),
+    FunctionCall(String, Vec<Expr>),
+    Dereference(Box<Expr>),
+    AddrOf(Box<Expr>),
+    Subscript(Box<Expr>, Box<Expr>),
+    Dot(Box<Expr>, String),
+    Arrow(Box<Expr>, String),
+    SizeOf(Box<Expr>),
+    SizeOfType(Type),
+    AlignOf(Type),
+    StmtExpr(Block),
+    Builtin(Builtin, Vec<Expr>),
 }
 
 #[derive(Debug)]
 #[derive(Clone)]
+#[derive(PartialEq)]
+pub struct Expr {
+    pub kind: ExprKind,
+    pub ty: Option<Type>,
+}
+
+impl Expr {
+    pub fn new(kind: ExprKind) -> Self {
+        Self { kind, ty: None }
+    }
+
+    pub fn typed(kind: ExprKind, ty: Type) -> Self {
+        Self { kind, ty: Some(ty) }
+    }
+
+    pub fn get_type(&self) -> Type {
+        match &self.ty {
+            Some(ty) => ty.clone(),
+            None => panic!("Expression has not been type checked"),
+        }
+    }
+
+    //Rebuilds the expression with f applied to the block of each statement expression in it.
+    //Statement expressions nested inside those blocks are left to f
+    pub fn map_blocks(&self, f: &mut dyn FnMut(&Block) -> Block) -> Expr {
+        let kind = match &self.kind {
+            ExprKind::StmtExpr(block) => ExprKind::StmtExpr(f(block)),
+            ExprKind::Unary(op, inner) => ExprKind::Unary(op.clone(), Box::from(inner.map_blocks(f))),
+            ExprKind::Binary(op, left, right) =>
+                ExprKind::Binary(op.clone(), Box::from(left.map_blocks(f)), Box::from(right.map_blocks(f))),
+            ExprKind::Cast(target, inner) => ExprKind::Cast(target.clone(), Box::from(inner.map_blocks(f))),
+            ExprKind::Assignment(left, right) =>
+                ExprKind::Assignment(Box::from(left.map_blocks(f)), Box::from(right.map_blocks(f))),
+            ExprKind::CompoundAssignment(op, left, right) =>
+                ExprKind::CompoundAssignment(op.clone(), Box::from(left.map_blocks(f)), Box::from(right.map_blocks(f))),
+            ExprKind::Postfix(op, inner) => ExprKind::Postfix(op.clone(), Box::from(inner.map_blocks(f))),
+            ExprKind::Conditional(cond, then, otherwise) => ExprKind::Conditional(Box::from(cond.map_blocks(f)),
+                Box::from(then.map_blocks(f)), Box::from(otherwise.map_blocks(f))),
+            ExprKind::Comma(left, right) => ExprKind::Comma(Box::from(left.map_blocks(f)), Box::from(right.map_blocks(f))),
+            ExprKind::FunctionCall(name, args) =>
+                ExprKind::FunctionCall(name.clone(), args.iter().map(|arg| arg.map_blocks(f)).collect()),
+            ExprKind::Builtin(builtin, args) =>
+                ExprKind::Builtin(builtin.clone(), args.iter().map(|arg| arg.map_blocks(f)).collect()),
+            ExprKind::Dereference(inner) => ExprKind::Dereference(Box::from(inner.map_blocks(f))),
+            ExprKind::AddrOf(inner) => ExprKind::AddrOf(Box::from(inner.map_blocks(f))),
+            ExprKind::Subscript(left, right) =>
+                ExprKind::Subscript(Box::from(left.map_blocks(f)), Box::from(right.map_blocks(f))),
+            ExprKind::Dot(inner, member) => ExprKind::Dot(Box::from(inner.map_blocks(f)), member.clone()),
+            ExprKind::Arrow(inner, member) => ExprKind::Arrow(Box::from(inner.map_blocks(f)), member.clone()),
+            ExprKind::SizeOf(inner) => ExprKind::SizeOf(Box::from(inner.map_blocks(f))),
+            ExprKind::Constant(_) | ExprKind::String(_) | ExprKind::Var(_) | ExprKind::SizeOfType(_) |
+                ExprKind::AlignOf(_) => self.kind.clone(),
+        };
+
+        Expr { kind, ty: self.ty.clone() }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
 pub enum Stmt {
     Return(Expr),
     If(Expr, Box<Stmt>, Option<Box<Stmt>>),
     Expression(Expr),
     Compound(Block),
+    Break(String),
+    Continue(String),
+    While(Expr, Box<Stmt>, String),
+    DoWhile(Box<Stmt>, Expr, String),
+    For(ForInit, Option<Expr>, Option<Expr>, Box<Stmt>, String),
+    Switch(Expr, Box<Stmt>, String, Vec<(Option<Const>, String)>),
+    Case(Expr, Box<Stmt>, String),
+    Default(Box<Stmt>, String),
+    Labeled(String, Box<Stmt>),
+    Goto(String),
     Null,
 }
 
 #[derive(Debug)]
 #[derive(Clone)]
+#[derive(PartialEq)]
+pub enum ForInit {
+    InitDecl(Vec<VarDecl>),
+    InitExpr(Option<Expr>),
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum Initializer {
+    SingleInit(Expr),
+    CompoundInit(Vec<Initializer>),
+}
+
+impl Initializer {
+    pub fn map_blocks(&self, f: &mut dyn FnMut(&Block) -> Block) -> Initializer {
+        match self {
+            Initializer::SingleInit(expr) => Initializer::SingleInit(expr.map_blocks(f)),
+            Initializer::CompoundInit(inits) =>
+                Initializer::CompoundInit(inits.iter().map(|init| init.map_blocks(f)).collect()),
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum VarDecl {
+    Declaration(String, Option<Initializer>, Type, Option<StorageClass>),
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum FuncDecl {
+    Function(String, Vec<String>, Option<Block>, Type, Option<StorageClass>),
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum MemberDecl {
+    Member(String, Type),
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum StructDecl {
+    Struct(String, Option<Vec<MemberDecl>>),
+    Union(String, Option<Vec<MemberDecl>>),
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum EnumDecl {
+    Enum(Option<String>, Vec<Enumerator>),
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum Enumerator {
+    Enumerator(String, Option<Expr>),
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum TypedefDecl {
+    Typedef(String, Type),
+}
+
+//The condition, the optional message and the line the assertion is on
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum StaticAssertDecl {
+    StaticAssert(Expr, Option<Vec<u8>>, Location),
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum StorageClass {
+    Static,
+    Extern,
+    Typedef,
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
 pub enum Decl {
-    Declaration(String, Option<Expr>),
+    Var(VarDecl),
+    Func(FuncDecl),
+    Struct(StructDecl),
+    Enum(EnumDecl),
+    Typedef(TypedefDecl),
+    StaticAssert(StaticAssertDecl),
 }
 
 #[derive(Debug)]
 #[derive(Clone)]
+#[derive(PartialEq)]
 pub enum BlockItem {
     S(Stmt),
     D(Decl),
@@ -68,22 +285,181 @@ pub enum BlockItem {
 
 #[derive(Debug)]
 #[derive(Clone)]
+#[derive(PartialEq)]
 pub enum Block {
     Block(Vec<BlockItem>),
 }
 
 #[derive(Debug)]
-pub enum FuncDef {
-    Function(String, Block),
+pub enum Program {
+    Program(Vec<Decl>),
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum Type {
+    Bool,
+    Char,
+    SChar,
+    UChar,
+    Int,
+    Long,
+    UInt,
+    ULong,
+    Double,
+    Pointer(Box<Type>),
+    Array(Box<Type>, i32),
+    //A dimension that is still an expression, which resolution evaluates, or that is left out for the initializer to decide
+    ArrayExpr(Box<Type>, Option<Box<Expr>>),
+    Structure(String),
+    Union(String),
+    Enum(String),
+    Typedef(String),
+    Function(Vec<Type>, Box<Type>),
+    //Never wraps an array, since qualifying an array type qualifies its elements
+    Const(Box<Type>),
+    //The type of expressions that produce no value, such as a call to __builtin_trap. It can't be written in a declaration
+    Void,
+}
+
+impl Type {
+    pub fn size(&self) -> i32 {
+        match self {
+            Type::Bool | Type::Char | Type::SChar | Type::UChar => 1,
+            Type::Int | Type::UInt | Type::Enum(_) => 4,
+            Type::Long | Type::ULong | Type::Double | Type::Pointer(_) => 8,
+            Type::Array(elem, count) => elem.size() * count,
+            Type::Function(_, _) | Type::Void => 0,
+            Type::Const(inner) => inner.size(),
+            Type::Structure(_) | Type::Union(_) => panic!("Structure and union sizes are only known to the symbol table"),
+            Type::Typedef(_) => panic!("Typedef names are replaced during resolution"),
+            Type::ArrayExpr(_, _) => panic!("Array dimensions are evaluated during type checking"),
+        }
+    }
+
+    pub fn alignment(&self) -> i32 {
+        match self {
+            Type::Array(elem, _) => elem.alignment(),
+            _ => self.size(),
+        }
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(self.unqualified(), Type::Char | Type::SChar | Type::Int | Type::Long | Type::Double)
+    }
+
+    pub fn is_arithmetic(&self) -> bool {
+        self.is_integer() || *self.unqualified() == Type::Double
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.promotes_to_int() || matches!(self.unqualified(), Type::Int | Type::Long | Type::UInt | Type::ULong)
+    }
+
+    pub fn is_scalar(&self) -> bool {
+        !matches!(self.unqualified(), Type::Array(_, _) | Type::ArrayExpr(_, _) | Type::Structure(_) | Type::Union(_)
+            | Type::Function(_, _) | Type::Void)
+    }
+
+    pub fn is_character(&self) -> bool {
+        matches!(self.unqualified(), Type::Char | Type::SChar | Type::UChar)
+    }
+
+    //Integer types narrower than int, which arithmetic promotes to int first
+    pub fn promotes_to_int(&self) -> bool {
+        self.is_character() || *self.unqualified() == Type::Bool
+    }
+
+    pub fn make_const(self) -> Type {
+        match self {
+            Type::Array(elem, count) => Type::Array(Box::from(elem.make_const()), count),
+            Type::ArrayExpr(elem, dim) => Type::ArrayExpr(Box::from(elem.make_const()), dim),
+            Type::Const(_) | Type::Function(_, _) => self,
+            _ => Type::Const(Box::from(self)),
+        }
+    }
+
+    pub fn is_const(&self) -> bool {
+        matches!(self, Type::Const(_))
+    }
+
+    //The type with its top level qualifiers removed, which is the type of an lvalue's value
+    pub fn unqualified(&self) -> &Type {
+        match self {
+            Type::Const(inner) => inner,
+            _ => self,
+        }
+    }
+
+    //Qualifiers only matter to type checking, so they are removed at every level before TACKY generation
+    pub fn strip_qualifiers(&self) -> Type {
+        match self {
+            Type::Const(inner) => inner.strip_qualifiers(),
+            Type::Pointer(referenced) => Type::Pointer(Box::from(referenced.strip_qualifiers())),
+            Type::Array(elem, count) => Type::Array(Box::from(elem.strip_qualifiers()), *count),
+            Type::ArrayExpr(elem, dim) => Type::ArrayExpr(Box::from(elem.strip_qualifiers()), dim.clone()),
+            Type::Function(params, ret) => Type::Function(
+                params.iter().map(Type::strip_qualifiers).collect(),
+                Box::from(ret.strip_qualifiers())),
+            _ => self.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Declarator {
+    Ident(String),
+    //Whether the pointer itself is const
+    Pointer(Box<Declarator>, bool),
+    Array(Box<Declarator>, Option<Expr>),
+    Function(Vec<ParamInfo>, Box<Declarator>),
+}
+
+#[derive(Debug)]
+pub enum ParamInfo {
+    Param(Type, Declarator),
+}
+
+#[derive(Debug)]
+pub enum AbstractDeclarator {
+    Pointer(Box<AbstractDeclarator>, bool),
+    Array(Box<AbstractDeclarator>, Option<Expr>),
+    Base,
 }
 
 pub struct TokenQue {
-    tokens: VecDeque<(Tkn, u32)>
+    tokens: VecDeque<(Tkn, Location)>,
+    //Ordinary identifiers declared in each open scope, and whether each one is a typedef name
+    scopes: Vec<HashMap<String, bool>>,
 }
 
 impl TokenQue {
-    pub fn new(tokens: Vec<(Tkn, u32)>) -> Self {
-        Self { tokens: VecDeque::from(tokens) }
+    pub fn new(tokens: Vec<(Tkn, Location)>) -> Self {
+        Self { tokens: VecDeque::from(tokens), scopes: vec![HashMap::new()] }
+    }
+
+    pub fn enter_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    pub fn exit_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    pub fn is_file_scope(&self) -> bool {
+        self.scopes.len() == 1
+    }
+
+    pub fn declare_ident(&mut self, name: &str, is_typedef: bool) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), is_typedef);
+        }
+    }
+
+    //The innermost declaration wins, so a variable can shadow an outer typedef name
+    pub fn is_typedef_name(&self, name: &str) -> bool {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name)).copied().unwrap_or(false)
     }
 
     pub fn consume(&mut self, expected: Tkn, msg: &str) {
@@ -92,7 +468,7 @@ impl TokenQue {
         if expected == front.0 {
             let _ = self.next_token();
         } else {
-            parser_error(front.1, msg);
+            parser_error(&front.1, msg);
         }
     }
 
@@ -103,22 +479,24 @@ impl TokenQue {
         }
     }
 
-    pub fn next_token(&mut self) -> (Tkn, u32) {
-        let token = match self.tokens.pop_front() {
+    pub fn next_token(&mut self) -> (Tkn, Location) {
+        match self.tokens.pop_front() {
             Some(res) => res,
             None => parser_error_no_line("Expected token but recieved none"),
-        };
-
-        token
+        }
     }
 
-    pub fn peek_next_token(&mut self) -> &(Tkn, u32) {
+    pub fn peek_next_token(&mut self) -> &(Tkn, Location) {
         match self.tokens.front() {
             Some(res) => res,
             None => parser_error_no_line("Expected token but recieved none"),
         }
     }
 
+    pub fn peek_nth_token(&self, n: usize) -> Option<&Tkn> {
+        self.tokens.get(n).map(|token| &token.0)
+    }
+
     pub fn len(&self) -> usize {
         self.tokens.len()
     }
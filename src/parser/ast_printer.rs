@@ -1,17 +1,27 @@
 
 use crate::parser::ast::*;
 
-pub fn print_ast(ast: FuncDef) {
+pub fn print_ast(ast: Program) {
     println!("Program(");
-    print_function(ast, 1);
+    match ast {
+        Program::Program(decls) => {
+            for decl in decls.iter() {
+                print_decl(decl, 1);
+            }
+        },
+    };
     println!(")");
 }
 
-fn print_function(ast: FuncDef, indent: u32) {
+fn print_function(ast: &FuncDecl, indent: u32) {
     match ast {
-        FuncDef::Function(name, body) => {
-            println!("{}{} <- Function(", level(indent), name);
-            print_body(&body, indent + 1);
+        FuncDecl::Function(name, params, body, fun_type, storage) => {
+            println!("{}{} <- {}Function {:?}(", level(indent), name, storage_class(storage), fun_type);
+            println!("{}Parameters: {}", level(indent + 1), params.join(", "));
+            match body {
+                Some(body) => print_body(body, indent + 1),
+                None => println!("{}No Body", level(indent + 1)),
+            }
             println!("{})", level(indent));
         },
     };
@@ -43,20 +53,82 @@ fn print_block_item(item: &BlockItem, indent: u32) {
 
 fn print_decl(decl: &Decl, indent: u32) {
     match decl {
-        Decl::Declaration(ident, init) => {
-            println!("{}{} <- Declaration(", level(indent), ident);
+        Decl::Var(var) => print_var_decl(var, indent),
+        Decl::Func(func) => print_function(func, indent),
+        Decl::Struct(decl) => print_struct_decl(decl, indent),
+        Decl::Enum(decl) => print_enum_decl(decl, indent),
+        Decl::Typedef(TypedefDecl::Typedef(name, target)) => println!("{}Typedef {}: {:?}", level(indent), name, target),
+        Decl::StaticAssert(StaticAssertDecl::StaticAssert(cond, msg, _)) => {
+            println!("{}StaticAssert(", level(indent));
+            print_expr(cond, indent + 1);
+            if let Some(msg) = msg {
+                println!("{}{:?}", level(indent + 1), String::from_utf8_lossy(msg));
+            }
+            println!("{})", level(indent));
+        },
+    }
+}
+
+fn print_enum_decl(decl: &EnumDecl, indent: u32) {
+    match decl {
+        EnumDecl::Enum(tag, enumerators) => {
+            println!("{}enum {}(", level(indent), tag.as_deref().unwrap_or("<anonymous>"));
+            for Enumerator::Enumerator(name, value) in enumerators.iter() {
+                println!("{}{}", level(indent + 1), name);
+                if let Some(value) = value {
+                    print_expr(value, indent + 2);
+                }
+            }
+            println!("{})", level(indent));
+        }
+    }
+}
+
+fn print_struct_decl(decl: &StructDecl, indent: u32) {
+    let (kind, tag, members) = match decl {
+        StructDecl::Struct(tag, members) => ("struct", tag, members),
+        StructDecl::Union(tag, members) => ("union", tag, members),
+    };
+
+    println!("{}{} {}(", level(indent), kind, tag);
+    match members {
+        Some(members) => for MemberDecl::Member(name, member_type) in members.iter() {
+            println!("{}{}: {:?}", level(indent + 1), name, member_type);
+        },
+        None => println!("{}Incomplete", level(indent + 1)),
+    }
+    println!("{})", level(indent));
+}
+
+fn print_var_decl(decl: &VarDecl, indent: u32) {
+    match decl {
+        VarDecl::Declaration(ident, init, var_type, storage) => {
+            println!("{}{} <- {}Declaration {:?}(", level(indent), ident, storage_class(storage), var_type);
             print_init(init, indent + 1);
         }
     }
 }
 
-fn print_init(init: &Option<Expr>, indent: u32) {
+fn print_init(init: &Option<Initializer>, indent: u32) {
     match init {
-        Some(expr) => print_expr(expr, indent),
+        Some(init) => print_initializer(init, indent),
         None => println!("{}Uninitialized", level(indent)),
     }
 }
 
+fn print_initializer(init: &Initializer, indent: u32) {
+    match init {
+        Initializer::SingleInit(expr) => print_expr(expr, indent),
+        Initializer::CompoundInit(inits) => {
+            println!("{}Compound(", level(indent));
+            for init in inits.iter() {
+                print_initializer(init, indent + 1);
+            }
+            println!("{})", level(indent));
+        },
+    }
+}
+
 fn print_stmt(stmt: &Stmt, indent: u32) {
     match stmt {
         Stmt::Return(expr) => {
@@ -69,12 +141,9 @@ fn print_stmt(stmt: &Stmt, indent: u32) {
             print_expr(cond, indent + 2);
             println!("{}Then:", level(indent + 1));
             print_stmt(then, indent + 2);
-            match otherwise {
-                Some(stmt) => {
-                    println!("{}Else:", level(indent + 1));
-                    print_stmt(stmt, indent + 2);
-                },
-                None => (),
+            if let Some(stmt) = otherwise {
+                println!("{}Else:", level(indent + 1));
+                print_stmt(stmt, indent + 2);
             }
             println!("{})", level(indent));
         }
@@ -84,33 +153,147 @@ fn print_stmt(stmt: &Stmt, indent: u32) {
             println!("{})", level(indent));
         },
         Stmt::Compound(block) => print_block(block, indent + 1),
+        Stmt::Break(label) => println!("{}Break({})", level(indent), label),
+        Stmt::Continue(label) => println!("{}Continue({})", level(indent), label),
+        Stmt::While(cond, body, label) => {
+            println!("{}While {}(", level(indent), label);
+            print_expr(cond, indent + 2);
+            println!("{}Do:", level(indent + 1));
+            print_stmt(body, indent + 2);
+            println!("{})", level(indent));
+        },
+        Stmt::DoWhile(body, cond, label) => {
+            println!("{}Do {}(", level(indent), label);
+            print_stmt(body, indent + 2);
+            println!("{}While:", level(indent + 1));
+            print_expr(cond, indent + 2);
+            println!("{})", level(indent));
+        },
+        Stmt::For(init, cond, post, body, label) => {
+            println!("{}For {}(", level(indent), label);
+            println!("{}Init:", level(indent + 1));
+            print_for_init(init, indent + 2);
+            println!("{}Condition:", level(indent + 1));
+            print_optional_expr(cond, indent + 2);
+            println!("{}Post:", level(indent + 1));
+            print_optional_expr(post, indent + 2);
+            println!("{}Do:", level(indent + 1));
+            print_stmt(body, indent + 2);
+            println!("{})", level(indent));
+        },
+        Stmt::Switch(cond, body, label, cases) => {
+            println!("{}Switch {}(", level(indent), label);
+            print_expr(cond, indent + 2);
+            for (value, case_label) in cases.iter() {
+                match value {
+                    Some(value) => println!("{}Case {:?} -> {}", level(indent + 1), value, case_label),
+                    None => println!("{}Default -> {}", level(indent + 1), case_label),
+                }
+            }
+            println!("{}Body:", level(indent + 1));
+            print_stmt(body, indent + 2);
+            println!("{})", level(indent));
+        },
+        Stmt::Case(value, body, label) => {
+            println!("{}Case {}(", level(indent), label);
+            print_expr(value, indent + 2);
+            print_stmt(body, indent + 1);
+            println!("{})", level(indent));
+        },
+        Stmt::Default(body, label) => {
+            println!("{}Default {}(", level(indent), label);
+            print_stmt(body, indent + 1);
+            println!("{})", level(indent));
+        },
+        Stmt::Labeled(label, body) => {
+            println!("{}Label {}(", level(indent), label);
+            print_stmt(body, indent + 1);
+            println!("{})", level(indent));
+        },
+        Stmt::Goto(label) => println!("{}Goto({})", level(indent), label),
         Stmt::Null => println!("{}Null Statement", level(indent)),
     }
 }
 
-fn print_expr(expr: &Expr, indent: u32) {
+fn print_for_init(init: &ForInit, indent: u32) {
+    match init {
+        ForInit::InitDecl(decls) => {
+            for decl in decls.iter() {
+                print_var_decl(decl, indent);
+            }
+        },
+        ForInit::InitExpr(expr) => print_optional_expr(expr, indent),
+    }
+}
+
+fn print_optional_expr(expr: &Option<Expr>, indent: u32) {
     match expr {
-        Expr::Constant(val) => {
+        Some(expr) => print_expr(expr, indent),
+        None => println!("{}Empty", level(indent)),
+    }
+}
+
+fn print_expr(expr: &Expr, indent: u32) {
+    if let Some(ty) = &expr.ty {
+        println!("{}<{:?}>", level(indent), ty);
+    }
+
+    match &expr.kind {
+        ExprKind::Constant(Const::Int(val)) => {
             println!("{}Constant({})", level(indent), val);
         },
-        Expr::Unary(op, expr) => {
+        ExprKind::Constant(Const::Long(val)) => {
+            println!("{}Constant({}L)", level(indent), val);
+        },
+        ExprKind::Constant(Const::UInt(val)) => {
+            println!("{}Constant({}U)", level(indent), val);
+        },
+        ExprKind::Constant(Const::ULong(val)) => {
+            println!("{}Constant({}UL)", level(indent), val);
+        },
+        ExprKind::Constant(Const::Double(val)) => {
+            println!("{}Constant({:?})", level(indent), val);
+        },
+        ExprKind::Constant(Const::Char(val)) => {
+            println!("{}Constant({}c)", level(indent), val);
+        },
+        ExprKind::String(bytes) => {
+            println!("{}String({:?})", level(indent), String::from_utf8_lossy(bytes));
+        },
+        ExprKind::Cast(target, inner) => {
+            println!("{}Cast {:?}(", level(indent), target);
+            print_expr(inner, indent + 1);
+            println!("{})", level(indent));
+        },
+        ExprKind::SizeOf(inner) => {
+            println!("{}SizeOf(", level(indent));
+            print_expr(inner, indent + 1);
+            println!("{})", level(indent));
+        },
+        ExprKind::SizeOfType(target) => {
+            println!("{}SizeOf {:?}", level(indent), target);
+        },
+        ExprKind::AlignOf(target) => {
+            println!("{}AlignOf {:?}", level(indent), target);
+        },
+        ExprKind::Unary(op, expr) => {
             print!("{}Unary ", level(indent));
             print_unary(op);
-            print_expr(&*expr, indent + 1);
+            print_expr(expr, indent + 1);
         },
-        Expr::Binary(op, left, right) => {
+        ExprKind::Binary(op, left, right) => {
             print!("{}Binary ", level(indent));
             print_binary(op);
             println!("{}Left(", level(indent + 1));
-            print_expr(&*left, indent + 2);
+            print_expr(left, indent + 2);
             println!("{})Right(", level(indent + 1));
-            print_expr(&*right, indent + 2);
+            print_expr(right, indent + 2);
             println!("{})", level(indent + 1));
         },
-        Expr::Var(ident) => {
+        ExprKind::Var(ident) => {
             println!("{}Var: {}", level(indent), ident);
         },
-        Expr::Conditional(left, middle, right) => {
+        ExprKind::Conditional(left, middle, right) => {
             println!("{}Conditional(", level(indent));
             print_expr(left, indent + 2);
             println!("{}?", level(indent + 1));
@@ -119,14 +302,84 @@ fn print_expr(expr: &Expr, indent: u32) {
             print_expr(right, indent + 2);
             println!("{})", level(indent));
         }
-        Expr::Assignment(left, right) => {
+        ExprKind::Comma(left, right) => {
+            println!("{}Comma(", level(indent));
+            print_expr(left, indent + 2);
+            println!("{},", level(indent + 1));
+            print_expr(right, indent + 2);
+            println!("{})", level(indent));
+        },
+        ExprKind::Assignment(left, right) => {
             println!("{}Assignment:", level(indent));
             println!("{}Into(", level(indent + 1));
-            print_expr(&*left, indent + 2);
+            print_expr(left, indent + 2);
             println!("{})From(", level(indent + 1));
-            print_expr(&*right, indent + 2);
+            print_expr(right, indent + 2);
             println!("{})", level(indent + 1));
-        }
+        },
+        ExprKind::CompoundAssignment(op, left, right) => {
+            print!("{}CompoundAssignment ", level(indent));
+            print_binary(op);
+            println!("{}Into(", level(indent + 1));
+            print_expr(left, indent + 2);
+            println!("{})From(", level(indent + 1));
+            print_expr(right, indent + 2);
+            println!("{})", level(indent + 1));
+        },
+        ExprKind::Postfix(op, inner) => {
+            print!("{}Postfix ", level(indent));
+            print_binary(op);
+            println!("{}(", level(indent + 1));
+            print_expr(inner, indent + 2);
+            println!("{})", level(indent + 1));
+        },
+        ExprKind::FunctionCall(name, args) => {
+            println!("{}Call {}(", level(indent), name);
+            for arg in args.iter() {
+                print_expr(arg, indent + 1);
+            }
+            println!("{})", level(indent));
+        },
+        ExprKind::Dereference(inner) => {
+            println!("{}Dereference(", level(indent));
+            print_expr(inner, indent + 1);
+            println!("{})", level(indent));
+        },
+        ExprKind::AddrOf(inner) => {
+            println!("{}AddrOf(", level(indent));
+            print_expr(inner, indent + 1);
+            println!("{})", level(indent));
+        },
+        ExprKind::Dot(inner, member) => {
+            println!("{}Member .{}(", level(indent), member);
+            print_expr(inner, indent + 1);
+            println!("{})", level(indent));
+        },
+        ExprKind::Arrow(inner, member) => {
+            println!("{}Member ->{}(", level(indent), member);
+            print_expr(inner, indent + 1);
+            println!("{})", level(indent));
+        },
+        ExprKind::Subscript(array, index) => {
+            println!("{}Subscript(", level(indent));
+            print_expr(array, indent + 1);
+            println!("{}[", level(indent + 1));
+            print_expr(index, indent + 2);
+            println!("{}]", level(indent + 1));
+            println!("{})", level(indent));
+        },
+        ExprKind::StmtExpr(block) => {
+            println!("{}StmtExpr(", level(indent));
+            print_block(block, indent + 1);
+            println!("{})", level(indent));
+        },
+        ExprKind::Builtin(builtin, args) => {
+            println!("{}Builtin {:?}(", level(indent), builtin);
+            for arg in args.iter() {
+                print_expr(arg, indent + 1);
+            }
+            println!("{})", level(indent));
+        },
     }
 }
 
@@ -145,6 +398,11 @@ fn print_binary(op: &BinaryOp) {
         BinaryOp::Multiply => println!("*:"),
         BinaryOp::Divide => println!("/:"),
         BinaryOp::Remainder => println!("%:"),
+        BinaryOp::BitAnd => println!("&:"),
+        BinaryOp::BitOr => println!("|:"),
+        BinaryOp::BitXor => println!("^:"),
+        BinaryOp::ShiftLeft => println!("<<:"),
+        BinaryOp::ShiftRight => println!(">>:"),
         BinaryOp::Equal => println!("==:"),
         BinaryOp::NotEqual => println!("!=:"),
         BinaryOp::LessThan => println!("<:"),
@@ -155,9 +413,19 @@ fn print_binary(op: &BinaryOp) {
         BinaryOp::Or => println!("||:"),
         BinaryOp::Assign => (),
         BinaryOp::Condition => (),
+        BinaryOp::Comma => (),
     };
 }
 
+fn storage_class(storage: &Option<StorageClass>) -> &str {
+    match storage {
+        Some(StorageClass::Static) => "Static ",
+        Some(StorageClass::Extern) => "Extern ",
+        Some(StorageClass::Typedef) => "Typedef ",
+        None => "",
+    }
+}
+
 fn level(num: u32) -> String {
     let mut indent = String::new();
 
@@ -1,89 +1,667 @@
 
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use crate::lexer::lexer_structs::*;
 use crate::utilities::error_handler::parser_error;
 use crate::parser::ast::*;
 
-pub fn parse(tokens: Vec<(Tkn, u32)>) -> FuncDef {
+const UNNAMED_PARAM: &str = ".param";
+const ANONYMOUS_TAG: &str = ".anonymous";
+
+static ANONYMOUS_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+pub fn parse(tokens: Vec<(Tkn, Location)>) -> Program {
     let mut token_que = TokenQue::new(tokens);
-    let mut program: Option<FuncDef> = None;
+    let mut decls: Vec<Decl> = Vec::new();
 
     while token_que.len() != 0 {
-        program = Some(fn_decl(&mut token_que));
+        decls.extend(declaration(&mut token_que));
     }
 
-    program.unwrap()
+    Program::Program(decls)
 }
 
-fn fn_decl(tokens: &mut TokenQue) -> FuncDef {
-    tokens.consume(Tkn::Key(Keyword::Int), "Expected int");
+fn is_type_specifier(token: &Tkn) -> bool {
+    matches!(token, Tkn::Key(Keyword::Int) | Tkn::Key(Keyword::Long) | Tkn::Key(Keyword::Signed)
+        | Tkn::Key(Keyword::Unsigned) | Tkn::Key(Keyword::Double) | Tkn::Key(Keyword::Char) | Tkn::Key(Keyword::Bool)
+        | Tkn::Key(Keyword::Struct)
+        | Tkn::Key(Keyword::Union) | Tkn::Key(Keyword::Enum))
+}
 
-    let expected_ident = tokens.next_token();
-    let name = match expected_ident.0 {
-        Tkn::Identifier(name) => name,
-        _ => parser_error(expected_ident.1, "Expected function identifier"),
+fn is_type_qualifier(token: &Tkn) -> bool {
+    *token == Tkn::Key(Keyword::Const)
+}
+
+fn is_storage_class(token: &Tkn) -> bool {
+    matches!(token, Tkn::Key(Keyword::Static) | Tkn::Key(Keyword::Extern) | Tkn::Key(Keyword::Typedef)
+        | Tkn::Key(Keyword::Auto) | Tkn::Key(Keyword::Register))
+}
+
+//Whether the nth token names a type, which for an identifier depends on the typedef names in scope
+fn starts_type(tokens: &TokenQue, n: usize) -> bool {
+    match tokens.peek_nth_token(n) {
+        Some(Tkn::Identifier(name)) => tokens.is_typedef_name(name),
+        Some(token) => is_type_specifier(token) || is_type_qualifier(token),
+        None => false,
+    }
+}
+
+fn starts_declaration(tokens: &TokenQue) -> bool {
+    match tokens.peek_nth_token(0) {
+        //A typedef name followed by ':' is a label, since labels have their own namespace
+        Some(Tkn::Identifier(_)) => starts_type(tokens, 0) && tokens.peek_nth_token(1) != Some(&Tkn::Colon),
+        Some(token) => is_storage_class(token) || starts_type(tokens, 0) || *token == Tkn::Key(Keyword::StaticAssert),
+        None => false,
+    }
+}
+
+fn parse_type(tokens: &mut TokenQue) -> Type {
+    let line = tokens.peek_next_token().1.clone();
+    let (parsed_type, storage, defs) = parse_specifiers(tokens);
+    if storage.is_some() {
+        parser_error(&line, "Storage class in type name");
+    }
+    if !defs.is_empty() {
+        parser_error(&line, "Structure, union or enumeration defined in a type name");
+    }
+
+    parsed_type
+}
+
+fn struct_tag(tokens: &mut TokenQue) -> String {
+    let current = tokens.next_token();
+    match current.0 {
+        Tkn::Identifier(tag) => tag,
+        _ => parser_error(&current.1, "Expected structure, union or enumeration tag"),
+    }
+}
+
+//Anonymous structures, unions and enumerations get a tag that no identifier can clash with
+fn anonymous_tag() -> String {
+    format!("{}{}", ANONYMOUS_TAG, ANONYMOUS_COUNTER.fetch_add(1, Ordering::Relaxed))
+}
+
+//With a member list the specifier also defines the tag, and the definition goes ahead of the declarations using it
+fn struct_specifier(is_union: bool, tokens: &mut TokenQue, defs: &mut Vec<Decl>) -> String {
+    let tag = match tokens.peek_next_token().0 {
+        Tkn::LeftBrace => anonymous_tag(),
+        _ => struct_tag(tokens),
+    };
+    if tokens.peek_next_token().0 != Tkn::LeftBrace {
+        return tag;
+    }
+
+    tokens.next();
+    let mut members: Vec<MemberDecl> = Vec::new();
+    while tokens.peek_next_token().0 != Tkn::RightBrace {
+        members.extend(member_declaration(tokens, defs));
+    }
+    let line = tokens.next_token().1;
+
+    if members.is_empty() {
+        parser_error(&line, "Structure or union declared without members");
+    }
+
+    let def = if is_union { StructDecl::Union(tag.clone(), Some(members)) } else { StructDecl::Struct(tag.clone(), Some(members)) };
+    defs.push(Decl::Struct(def));
+
+    tag
+}
+
+fn parse_specifiers(tokens: &mut TokenQue) -> (Type, Option<StorageClass>, Vec<Decl>) {
+    let line = tokens.peek_next_token().1.clone();
+    let mut defs: Vec<Decl> = Vec::new();
+    let mut types: Vec<Keyword> = Vec::new();
+    let mut storage: Vec<Option<StorageClass>> = Vec::new();
+    let mut tag: Option<String> = None;
+    let mut typedef_name: Option<String> = None;
+    let mut is_const = false;
+
+    loop {
+        //Once any type specifier has been seen, an identifier has to be the declarator instead
+        let is_typedef = types.is_empty() && typedef_name.is_none() && starts_type(tokens, 0);
+        match tokens.peek_nth_token(0) {
+            Some(Tkn::Identifier(name)) if is_typedef => typedef_name = Some(name.clone()),
+            Some(token) if is_storage_class(token) || is_type_specifier(token) || is_type_qualifier(token) => (),
+            _ => break,
+        }
+
+        match tokens.next_token().0 {
+            Tkn::Key(Keyword::Static) => storage.push(Some(StorageClass::Static)),
+            Tkn::Key(Keyword::Extern) => storage.push(Some(StorageClass::Extern)),
+            Tkn::Key(Keyword::Typedef) => storage.push(Some(StorageClass::Typedef)),
+            //auto and register only allow what a block scope declaration already implies
+            Tkn::Key(Keyword::Auto | Keyword::Register) => {
+                if tokens.is_file_scope() {
+                    parser_error(&line, "auto or register used at file scope");
+                }
+                storage.push(None);
+            },
+            //Repeating a qualifier is allowed and means the same as writing it once
+            Tkn::Key(Keyword::Const) => is_const = true,
+            Tkn::Key(key @ (Keyword::Struct | Keyword::Union)) => {
+                tag = Some(struct_specifier(key == Keyword::Union, tokens, &mut defs));
+                types.push(key);
+            },
+            Tkn::Key(Keyword::Enum) => {
+                tag = Some(enum_specifier(tokens, &mut defs));
+                types.push(Keyword::Enum);
+            },
+            Tkn::Key(key) => types.push(key),
+            _ => (),
+        }
+    }
+
+    if storage.len() > 1 {
+        parser_error(&line, "Multiple storage classes in declaration specifiers");
+    }
+
+    let base_type = type_from_specifiers(&types, tag, typedef_name, &line);
+    (if is_const { base_type.make_const() } else { base_type }, storage.pop().flatten(), defs)
+}
+
+fn type_from_specifiers(types: &[Keyword], tag: Option<String>, typedef_name: Option<String>, line: &Location) -> Type {
+    //Specifiers can come in any order, so only how many times each one appears matters
+    let count = |key: &Keyword| types.iter().filter(|k| *k == key).count();
+    for key in types.iter() {
+        let allowed = if *key == Keyword::Long { 2 } else { 1 };
+        if count(key) > allowed {
+            let spelling = match key {
+                Keyword::Bool => String::from("_Bool"),
+                _ => format!("{:?}", key).to_lowercase(),
+            };
+            parser_error(line, format!("Duplicate type specifier '{}'", spelling).as_str());
+        }
+    }
+
+    if let Some(name) = typedef_name {
+        if !types.is_empty() {
+            parser_error(line, format!("Type specifier combined with typedef name {}", name).as_str());
+        }
+        return Type::Typedef(name);
+    }
+
+    if let Some(tag) = tag {
+        if types.len() != 1 {
+            parser_error(line, "Invalid combination of type specifiers");
+        }
+        return match types[0] {
+            Keyword::Union => Type::Union(tag),
+            Keyword::Enum => Type::Enum(tag),
+            _ => Type::Structure(tag),
+        };
+    }
+
+    let (has_signed, has_unsigned) = (count(&Keyword::Signed) > 0, count(&Keyword::Unsigned) > 0);
+    if types.is_empty() {
+        parser_error(line, "Expected type specifier");
+    }
+    if has_signed && has_unsigned {
+        parser_error(line, "Both signed and unsigned in declaration specifiers");
+    }
+
+    if types == [Keyword::Double] {
+        return Type::Double;
+    }
+    if types == [Keyword::Bool] {
+        return Type::Bool;
+    }
+    if count(&Keyword::Double) > 0 || count(&Keyword::Bool) > 0 {
+        parser_error(line, "Invalid combination of type specifiers");
+    }
+
+    if count(&Keyword::Char) > 0 {
+        if count(&Keyword::Int) > 0 || count(&Keyword::Long) > 0 {
+            parser_error(line, "Invalid combination of type specifiers");
+        }
+        return match (has_signed, has_unsigned) {
+            (true, _) => Type::SChar,
+            (_, true) => Type::UChar,
+            _ => Type::Char,
+        };
+    }
+
+    //long long has the same size as long on this target
+    match (has_unsigned, count(&Keyword::Long) > 0) {
+        (true, true) => Type::ULong,
+        (true, false) => Type::UInt,
+        (false, true) => Type::Long,
+        (false, false) => Type::Int,
+    }
+}
+
+fn declarator(tokens: &mut TokenQue) -> Declarator {
+    if tokens.peek_next_token().0 == Tkn::Star {
+        tokens.next();
+        let is_const = pointer_qualifiers(tokens);
+        return Declarator::Pointer(Box::from(declarator(tokens)), is_const);
+    }
+
+    let mut simple = simple_declarator(tokens);
+    match tokens.peek_next_token().0 {
+        Tkn::LeftParen => Declarator::Function(parse_params(tokens), Box::from(simple)),
+        _ => {
+            while tokens.peek_next_token().0 == Tkn::LeftBracket {
+                simple = Declarator::Array(Box::from(simple), array_size(tokens));
+            }
+            simple
+        },
+    }
+}
+
+//Qualifiers after a '*' apply to the pointer rather than to what it points to
+fn pointer_qualifiers(tokens: &mut TokenQue) -> bool {
+    let mut is_const = false;
+    while tokens.peek_next_token().0 == Tkn::Key(Keyword::Const) {
+        tokens.next();
+        is_const = true;
+    }
+
+    is_const
+}
+
+//The dimension is a constant expression, which can only be evaluated once enumerators are resolved
+fn array_size(tokens: &mut TokenQue) -> Option<Expr> {
+    tokens.consume(Tkn::LeftBracket, "Expected '['");
+    if tokens.peek_next_token().0 == Tkn::RightBracket {
+        tokens.next();
+        return None;
+    }
+    let size = expr(tokens, precedence(&BinaryOp::Condition));
+    tokens.consume(Tkn::RightBracket, "Expected ']'");
+
+    Some(size)
+}
+
+fn simple_declarator(tokens: &mut TokenQue) -> Declarator {
+    let current = tokens.next_token();
+    match current.0 {
+        Tkn::Identifier(name) => Declarator::Ident(name),
+        Tkn::LeftParen => {
+            let inner = declarator(tokens);
+            tokens.consume(Tkn::RightParen, "Expected ')'");
+            inner
+        },
+        _ => parser_error(&current.1, "Identifier Expected"),
+    }
+}
+
+fn process_declarator(decl: Declarator, base_type: Type, line: &Location) -> (String, Type, Vec<String>) {
+    match decl {
+        Declarator::Ident(name) => (name, base_type, Vec::new()),
+        Declarator::Pointer(inner, is_const) => {
+            let ptr_type = Type::Pointer(Box::from(base_type));
+            process_declarator(*inner, if is_const { ptr_type.make_const() } else { ptr_type }, line)
+        },
+        Declarator::Array(inner, size) =>
+            process_declarator(*inner, Type::ArrayExpr(Box::from(base_type), size.map(Box::from)), line),
+        Declarator::Function(params, inner) => {
+            let name = match *inner {
+                Declarator::Ident(name) => name,
+                _ => parser_error(line, "Function pointers are not supported"),
+            };
+
+            let mut param_names: Vec<String> = Vec::new();
+            let mut param_types: Vec<Type> = Vec::new();
+            for ParamInfo::Param(param_base, param_decl) in params.into_iter() {
+                let (param_name, param_type, _) = process_declarator(param_decl, param_base, line);
+                if let Type::Function(_, _) = param_type {
+                    parser_error(line, "Function pointers are not supported");
+                }
+                param_names.push(param_name);
+                param_types.push(param_type);
+            }
+
+            (name, Type::Function(param_types, Box::from(base_type)), param_names)
+        },
+    }
+}
+
+fn abstract_declarator(tokens: &mut TokenQue) -> AbstractDeclarator {
+    let mut decl = match tokens.peek_next_token().0 {
+        Tkn::Star => {
+            tokens.next();
+            let is_const = pointer_qualifiers(tokens);
+            return AbstractDeclarator::Pointer(Box::from(abstract_declarator(tokens)), is_const);
+        },
+        Tkn::LeftParen => {
+            let line = tokens.next_token().1;
+            let inner = abstract_declarator(tokens);
+            if let AbstractDeclarator::Base = inner {
+                parser_error(&line, "Invalid abstract declarator");
+            }
+            tokens.consume(Tkn::RightParen, "Expected ')'");
+            inner
+        },
+        _ => AbstractDeclarator::Base,
     };
 
+    while tokens.peek_next_token().0 == Tkn::LeftBracket {
+        decl = AbstractDeclarator::Array(Box::from(decl), array_size(tokens));
+    }
+
+    decl
+}
+
+fn process_abstract_declarator(decl: AbstractDeclarator, base_type: Type) -> Type {
+    match decl {
+        AbstractDeclarator::Pointer(inner, is_const) => {
+            let ptr_type = Type::Pointer(Box::from(base_type));
+            process_abstract_declarator(*inner, if is_const { ptr_type.make_const() } else { ptr_type })
+        },
+        AbstractDeclarator::Array(inner, size) =>
+            process_abstract_declarator(*inner, Type::ArrayExpr(Box::from(base_type), size.map(Box::from))),
+        AbstractDeclarator::Base => base_type,
+    }
+}
+
+fn parse_params(tokens: &mut TokenQue) -> Vec<ParamInfo> {
+    let mut params: Vec<ParamInfo> = Vec::new();
+
     tokens.consume(Tkn::LeftParen, "Expected '('");
-    tokens.consume(Tkn::Key(Keyword::Void), "Expected 'void'");
+    //Parameters are in their own scope, even in a declaration that is not a definition
+    tokens.enter_scope();
+    match tokens.peek_next_token().0 {
+        Tkn::Key(Keyword::Void) => tokens.next(),
+        Tkn::RightParen => (),
+        _ => loop {
+            let line = tokens.peek_next_token().1.clone();
+            let (param_type, storage, defs) = parse_specifiers(tokens);
+            if storage.is_some() {
+                parser_error(&line, "Invalid storage class for parameter");
+            }
+            if !defs.is_empty() {
+                parser_error(&line, "Structure, union or enumeration defined in a parameter list");
+            }
+            params.push(ParamInfo::Param(param_type, param_declarator(tokens, params.len())));
+
+            if tokens.peek_next_token().0 != Tkn::Comma {
+                break;
+            }
+            tokens.next();
+        },
+    }
+    tokens.exit_scope();
     tokens.consume(Tkn::RightParen, "Expected ')'");
 
-    tokens.consume(Tkn::LeftBrace, "Expected '{'");
-    let body = parse_block(tokens);
+    params
+}
+
+//A parameter is named when an identifier comes before anything but '*', const and '('
+fn is_named_param(tokens: &TokenQue) -> bool {
+    let mut n = 0;
+    while let Some(Tkn::Star | Tkn::Key(Keyword::Const) | Tkn::LeftParen) = tokens.peek_nth_token(n) {
+        n += 1;
+    }
+
+    matches!(tokens.peek_nth_token(n), Some(Tkn::Identifier(_)))
+}
+
+//Unnamed parameters get a placeholder that cannot clash with an identifier, and only definitions reject them
+fn param_declarator(tokens: &mut TokenQue, index: usize) -> Declarator {
+    if is_named_param(tokens) {
+        return declarator(tokens);
+    }
+
+    name_abstract_declarator(abstract_declarator(tokens), format!("{}{}", UNNAMED_PARAM, index))
+}
 
-    FuncDef::Function(name, body)
+//Abstract declarators nest the same way as declarators, with the name where the base is
+fn name_abstract_declarator(decl: AbstractDeclarator, name: String) -> Declarator {
+    match decl {
+        AbstractDeclarator::Pointer(inner, is_const) =>
+            Declarator::Pointer(Box::from(name_abstract_declarator(*inner, name)), is_const),
+        AbstractDeclarator::Array(inner, size) =>
+            Declarator::Array(Box::from(name_abstract_declarator(*inner, name)), size),
+        AbstractDeclarator::Base => Declarator::Ident(name),
+    }
 }
 
 fn parse_block(tokens: &mut TokenQue) -> Block {
     let mut block: Vec<BlockItem> = Vec::new();
 
+    tokens.enter_scope();
     while tokens.peek_next_token().0 != Tkn::RightBrace {
-        block.push(next_block_item(tokens));
+        block.extend(next_block_item(tokens));
     }
+    tokens.exit_scope();
 
     tokens.consume(Tkn::RightBrace, "Expected '}'");
 
     Block::Block(block)
 }
 
-fn next_block_item(tokens: &mut TokenQue) -> BlockItem {
-    let current = tokens.peek_next_token();
+fn next_block_item(tokens: &mut TokenQue) -> Vec<BlockItem> {
+    if starts_declaration(tokens) {
+        declaration(tokens).into_iter().map(BlockItem::D).collect()
+    } else {
+        vec![BlockItem::S(statement(tokens))]
+    }
+}
 
-    match current.0 {
-        Tkn::Key(Keyword::Int) => {
+//A tag on its own declares it in the current scope, hiding any tag of the same name from an outer scope
+fn is_struct_declaration(tokens: &TokenQue) -> bool {
+    matches!(tokens.peek_nth_token(0), Some(Tkn::Key(Keyword::Struct | Keyword::Union)))
+        && matches!(tokens.peek_nth_token(1), Some(Tkn::Identifier(_)))
+        && matches!(tokens.peek_nth_token(2), Some(Tkn::Semicolon))
+}
+
+fn struct_declaration(tokens: &mut TokenQue) -> StructDecl {
+    let is_union = tokens.next_token().0 == Tkn::Key(Keyword::Union);
+    let tag = struct_tag(tokens);
+    tokens.consume(Tkn::Semicolon, "Expected ';'");
+
+    if is_union { StructDecl::Union(tag, None) } else { StructDecl::Struct(tag, None) }
+}
+
+//As with structures, an enumerator list makes the specifier a definition
+fn enum_specifier(tokens: &mut TokenQue, defs: &mut Vec<Decl>) -> String {
+    let tag = match tokens.peek_next_token().0 {
+        Tkn::LeftBrace => anonymous_tag(),
+        _ => struct_tag(tokens),
+    };
+    if tokens.peek_next_token().0 != Tkn::LeftBrace {
+        return tag;
+    }
+
+    tokens.next();
+
+    let mut enumerators: Vec<Enumerator> = Vec::new();
+    loop {
+        let current = tokens.next_token();
+        let name = match current.0 {
+            Tkn::Identifier(name) => name,
+            _ => parser_error(&current.1, "Expected enumerator name"),
+        };
+        let value = if tokens.peek_next_token().0 == Tkn::Equal {
             tokens.next();
-            BlockItem::D(declaration(tokens))
-        },
-        _ => BlockItem::S(statement(tokens)),
+            Some(expr(tokens, precedence(&BinaryOp::Condition)))
+        } else {
+            None
+        };
+        tokens.declare_ident(&name, false);
+        enumerators.push(Enumerator::Enumerator(name, value));
+
+        //A trailing comma is allowed before the closing brace
+        if tokens.peek_next_token().0 != Tkn::Comma {
+            break;
+        }
+        tokens.next();
+        if tokens.peek_next_token().0 == Tkn::RightBrace {
+            break;
+        }
     }
+    tokens.consume(Tkn::RightBrace, "Expected '}'");
+    defs.push(Decl::Enum(EnumDecl::Enum(Some(tag.clone()), enumerators)));
+
+    tag
 }
 
-fn declaration(tokens: &mut TokenQue) -> Decl {
-    let current = tokens.next_token();
-    let ident;
-    match current.0 {
-        Tkn::Identifier(val) => ident = val,
-        _ => parser_error(current.1, "Identifier Expected"),
+//Like other declarations, one set of specifiers can be shared by several comma separated members
+fn member_declaration(tokens: &mut TokenQue, defs: &mut Vec<Decl>) -> Vec<MemberDecl> {
+    let (base_type, storage, member_defs) = parse_specifiers(tokens);
+    defs.extend(member_defs);
+    if storage.is_some() {
+        parser_error(&tokens.peek_next_token().1, "Storage class on structure member");
     }
 
-    let next = tokens.peek_next_token();
-    let mut init = None;
-    match next.0 {
-        Tkn::Equal => {
-            tokens.next();
-            init = Some(expr(tokens, 0));
-        },
-        _ => (),
+    let mut members: Vec<MemberDecl> = Vec::new();
+    loop {
+        let line = tokens.peek_next_token().1.clone();
+        let (name, member_type, _) = process_declarator(declarator(tokens), base_type.clone(), &line);
+        if let Type::Function(_, _) = member_type {
+            parser_error(&line, "Function declared as structure member");
+        }
+        members.push(MemberDecl::Member(name, member_type));
+
+        if tokens.peek_next_token().0 != Tkn::Comma {
+            break;
+        }
+        tokens.next();
+    }
+    tokens.consume(Tkn::Semicolon, "Expected ';'");
+
+    members
+}
+
+fn declaration(tokens: &mut TokenQue) -> Vec<Decl> {
+    if is_struct_declaration(tokens) {
+        return vec![Decl::Struct(struct_declaration(tokens))];
+    }
+    if tokens.peek_next_token().0 == Tkn::Key(Keyword::StaticAssert) {
+        return vec![Decl::StaticAssert(static_assert(tokens))];
     }
 
+    //Tag definitions in the specifiers come first, and can make up the whole declaration
+    let (base_type, storage, mut decls) = parse_specifiers(tokens);
+    if !decls.is_empty() && tokens.peek_next_token().0 == Tkn::Semicolon {
+        tokens.next();
+        return decls;
+    }
+    let def_count = decls.len();
+
+    loop {
+        let line = tokens.peek_next_token().1.clone();
+        let (ident, decl_type, params) = process_declarator(declarator(tokens), base_type.clone(), &line);
+
+        if storage == Some(StorageClass::Typedef) {
+            decls.push(Decl::Typedef(typedef_declaration(ident, decl_type, &line, tokens)));
+        } else if let Type::Function(_, _) = decl_type {
+            tokens.declare_ident(&ident, false);
+            if tokens.peek_next_token().0 == Tkn::LeftBrace {
+                if decls.len() > def_count {
+                    parser_error(&line, "Function definition in a declaration list");
+                }
+                decls.push(Decl::Func(function_definition(ident, decl_type, params, storage, tokens)));
+                return decls;
+            }
+            decls.push(Decl::Func(FuncDecl::Function(ident, params, None, decl_type, storage.clone())));
+        } else {
+            tokens.declare_ident(&ident, false);
+            let init = if tokens.peek_next_token().0 == Tkn::Equal {
+                tokens.next();
+                Some(initializer(tokens))
+            } else {
+                None
+            };
+            decls.push(Decl::Var(VarDecl::Declaration(ident, init, decl_type, storage.clone())));
+        }
+
+        if tokens.peek_next_token().0 != Tkn::Comma {
+            break;
+        }
+        tokens.next();
+    }
     tokens.consume(Tkn::Semicolon, "Expected ';'");
 
-    Decl::Declaration(ident, init)
+    decls
+}
+
+//The message is optional, as in C23
+fn static_assert(tokens: &mut TokenQue) -> StaticAssertDecl {
+    let line = tokens.next_token().1;
+    tokens.consume(Tkn::LeftParen, "Expected '('");
+    let cond = expr(tokens, precedence(&BinaryOp::Condition));
+
+    let msg = if tokens.peek_next_token().0 == Tkn::Comma {
+        tokens.next();
+        let current = tokens.next_token();
+        let mut bytes = match current.0 {
+            Tkn::StringLiteral(bytes) => bytes,
+            _ => parser_error(&current.1, "Expected string literal in static assertion"),
+        };
+        while let Tkn::StringLiteral(next) = &tokens.peek_next_token().0 {
+            bytes.extend_from_slice(next);
+            tokens.next();
+        }
+        Some(bytes)
+    } else {
+        None
+    };
+    tokens.consume(Tkn::RightParen, "Expected ')'");
+    tokens.consume(Tkn::Semicolon, "Expected ';'");
+
+    StaticAssertDecl::StaticAssert(cond, msg, line)
+}
+
+fn typedef_declaration(name: String, decl_type: Type, line: &Location, tokens: &mut TokenQue) -> TypedefDecl {
+    if let Type::Function(_, _) = decl_type {
+        parser_error(line, "Function typedefs are not supported");
+    }
+    tokens.declare_ident(&name, true);
+
+    TypedefDecl::Typedef(name, decl_type)
+}
+
+fn initializer(tokens: &mut TokenQue) -> Initializer {
+    if tokens.peek_next_token().0 != Tkn::LeftBrace {
+        return Initializer::SingleInit(expr(tokens, precedence(&BinaryOp::Assign)));
+    }
+
+    tokens.next();
+    let mut inits: Vec<Initializer> = Vec::new();
+    loop {
+        inits.push(initializer(tokens));
+
+        if tokens.peek_next_token().0 != Tkn::Comma {
+            break;
+        }
+        tokens.next();
+
+        if tokens.peek_next_token().0 == Tkn::RightBrace {
+            break;
+        }
+    }
+    tokens.consume(Tkn::RightBrace, "Expected '}'");
+
+    Initializer::CompoundInit(inits)
+}
+
+fn function_definition(name: String, fun_type: Type, params: Vec<String>, storage: Option<StorageClass>,
+    tokens: &mut TokenQue) -> FuncDecl {
+
+    let line = tokens.peek_next_token().1.clone();
+    if params.iter().any(|param| param.starts_with(UNNAMED_PARAM)) {
+        parser_error(&line, format!("Parameter name omitted in definition of {}", name).as_str());
+    }
+
+    //Parameters can shadow typedef names inside the body
+    tokens.consume(Tkn::LeftBrace, "Expected '{'");
+    tokens.enter_scope();
+    for param in params.iter() {
+        tokens.declare_ident(param, false);
+    }
+    let body = parse_block(tokens);
+    tokens.exit_scope();
+
+    FuncDecl::Function(name, params, Some(body), fun_type, storage)
 }
 
 fn statement(tokens: &mut TokenQue) -> Stmt {
+    let is_labeled = tokens.peek_nth_token(1) == Some(&Tkn::Colon);
     let current = tokens.peek_next_token();
 
-    let res = match current.0 {
+    match current.0 {
         Tkn::Semicolon => {
             tokens.next();
             Stmt::Null
@@ -102,14 +680,67 @@ fn statement(tokens: &mut TokenQue) -> Stmt {
             tokens.next();
             Stmt::Compound(parse_block(tokens))
         }
+        Tkn::Key(Keyword::Break) => {
+            tokens.next();
+            tokens.consume(Tkn::Semicolon, "Expected ';'");
+            Stmt::Break(String::new())
+        },
+        Tkn::Key(Keyword::Continue) => {
+            tokens.next();
+            tokens.consume(Tkn::Semicolon, "Expected ';'");
+            Stmt::Continue(String::new())
+        },
+        Tkn::Key(Keyword::While) => {
+            tokens.next();
+            while_stmt(tokens)
+        },
+        Tkn::Key(Keyword::Do) => {
+            tokens.next();
+            do_while_stmt(tokens)
+        },
+        Tkn::Key(Keyword::For) => {
+            tokens.next();
+            for_stmt(tokens)
+        },
+        Tkn::Key(Keyword::Switch) => {
+            tokens.next();
+            switch_stmt(tokens)
+        },
+        Tkn::Key(Keyword::Case) => {
+            tokens.next();
+            let value = expr(tokens, 0);
+            tokens.consume(Tkn::Colon, "Expected ':'");
+            Stmt::Case(value, Box::from(statement(tokens)), String::new())
+        },
+        Tkn::Key(Keyword::Goto) => {
+            tokens.next();
+            let target = tokens.next_token();
+            let label = match target.0 {
+                Tkn::Identifier(label) => label,
+                _ => parser_error(&target.1, "Expected label after goto"),
+            };
+            tokens.consume(Tkn::Semicolon, "Expected ';'");
+            Stmt::Goto(label)
+        },
+        Tkn::Identifier(_) if is_labeled => {
+            let label = match tokens.next_token().0 {
+                Tkn::Identifier(label) => label,
+                _ => unreachable!(),
+            };
+            tokens.next();
+            Stmt::Labeled(label, Box::from(statement(tokens)))
+        },
+        Tkn::Key(Keyword::Default) => {
+            tokens.next();
+            tokens.consume(Tkn::Colon, "Expected ':'");
+            Stmt::Default(Box::from(statement(tokens)), String::new())
+        },
         _ => {
             let expr_stmt = Stmt::Expression(expr(tokens, 0));
             tokens.consume(Tkn::Semicolon, "Expected ';'");
             expr_stmt
         },
-    };
-    
-    res
+    }
 }
 
 fn if_stmt(tokens: &mut TokenQue) -> Stmt {
@@ -128,29 +759,110 @@ fn if_stmt(tokens: &mut TokenQue) -> Stmt {
     Stmt::If(cond_expr, then_stmt, else_stmt)
 }
 
+fn while_stmt(tokens: &mut TokenQue) -> Stmt {
+    tokens.consume(Tkn::LeftParen, "Expected '('");
+    let cond_expr = expr(tokens, 0);
+    tokens.consume(Tkn::RightParen, "Expected ')'");
+
+    let body = Box::from(statement(tokens));
+
+    Stmt::While(cond_expr, body, String::new())
+}
+
+fn switch_stmt(tokens: &mut TokenQue) -> Stmt {
+    tokens.consume(Tkn::LeftParen, "Expected '('");
+    let cond_expr = expr(tokens, 0);
+    tokens.consume(Tkn::RightParen, "Expected ')'");
+
+    let body = Box::from(statement(tokens));
+
+    Stmt::Switch(cond_expr, body, String::new(), Vec::new())
+}
+
+fn do_while_stmt(tokens: &mut TokenQue) -> Stmt {
+    let body = Box::from(statement(tokens));
+
+    tokens.consume(Tkn::Key(Keyword::While), "Expected 'while'");
+    tokens.consume(Tkn::LeftParen, "Expected '('");
+    let cond_expr = expr(tokens, 0);
+    tokens.consume(Tkn::RightParen, "Expected ')'");
+    tokens.consume(Tkn::Semicolon, "Expected ';'");
+
+    Stmt::DoWhile(body, cond_expr, String::new())
+}
+
+fn for_stmt(tokens: &mut TokenQue) -> Stmt {
+    tokens.consume(Tkn::LeftParen, "Expected '('");
+
+    tokens.enter_scope();
+    let init = for_init(tokens);
+    let cond = optional_expr(tokens, Tkn::Semicolon);
+    tokens.consume(Tkn::Semicolon, "Expected ';'");
+    let post = optional_expr(tokens, Tkn::RightParen);
+    tokens.consume(Tkn::RightParen, "Expected ')'");
+
+    let body = Box::from(statement(tokens));
+    tokens.exit_scope();
+
+    Stmt::For(init, cond, post, body, String::new())
+}
+
+fn for_init(tokens: &mut TokenQue) -> ForInit {
+    if starts_declaration(tokens) {
+        let line = tokens.peek_next_token().1.clone();
+        let decls = declaration(tokens).into_iter().map(|decl| match decl {
+            Decl::Var(VarDecl::Declaration(_, _, _, Some(_))) => parser_error(&line, "Storage class in for loop initializer"),
+            Decl::Var(decl) => decl,
+            Decl::Func(_) => parser_error(&line, "Function declaration in for loop initializer"),
+            Decl::Struct(_) => parser_error(&line, "Structure or union declaration in for loop initializer"),
+            Decl::Enum(_) => parser_error(&line, "Enumeration declaration in for loop initializer"),
+            Decl::Typedef(_) => parser_error(&line, "Typedef in for loop initializer"),
+            Decl::StaticAssert(_) => parser_error(&line, "Static assertion in for loop initializer"),
+        }).collect();
+        ForInit::InitDecl(decls)
+    } else {
+        let init = optional_expr(tokens, Tkn::Semicolon);
+        tokens.consume(Tkn::Semicolon, "Expected ';'");
+        ForInit::InitExpr(init)
+    }
+}
+
+fn optional_expr(tokens: &mut TokenQue, end: Tkn) -> Option<Expr> {
+    if tokens.peek_next_token().0 == end {
+        None
+    } else {
+        Some(expr(tokens, 0))
+    }
+}
+
 fn expr(tokens: &mut TokenQue, min_prec: u32) -> Expr {
     let mut left = factor(tokens);
-    let mut next_op = parse_binary_op(&tokens.peek_next_token());
 
-    while next_op != None && precedence(&next_op.unwrap()) >= min_prec {
-        let op = parse_binary_op(&tokens.next_token()).unwrap();
+    while let Some(op) = parse_binary_op(tokens.peek_next_token()) && precedence(&op) >= min_prec {
+        let token = tokens.next_token();
         match op {
             BinaryOp::Assign => {
-                let right = expr(tokens, precedence(&op));
-                left = Expr::Assignment(Box::from(left), Box::from(right));
+                let right = Box::from(expr(tokens, precedence(&op)));
+                left = match compound_op(&token.0) {
+                    Some(inner) => Expr::new(ExprKind::CompoundAssignment(inner, Box::from(left), right)),
+                    None => Expr::new(ExprKind::Assignment(Box::from(left), right)),
+                };
             },
             BinaryOp::Condition => {
                 let middle = Box::from(expr(tokens, 0));
                 tokens.consume(Tkn::Colon, "Expected ':'");
                 let right = Box::from(expr(tokens, precedence(&op)));
-                left = Expr::Conditional(Box::from(left), middle, right)
-            }
+                left = Expr::new(ExprKind::Conditional(Box::from(left), middle, right))
+            },
+            BinaryOp::Comma => {
+                let right = expr(tokens, precedence(&op) + 1);
+                left = Expr::new(ExprKind::Comma(Box::from(left), Box::from(right)));
+            },
             _ => {
                 let right = expr(tokens, precedence(&op) + 1);
-                left = Expr::Binary(op, Box::from(left), Box::from(right));
+                left = Expr::new(ExprKind::Binary(op, Box::from(left), Box::from(right)));
             }
         }
-        next_op = parse_binary_op(&tokens.peek_next_token());
     }
 
     left
@@ -159,33 +871,180 @@ fn expr(tokens: &mut TokenQue, min_prec: u32) -> Expr {
 fn factor(tokens: &mut TokenQue) -> Expr {
     
     let current = tokens.next_token();
-    match current.0 {
-        Tkn::Constant(value) => Expr::Constant(value),
+    let primary = match current.0 {
+        Tkn::Constant(_) | Tkn::LongConstant(_) | Tkn::UnsignedConstant(_) | Tkn::UnsignedLongConstant(_) =>
+            Expr::new(ExprKind::Constant(int_constant(&current.0, &current.1))),
+        Tkn::DoubleConstant(value) => Expr::new(ExprKind::Constant(Const::Double(value))),
+        Tkn::CharConstant(value) => Expr::new(ExprKind::Constant(Const::Int(value as i8 as i32))),
+        //C23's true and false are constants of type bool
+        Tkn::Key(Keyword::True) | Tkn::Key(Keyword::False) => {
+            let value = Expr::new(ExprKind::Constant(Const::Int((current.0 == Tkn::Key(Keyword::True)) as i32)));
+            Expr::new(ExprKind::Cast(Type::Bool, Box::new(value)))
+        },
+        Tkn::StringLiteral(mut bytes) => {
+            while let Tkn::StringLiteral(next) = &tokens.peek_next_token().0 {
+                bytes.extend_from_slice(next);
+                tokens.next();
+            }
+            Expr::new(ExprKind::String(bytes))
+        },
         Tkn::Tilde | Tkn::Subtract | Tkn::Not => {
             let operator = parse_unary_op(&current);
-            Expr::Unary(operator, Box::new(factor(tokens)))
+            Expr::new(ExprKind::Unary(operator, Box::new(factor(tokens))))
+        },
+        //Prefix increment and decrement are the same as adding or subtracting one
+        Tkn::Increment | Tkn::Decrement => {
+            let op = if current.0 == Tkn::Increment { BinaryOp::Add } else { BinaryOp::Subtract };
+            let one = Expr::new(ExprKind::Constant(Const::Int(1)));
+            Expr::new(ExprKind::CompoundAssignment(op, Box::new(factor(tokens)), Box::new(one)))
+        },
+        Tkn::Star => Expr::new(ExprKind::Dereference(Box::new(factor(tokens)))),
+        Tkn::BAnd => Expr::new(ExprKind::AddrOf(Box::new(factor(tokens)))),
+        //A GNU statement expression, whose value is that of its last expression statement
+        Tkn::LeftParen if tokens.peek_next_token().0 == Tkn::LeftBrace => {
+            tokens.next();
+            let block = parse_block(tokens);
+            tokens.consume(Tkn::RightParen, "Expected ')'");
+            Expr::new(ExprKind::StmtExpr(block))
+        },
+        Tkn::LeftParen if starts_type(tokens, 0) => {
+            let target = type_name(tokens);
+            Expr::new(ExprKind::Cast(target, Box::new(factor(tokens))))
+        },
+        Tkn::Key(Keyword::Sizeof) if tokens.peek_next_token().0 == Tkn::LeftParen
+            && starts_type(tokens, 1) => {
+            tokens.next();
+            Expr::new(ExprKind::SizeOfType(type_name(tokens)))
+        },
+        //The operand of sizeof is only type checked, never evaluated
+        Tkn::Key(Keyword::Sizeof) => Expr::new(ExprKind::SizeOf(Box::new(factor(tokens)))),
+        Tkn::Key(Keyword::Alignof) => {
+            tokens.consume(Tkn::LeftParen, "Expected '('");
+            Expr::new(ExprKind::AlignOf(type_name(tokens)))
         },
         Tkn::LeftParen => {
             let inner_expr = expr(tokens, 0);
             tokens.consume(Tkn::RightParen, "Expected ')'");
             inner_expr
         },
-        Tkn::Identifier(val) => Expr::Var(val),
+        Tkn::Identifier(val) => {
+            if let (Some((builtin, arity)), Tkn::LeftParen) = (builtin(&val), &tokens.peek_next_token().0) {
+                tokens.next();
+                let args = arguments(tokens);
+                if args.len() != arity {
+                    parser_error(&current.1, &format!("{} called with the wrong number of arguments", val));
+                }
+                Expr::new(ExprKind::Builtin(builtin, args))
+            } else if tokens.peek_next_token().0 == Tkn::LeftParen {
+                tokens.next();
+                Expr::new(ExprKind::FunctionCall(val, arguments(tokens)))
+            } else {
+                Expr::new(ExprKind::Var(val))
+            }
+        },
+
+        _ => parser_error(&current.1, "Expression Expected"),
+    };
+
+    postfix(tokens, primary)
+}
+
+//Parses a parenthesized type name after its opening '('
+fn type_name(tokens: &mut TokenQue) -> Type {
+    let base_type = parse_type(tokens);
+    let target = process_abstract_declarator(abstract_declarator(tokens), base_type);
+    tokens.consume(Tkn::RightParen, "Expected ')'");
+
+    target
+}
 
-        _ => parser_error(current.1, "Expression Expected"),
+fn postfix(tokens: &mut TokenQue, mut primary: Expr) -> Expr {
+    loop {
+        primary = match tokens.peek_next_token().0 {
+            Tkn::LeftBracket => {
+                tokens.next();
+                let index = expr(tokens, 0);
+                tokens.consume(Tkn::RightBracket, "Expected ']'");
+                Expr::new(ExprKind::Subscript(Box::from(primary), Box::from(index)))
+            },
+            Tkn::Dot => {
+                tokens.next();
+                Expr::new(ExprKind::Dot(Box::from(primary), member_name(tokens)))
+            },
+            Tkn::Arrow => {
+                tokens.next();
+                Expr::new(ExprKind::Arrow(Box::from(primary), member_name(tokens)))
+            },
+            Tkn::Increment => {
+                tokens.next();
+                Expr::new(ExprKind::Postfix(BinaryOp::Add, Box::from(primary)))
+            },
+            Tkn::Decrement => {
+                tokens.next();
+                Expr::new(ExprKind::Postfix(BinaryOp::Subtract, Box::from(primary)))
+            },
+            _ => break,
+        };
     }
+
+    primary
 }
 
-fn parse_unary_op(token: &(Tkn, u32)) -> UnaryOp {
+fn member_name(tokens: &mut TokenQue) -> String {
+    let current = tokens.next_token();
+    match current.0 {
+        Tkn::Identifier(name) => name,
+        _ => parser_error(&current.1, "Expected member name"),
+    }
+}
+
+//The lexer already picked the type of the constant from its value and suffix
+fn int_constant(token: &Tkn, line: &Location) -> Const {
+    match *token {
+        Tkn::Constant(value) => Const::Int(value as i32),
+        Tkn::LongConstant(value) => Const::Long(value as i64),
+        Tkn::UnsignedConstant(value) => Const::UInt(value as u32),
+        Tkn::UnsignedLongConstant(value) => Const::ULong(value),
+        _ => parser_error(line, "Integer constant expected"),
+    }
+}
+
+//Compiler builtins and how many arguments each one takes
+fn builtin(name: &str) -> Option<(Builtin, usize)> {
+    match name {
+        "__builtin_expect" => Some((Builtin::Expect, 2)),
+        "__builtin_unreachable" => Some((Builtin::Unreachable, 0)),
+        "__builtin_trap" => Some((Builtin::Trap, 0)),
+        "__builtin_constant_p" => Some((Builtin::ConstantP, 1)),
+        _ => None,
+    }
+}
+
+fn arguments(tokens: &mut TokenQue) -> Vec<Expr> {
+    let mut args: Vec<Expr> = Vec::new();
+
+    if tokens.peek_next_token().0 != Tkn::RightParen {
+        args.push(expr(tokens, precedence(&BinaryOp::Assign)));
+        while tokens.peek_next_token().0 == Tkn::Comma {
+            tokens.next();
+            args.push(expr(tokens, precedence(&BinaryOp::Assign)));
+        }
+    }
+    tokens.consume(Tkn::RightParen, "Expected ')'");
+
+    args
+}
+
+fn parse_unary_op(token: &(Tkn, Location)) -> UnaryOp {
     match token.0 {
         Tkn::Tilde => UnaryOp::Complement,
         Tkn::Subtract => UnaryOp::Negate,
         Tkn::Not => UnaryOp::Not,
-        _ => parser_error(token.1, "Unary Operator Expected"),
+        _ => parser_error(&token.1, "Unary Operator Expected"),
     }
 }
 
-fn parse_binary_op(token: &(Tkn, u32)) -> Option<BinaryOp> {
+fn parse_binary_op(token: &(Tkn, Location)) -> Option<BinaryOp> {
     match token.0 {
         Tkn::Subtract => Some(BinaryOp::Subtract),
         Tkn::Plus => Some(BinaryOp::Add),
@@ -198,10 +1057,35 @@ fn parse_binary_op(token: &(Tkn, u32)) -> Option<BinaryOp> {
         Tkn::Great => Some(BinaryOp::GreatThan),
         Tkn::GreatEqual => Some(BinaryOp::GreatEqual),
         Tkn::NotEqual => Some(BinaryOp::NotEqual),
+        Tkn::BAnd => Some(BinaryOp::BitAnd),
+        Tkn::BOr => Some(BinaryOp::BitOr),
+        Tkn::Caret => Some(BinaryOp::BitXor),
+        Tkn::ShiftLeft => Some(BinaryOp::ShiftLeft),
+        Tkn::ShiftRight => Some(BinaryOp::ShiftRight),
         Tkn::And => Some(BinaryOp::And),
         Tkn::Or => Some(BinaryOp::Or),
-        Tkn::Equal => Some(BinaryOp::Assign),
+        Tkn::Equal | Tkn::PlusEqual | Tkn::SubtractEqual | Tkn::StarEqual | Tkn::SlashEqual | Tkn::ModEqual
+            | Tkn::BAndEqual | Tkn::BOrEqual | Tkn::CaretEqual | Tkn::ShiftLeftEqual | Tkn::ShiftRightEqual =>
+            Some(BinaryOp::Assign),
         Tkn::Question => Some(BinaryOp::Condition),
+        Tkn::Comma => Some(BinaryOp::Comma),
+        _ => None,
+    }
+}
+
+//Returns the operator applied by a compound assignment token
+fn compound_op(token: &Tkn) -> Option<BinaryOp> {
+    match token {
+        Tkn::PlusEqual => Some(BinaryOp::Add),
+        Tkn::SubtractEqual => Some(BinaryOp::Subtract),
+        Tkn::StarEqual => Some(BinaryOp::Multiply),
+        Tkn::SlashEqual => Some(BinaryOp::Divide),
+        Tkn::ModEqual => Some(BinaryOp::Remainder),
+        Tkn::BAndEqual => Some(BinaryOp::BitAnd),
+        Tkn::BOrEqual => Some(BinaryOp::BitOr),
+        Tkn::CaretEqual => Some(BinaryOp::BitXor),
+        Tkn::ShiftLeftEqual => Some(BinaryOp::ShiftLeft),
+        Tkn::ShiftRightEqual => Some(BinaryOp::ShiftRight),
         _ => None,
     }
 }
@@ -213,15 +1097,21 @@ fn precedence(op: &BinaryOp) -> u32 {
         BinaryOp::Remainder => 50,
         BinaryOp::Add => 45,
         BinaryOp::Subtract => 45,
+        BinaryOp::ShiftLeft => 40,
+        BinaryOp::ShiftRight => 40,
         BinaryOp::LessThan => 35,
         BinaryOp::LessEqual => 35,
         BinaryOp::GreatThan => 35,
         BinaryOp::GreatEqual => 35,
         BinaryOp::Equal => 30,
         BinaryOp::NotEqual => 30,
+        BinaryOp::BitAnd => 25,
+        BinaryOp::BitXor => 20,
+        BinaryOp::BitOr => 15,
         BinaryOp::And => 10,
         BinaryOp::Or => 5,
         BinaryOp::Condition => 3,
         BinaryOp::Assign => 1,
+        BinaryOp::Comma => 0,
     }
 }
\ No newline at end of file
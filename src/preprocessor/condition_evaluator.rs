@@ -0,0 +1,197 @@
+use crate::preprocessor::preprocessor_structs::*;
+use crate::preprocessor::macro_expander::pp_error;
+
+//A value and whether it has type uintmax_t instead of intmax_t
+type Value = (i64, bool);
+
+//Evaluates the controlling expression of #if or #elif once macros are expanded, in intmax_t and uintmax_t arithmetic
+pub fn evaluate(tokens: &[PpToken], pp: &Preprocessor) -> Result<i64, String> {
+    let mut pos = 0;
+    let (value, _) = conditional(tokens, &mut pos, true, pp)?;
+
+    match tokens.get(pos) {
+        None => Ok(value),
+        Some(token) => Err(pp_error(pp, &format!("Unexpected \"{}\" in #if expression", token.text))),
+    }
+}
+
+//Subexpressions that are never evaluated, like the right side of a false &&, can't divide by zero
+fn conditional(tokens: &[PpToken], pos: &mut usize, live: bool, pp: &Preprocessor) -> Result<Value, String> {
+    let cond = binary(tokens, pos, 0, live, pp)?;
+    if !tokens.get(*pos).is_some_and(|t| t.is_punct("?")) {
+        return Ok(cond);
+    }
+
+    *pos += 1;
+    let then = conditional(tokens, pos, live && cond.0 != 0, pp)?;
+    expect(tokens, pos, ":", pp)?;
+    let otherwise = conditional(tokens, pos, live && cond.0 == 0, pp)?;
+
+    //The result has the common type of both branches
+    let value = if cond.0 != 0 { then.0 } else { otherwise.0 };
+    Ok((value, then.1 || otherwise.1))
+}
+
+fn precedence(op: &str) -> Option<u32> {
+    match op {
+        "*" | "/" | "%" => Some(10),
+        "+" | "-" => Some(9),
+        "<<" | ">>" => Some(8),
+        "<" | "<=" | ">" | ">=" => Some(7),
+        "==" | "!=" => Some(6),
+        "&" => Some(5),
+        "^" => Some(4),
+        "|" => Some(3),
+        "&&" => Some(2),
+        "||" => Some(1),
+        _ => None,
+    }
+}
+
+//Operands are converted to uintmax_t if either one is unsigned, except for shifts, && and ||
+fn binary(tokens: &[PpToken], pos: &mut usize, min_prec: u32, live: bool, pp: &Preprocessor) -> Result<Value, String> {
+    let mut left = unary(tokens, pos, live, pp)?;
+
+    while let Some(token) = tokens.get(*pos) && token.kind == PpKind::Punct
+        && let Some(prec) = precedence(&token.text) && prec >= min_prec {
+
+        let op = token.text.as_str();
+        *pos += 1;
+        let right_live = match op {
+            "&&" => live && left.0 != 0,
+            "||" => live && left.0 == 0,
+            _ => live,
+        };
+        let (right, right_unsigned) = binary(tokens, pos, prec + 1, right_live, pp)?;
+        let (value, left_unsigned) = left;
+        let is_unsigned = left_unsigned || right_unsigned;
+
+        left = match op {
+            "*" => (value.wrapping_mul(right), is_unsigned),
+            "/" | "%" if right == 0 => {
+                if live {
+                    return Err(pp_error(pp, "Division by zero in #if expression"));
+                }
+                (0, is_unsigned)
+            },
+            "/" if is_unsigned => (((value as u64) / (right as u64)) as i64, true),
+            "%" if is_unsigned => (((value as u64) % (right as u64)) as i64, true),
+            "/" => (value.wrapping_div(right), false),
+            "%" => (value.wrapping_rem(right), false),
+            "+" => (value.wrapping_add(right), is_unsigned),
+            "-" => (value.wrapping_sub(right), is_unsigned),
+            "<<" => (value.wrapping_shl(right as u32), left_unsigned),
+            ">>" if left_unsigned => ((value as u64).wrapping_shr(right as u32) as i64, true),
+            ">>" => (value.wrapping_shr(right as u32), false),
+            "<" | "<=" | ">" | ">=" => {
+                let ordering = if is_unsigned { (value as u64).cmp(&(right as u64)) } else { value.cmp(&right) };
+                let holds = match op {
+                    "<" => ordering.is_lt(),
+                    "<=" => ordering.is_le(),
+                    ">" => ordering.is_gt(),
+                    _ => ordering.is_ge(),
+                };
+                (holds as i64, false)
+            },
+            "==" => ((value == right) as i64, false),
+            "!=" => ((value != right) as i64, false),
+            "&" => (value & right, is_unsigned),
+            "^" => (value ^ right, is_unsigned),
+            "|" => (value | right, is_unsigned),
+            "&&" => ((value != 0 && right != 0) as i64, false),
+            _ => ((value != 0 || right != 0) as i64, false),
+        };
+    }
+
+    Ok(left)
+}
+
+fn unary(tokens: &[PpToken], pos: &mut usize, live: bool, pp: &Preprocessor) -> Result<Value, String> {
+    let token = match tokens.get(*pos) {
+        Some(token) => token,
+        None => return Err(pp_error(pp, "Expected value in #if expression")),
+    };
+    *pos += 1;
+
+    match token.kind {
+        PpKind::Number => number(&token.text, pp),
+        PpKind::CharLit => Ok((char_value(&token.text, pp)?, false)),
+        PpKind::Ident if pp.c23 && token.text == "true" => Ok((1, false)),
+        //Identifiers left after expansion evaluate to 0
+        PpKind::Ident => Ok((0, false)),
+        _ if token.is_punct("(") => {
+            let value = conditional(tokens, pos, live, pp)?;
+            expect(tokens, pos, ")", pp)?;
+            Ok(value)
+        },
+        _ if token.is_punct("-") => {
+            let (value, is_unsigned) = unary(tokens, pos, live, pp)?;
+            Ok((value.wrapping_neg(), is_unsigned))
+        },
+        _ if token.is_punct("+") => unary(tokens, pos, live, pp),
+        _ if token.is_punct("~") => {
+            let (value, is_unsigned) = unary(tokens, pos, live, pp)?;
+            Ok((!value, is_unsigned))
+        },
+        _ if token.is_punct("!") => Ok(((unary(tokens, pos, live, pp)?.0 == 0) as i64, false)),
+        _ => Err(pp_error(pp, &format!("Invalid token \"{}\" in #if expression", token.text))),
+    }
+}
+
+fn expect(tokens: &[PpToken], pos: &mut usize, expected: &str, pp: &Preprocessor) -> Result<(), String> {
+    if tokens.get(*pos).is_some_and(|t| t.is_punct(expected)) {
+        *pos += 1;
+        Ok(())
+    } else {
+        Err(pp_error(pp, &format!("Expected '{}' in #if expression", expected)))
+    }
+}
+
+//A constant is unsigned if it has a u suffix or is too big for intmax_t
+fn number(text: &str, pp: &Preprocessor) -> Result<Value, String> {
+    let digits = text.trim_end_matches(['u', 'U', 'l', 'L']);
+    let has_u = text[digits.len()..].contains(['u', 'U']);
+    let lower = digits.to_ascii_lowercase();
+    let parsed = if let Some(hex) = lower.strip_prefix("0x") {
+        u64::from_str_radix(hex, 16)
+    } else if let Some(bin) = lower.strip_prefix("0b") {
+        u64::from_str_radix(bin, 2)
+    } else if lower.len() > 1 && lower.starts_with('0') {
+        u64::from_str_radix(&lower[1..], 8)
+    } else {
+        lower.parse::<u64>()
+    };
+
+    match parsed {
+        Ok(value) => Ok((value as i64, has_u || value > i64::MAX as u64)),
+        Err(_) => Err(pp_error(pp, &format!("Invalid integer constant {} in #if expression", text))),
+    }
+}
+
+fn char_value(text: &str, pp: &Preprocessor) -> Result<i64, String> {
+    let inner: Vec<char> = text.trim_matches('\'').chars().collect();
+    let value = match inner.as_slice() {
+        [c] => *c as u32,
+        ['\\', 'x', hex @ ..] => u32::from_str_radix(&hex.iter().collect::<String>(), 16).unwrap_or(256),
+        ['\\', octal @ ..] if octal.iter().all(|c| c.is_digit(8)) && !octal.is_empty() =>
+            u32::from_str_radix(&octal.iter().collect::<String>(), 8).unwrap_or(256),
+        ['\\', c] => match c {
+            'n' => 10,
+            't' => 9,
+            'r' => 13,
+            'a' => 7,
+            'b' => 8,
+            'f' => 12,
+            'v' => 11,
+            '\\' | '\'' | '"' | '?' => *c as u32,
+            _ => 256,
+        },
+        _ => 256,
+    };
+
+    if value > 255 {
+        return Err(pp_error(pp, &format!("Invalid character constant {} in #if expression", text)));
+    }
+    //Plain char is signed on this target
+    Ok(value as u8 as i8 as i64)
+}
@@ -0,0 +1,294 @@
+use std::collections::VecDeque;
+
+use crate::utilities::error_handler::fmt_preprocessor_error;
+use crate::preprocessor::preprocessor_structs::*;
+
+static PUNCTUATORS: [&str; 23] = ["...", "<<=", ">>=", "->", "++", "--", "<<", ">>", "<=", ">=", "==", "!=", "&&",
+    "||", "*=", "/=", "%=", "+=", "-=", "&=", "^=", "|=", "##"];
+
+pub fn tokenize(text: &str) -> Vec<PpToken> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens: Vec<PpToken> = Vec::new();
+    let mut space_before = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let start = i;
+        let current = chars[i];
+        let kind = if current.is_whitespace() {
+            space_before = true;
+            i += 1;
+            continue;
+        } else if current.is_ascii_alphabetic() || current == '_' {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') { i += 1; }
+            PpKind::Ident
+        } else if current.is_ascii_digit() || (current == '.' && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit())) {
+            //Preprocessing numbers also swallow suffixes and exponent signs
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == '.'
+                || (matches!(chars[i], '+' | '-') && matches!(chars[i - 1], 'e' | 'E' | 'p' | 'P'))) {
+                i += 1;
+            }
+            PpKind::Number
+        } else if current == '"' || current == '\'' {
+            //An unterminated literal runs to the end of the line, and the lexer reports it
+            i += 1;
+            while i < chars.len() && chars[i] != current {
+                if chars[i] == '\\' { i += 1; }
+                i += 1;
+            }
+            i = (i + 1).min(chars.len());
+            if current == '"' { PpKind::StrLit } else { PpKind::CharLit }
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 3)].iter().collect();
+            let len = PUNCTUATORS.iter().find(|p| rest.starts_with(*p)).map_or(1, |p| p.len());
+            i += len;
+            PpKind::Punct
+        };
+
+        let text: String = chars[start..i].iter().collect();
+        tokens.push(PpToken::new(kind, &text, space_before));
+        space_before = false;
+    }
+
+    tokens
+}
+
+//Writes tokens back out as text, adding a space wherever two tokens would otherwise lex as one
+pub fn tokens_to_string(tokens: &[PpToken]) -> String {
+    let mut text = String::new();
+    let mut last: Option<&PpToken> = None;
+
+    for token in tokens.iter().filter(|t| t.kind != PpKind::Placemarker) {
+        if let Some(prev) = last {
+            let joined = format!("{}{}", prev.text, token.text);
+            if token.space_before || tokenize(&joined).len() != 2 {
+                text.push(' ');
+            }
+        }
+        text.push_str(&token.text);
+        last = Some(token);
+    }
+
+    text
+}
+
+pub fn expand(tokens: Vec<PpToken>, pp: &Preprocessor) -> Result<Vec<PpToken>, String> {
+    let mut input: VecDeque<PpToken> = VecDeque::from(tokens);
+    let mut output: Vec<PpToken> = Vec::new();
+
+    while let Some(token) = input.pop_front() {
+        if token.kind != PpKind::Ident || token.hideset.contains(&token.text) {
+            output.push(token);
+            continue;
+        }
+
+        match (token.text.as_str(), pp.macros.get(&token.text)) {
+            ("__LINE__", _) => output.push(PpToken::new(PpKind::Number, &pp.line.to_string(), token.space_before)),
+            ("__FILE__", _) => output.push(PpToken::new(PpKind::StrLit, &string_literal(&pp.file), token.space_before)),
+            (_, Some(Macro::Object(body))) => {
+                let mut hideset = token.hideset.clone();
+                hideset.push(token.text.clone());
+                let replacement = substitute(body, &[], &[], false, &hideset, token.space_before, pp)?;
+                for new_token in replacement.into_iter().rev() {
+                    input.push_front(new_token);
+                }
+            },
+            //A function-like macro name that isn't followed by '(' is an ordinary identifier
+            (name, Some(Macro::Function(params, variadic, body))) if input.front().is_some_and(|t| t.is_punct("(")) => {
+                input.pop_front();
+                let (args, rparen) = collect_args(name, &mut input, pp)?;
+                let args = match_args(name, args, params, *variadic, pp)?;
+
+                let mut hideset: Vec<String> = token.hideset.iter()
+                    .filter(|name| rparen.hideset.contains(name)).cloned().collect();
+                hideset.push(token.text.clone());
+                let replacement = substitute(body, params, &args, *variadic, &hideset, token.space_before, pp)?;
+                for new_token in replacement.into_iter().rev() {
+                    input.push_front(new_token);
+                }
+            },
+            _ => output.push(token),
+        }
+    }
+
+    Ok(output)
+}
+
+//Splits the arguments of an invocation at top level commas, after its opening '('
+fn collect_args(name: &str, input: &mut VecDeque<PpToken>, pp: &Preprocessor) -> Result<(Vec<Vec<PpToken>>, PpToken), String> {
+    let mut args: Vec<Vec<PpToken>> = vec![Vec::new()];
+    let mut depth = 0;
+
+    loop {
+        let token = match input.pop_front() {
+            Some(token) => token,
+            None => return Err(pp_error(pp, &format!("Unterminated argument list invoking macro {}", name))),
+        };
+
+        if token.is_punct(")") && depth == 0 {
+            return Ok((args, token));
+        } else if token.is_punct(",") && depth == 0 {
+            args.push(Vec::new());
+            continue;
+        } else if token.is_punct("(") {
+            depth += 1;
+        } else if token.is_punct(")") {
+            depth -= 1;
+        }
+        if let Some(arg) = args.last_mut() {
+            arg.push(token);
+        }
+    }
+}
+
+fn match_args(name: &str, mut args: Vec<Vec<PpToken>>, params: &[String], variadic: bool, pp: &Preprocessor)
+    -> Result<Vec<Vec<PpToken>>, String> {
+
+    //F() passes one empty argument, which is no arguments at all for a macro without parameters
+    if params.is_empty() && args.len() == 1 && args[0].is_empty() {
+        args.clear();
+    }
+
+    if variadic && args.len() >= params.len() {
+        let mut rest = args.split_off(params.len() - 1);
+        let mut va_args = rest.remove(0);
+        for arg in rest.into_iter() {
+            va_args.push(PpToken::new(PpKind::Punct, ",", false));
+            va_args.extend(arg);
+        }
+        args.push(va_args);
+    } else if variadic && args.len() == params.len() - 1 {
+        args.push(Vec::new());
+    }
+
+    if args.len() != params.len() {
+        return Err(pp_error(pp, &format!("Macro {} requires {} arguments, but {} given", name, params.len(), args.len())));
+    }
+
+    Ok(args)
+}
+
+fn substitute(body: &[PpToken], params: &[String], args: &[Vec<PpToken>], variadic: bool, hideset: &[String],
+    space_before: bool, pp: &Preprocessor) -> Result<Vec<PpToken>, String> {
+
+    let param_index = |token: &PpToken| if token.kind == PpKind::Ident {
+        params.iter().position(|param| *param == token.text)
+    } else {
+        None
+    };
+    let mut result: Vec<PpToken> = Vec::new();
+    let mut i = 0;
+
+    while i < body.len() {
+        let token = &body[i];
+        let next_is_paste = body.get(i + 1).is_some_and(|t| t.is_punct("##"));
+
+        if token.is_punct("#") && !params.is_empty() && let Some(index) = body.get(i + 1).and_then(param_index) {
+            result.push(PpToken::new(PpKind::StrLit, &stringify(&args[index]), token.space_before));
+            i += 2;
+        } else if token.is_punct("##") {
+            let operand: Vec<PpToken> = match body.get(i + 1) {
+                Some(next) => match param_index(next) {
+                    Some(index) => args[index].clone(),
+                    None => vec![next.clone()],
+                },
+                None => Vec::new(),
+            };
+            let is_va_args = variadic && body.get(i + 1).is_some_and(|t| t.text == "__VA_ARGS__");
+            paste(&mut result, operand, is_va_args, pp)?;
+            i += 2;
+        } else if let Some(index) = param_index(token) {
+            //Operands of '##' are pasted before any expansion
+            let mut arg = if next_is_paste { args[index].clone() } else { expand(args[index].clone(), pp)? };
+            match arg.first_mut() {
+                Some(first) => first.space_before = token.space_before,
+                None => arg.push(PpToken::new(PpKind::Placemarker, "", token.space_before)),
+            }
+            result.extend(arg);
+            i += 1;
+        } else {
+            result.push(token.clone());
+            i += 1;
+        }
+    }
+
+    result.retain(|t| t.kind != PpKind::Placemarker);
+    for token in result.iter_mut() {
+        for name in hideset.iter() {
+            if !token.hideset.contains(name) {
+                token.hideset.push(name.clone());
+            }
+        }
+    }
+    if let Some(first) = result.first_mut() {
+        first.space_before = space_before;
+    }
+
+    Ok(result)
+}
+
+fn paste(result: &mut Vec<PpToken>, operand: Vec<PpToken>, is_va_args: bool, pp: &Preprocessor) -> Result<(), String> {
+    let lhs = match result.pop() {
+        Some(lhs) => lhs,
+        None => {
+            result.extend(operand);
+            return Ok(());
+        },
+    };
+
+    //As in GCC, ", ## __VA_ARGS__" drops the comma when there are no variable arguments
+    if operand.is_empty() {
+        if !(is_va_args && lhs.is_punct(",")) {
+            result.push(lhs);
+        }
+        return Ok(());
+    }
+    if lhs.kind == PpKind::Placemarker {
+        result.extend(operand);
+        return Ok(());
+    }
+    if is_va_args && lhs.is_punct(",") {
+        result.push(lhs);
+        result.extend(operand);
+        return Ok(());
+    }
+
+    let mut rest = operand.into_iter();
+    let rhs = rest.next().unwrap_or_else(|| unreachable!());
+    let joined = format!("{}{}", lhs.text, rhs.text);
+    let mut pasted = tokenize(&joined);
+    if pasted.len() != 1 {
+        return Err(pp_error(pp, &format!("Pasting \"{}\" and \"{}\" does not give a valid preprocessing token",
+            lhs.text, rhs.text)));
+    }
+    let mut token = pasted.remove(0);
+    token.space_before = lhs.space_before;
+    result.push(token);
+    result.extend(rest);
+
+    Ok(())
+}
+
+fn stringify(arg: &[PpToken]) -> String {
+    let mut text = String::new();
+    for (i, token) in arg.iter().enumerate() {
+        if i > 0 && token.space_before {
+            text.push(' ');
+        }
+        match token.kind {
+            PpKind::StrLit | PpKind::CharLit => text.push_str(&token.text.replace('\\', "\\\\").replace('"', "\\\"")),
+            _ => text.push_str(&token.text),
+        }
+    }
+
+    format!("\"{}\"", text)
+}
+
+pub fn string_literal(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+pub fn pp_error(pp: &Preprocessor, msg: &str) -> String {
+    fmt_preprocessor_error(&pp.file, pp.line, msg)
+}
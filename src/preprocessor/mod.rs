@@ -0,0 +1,4 @@
+pub mod preprocessor_structs;
+pub mod preprocessor_ops;
+mod macro_expander;
+mod condition_evaluator;
\ No newline at end of file
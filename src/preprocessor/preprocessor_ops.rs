@@ -0,0 +1,464 @@
+use std::fs;
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use crate::utilities::error_handler::fmt_preprocessor_error;
+use crate::preprocessor::preprocessor_structs::*;
+use crate::preprocessor::macro_expander::*;
+use crate::preprocessor::condition_evaluator::evaluate;
+
+static PREDEFINED: [&str; 10] = ["__STDC__ 1", "__STDC_VERSION__ 201710L", "__STDC_HOSTED__ 1", "__x86_64__ 1",
+    "__x86_64 1", "__linux__ 1", "__linux 1", "__unix__ 1", "__LP64__ 1", "__CHAR_BIT__ 8"];
+
+static SYSTEM_DIRS: [&str; 3] = ["/usr/local/include", "/usr/include/x86_64-linux-gnu", "/usr/include"];
+
+static MAX_INCLUDE_DEPTH: usize = 200;
+
+//Headers that normally come with the compiler rather than the C library, used when no file is found. The C library's
+//headers ask stddef.h and stdarg.h for single definitions with __need_ macros. There are no variadic functions, so
+//stdarg.h only gives the va_list type that library headers use in prototypes
+static BUILTIN_HEADERS: [(&str, &str); 3] = [
+    ("stdbool.h", "#ifndef __bool_true_false_are_defined\n#define bool _Bool\n#define true 1\n#define false 0\n\
+        #define __bool_true_false_are_defined 1\n#endif\n"),
+    ("stddef.h", "#if !defined __need_size_t && !defined __need_ptrdiff_t && !defined __need_wchar_t \
+        && !defined __need_wint_t && !defined __need_NULL\n\
+        #define __need_size_t\n#define __need_ptrdiff_t\n#define __need_wchar_t\n#define __need_NULL\n\
+        #ifndef __glasses_max_align_t\n#define __glasses_max_align_t\n\
+        typedef struct { long __max_align_l; double __max_align_d; } max_align_t;\n#endif\n\
+        #define offsetof(type, member) ((size_t)&((type *)0)->member)\n#endif\n\
+        #if defined __need_size_t && !defined __glasses_size_t\n#define __glasses_size_t\n\
+        typedef unsigned long size_t;\n#endif\n\
+        #if defined __need_ptrdiff_t && !defined __glasses_ptrdiff_t\n#define __glasses_ptrdiff_t\n\
+        typedef long ptrdiff_t;\n#endif\n\
+        #if defined __need_wchar_t && !defined __glasses_wchar_t\n#define __glasses_wchar_t\n\
+        typedef int wchar_t;\n#endif\n\
+        #if defined __need_wint_t && !defined __glasses_wint_t\n#define __glasses_wint_t\n\
+        typedef unsigned int wint_t;\n#endif\n\
+        #if defined __need_NULL\n#undef NULL\n#define NULL 0\n#endif\n\
+        #undef __need_size_t\n#undef __need_ptrdiff_t\n#undef __need_wchar_t\n#undef __need_wint_t\n\
+        #undef __need_NULL\n"),
+    ("stdarg.h", "#ifndef __GNUC_VA_LIST\n#define __GNUC_VA_LIST\ntypedef char *__gnuc_va_list;\n#endif\n\
+        #if !defined __need___va_list && !defined __glasses_va_list\n#define __glasses_va_list\n\
+        typedef __gnuc_va_list va_list;\n#endif\n#undef __need___va_list\n"),
+];
+
+//Line markers in the output keep the lexer's line numbers matching the source files
+pub fn preprocess(path: &str, include_dirs: Vec<PathBuf>, c23: bool) -> Result<Vec<u8>, String> {
+    let mut pp = Preprocessor::new(include_dirs, c23);
+    for definition in PREDEFINED.iter() {
+        define(&tokenize(definition), &mut pp)?;
+    }
+    if c23 {
+        define(&tokenize("__STDC_VERSION__ 202311L"), &mut pp)?;
+    }
+
+    process_file(Path::new(path), path, &mut pp)?;
+    if !pp.output.ends_with('\n') {
+        pp.output.push('\n');
+    }
+
+    Ok(pp.output.into_bytes())
+}
+
+fn process_file(path: &Path, name: &str, pp: &mut Preprocessor) -> Result<(), String> {
+    let text = match fs::read(path) {
+        Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+        Err(_) => return Err(fmt_preprocessor_error(&pp.file, pp.line, &format!("Cannot read file {}", name))),
+    };
+
+    process_source(&text, path, name, pp)
+}
+
+fn process_source(text: &str, path: &Path, name: &str, pp: &mut Preprocessor) -> Result<(), String> {
+    let saved_file = mem::replace(&mut pp.file, name.to_string());
+    let lines = source_lines(text, pp)?;
+    let mut conds: Vec<Conditional> = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let SourceLine(number, line) = &lines[i];
+        pp.line = *number;
+        let active = conds.last().is_none_or(|cond| cond.active);
+
+        if let Some(text) = line.trim_start().strip_prefix('#') {
+            directive(text, path, &mut conds, active, pp)?;
+            i += 1;
+            continue;
+        }
+        if !active {
+            i += 1;
+            continue;
+        }
+
+        //The arguments of a function-like macro can run onto the following lines
+        let mut tokens = tokenize(line);
+        let mut consumed = 1;
+        while has_open_invocation(&tokens, pp) && i + consumed < lines.len()
+            && !lines[i + consumed].1.trim_start().starts_with('#') {
+            let mut next = tokenize(&lines[i + consumed].1);
+            if let Some(first) = next.first_mut() {
+                first.space_before = true;
+            }
+            tokens.extend(next);
+            consumed += 1;
+        }
+
+        let expanded = expand(tokens, pp)?;
+        emit(&tokens_to_string(&expanded), *number, pp);
+        i += consumed;
+    }
+
+    if !conds.is_empty() {
+        return Err(fmt_preprocessor_error(name, pp.line, "Unterminated conditional directive"));
+    }
+    pp.file = saved_file;
+
+    Ok(())
+}
+
+//Joins continued lines and replaces each comment with a space, splitting lines at comments that span several
+fn source_lines(text: &str, pp: &Preprocessor) -> Result<Vec<SourceLine>, String> {
+    let chars: Vec<char> = text.chars().filter(|c| *c != '\r').collect();
+    let mut lines: Vec<SourceLine> = Vec::new();
+    let mut current = String::new();
+    let mut start = 1;
+    let mut line = 1;
+    let mut quote: Option<char> = None;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        if c == '\\' && next == Some('\n') {
+            line += 1;
+            i += 2;
+        } else if c == '\n' {
+            lines.push(SourceLine(start, mem::take(&mut current)));
+            line += 1;
+            start = line;
+            quote = None;
+            i += 1;
+        } else if let Some(q) = quote {
+            current.push(c);
+            if c == '\\' && let Some(escaped) = next && escaped != '\n' {
+                current.push(escaped);
+                i += 1;
+            } else if c == q {
+                quote = None;
+            }
+            i += 1;
+        } else if c == '"' || c == '\'' {
+            quote = Some(c);
+            current.push(c);
+            i += 1;
+        } else if c == '/' && next == Some('/') {
+            while i < chars.len() && chars[i] != '\n' {
+                if chars[i] == '\\' && chars.get(i + 1) == Some(&'\n') {
+                    line += 1;
+                    i += 1;
+                }
+                i += 1;
+            }
+            current.push(' ');
+        } else if c == '/' && next == Some('*') {
+            let comment_line = line;
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                if chars[i] == '\n' {
+                    line += 1;
+                }
+                i += 1;
+            }
+            if i >= chars.len() {
+                return Err(fmt_preprocessor_error(&pp.file, comment_line, "Unterminated comment"));
+            }
+            current.push(' ');
+            i += 2;
+            //Code after a comment spanning lines starts a new line, so it keeps its own line number. Directives
+            //can't be split, and the lines they lose are made up for by the next line marker or blank lines
+            if line > comment_line && !current.trim_start().starts_with('#') {
+                lines.push(SourceLine(start, mem::take(&mut current)));
+                start = line;
+            }
+        } else {
+            current.push(c);
+            i += 1;
+        }
+    }
+    if !current.is_empty() {
+        lines.push(SourceLine(start, current));
+    }
+
+    Ok(lines)
+}
+
+fn has_open_invocation(tokens: &[PpToken], pp: &Preprocessor) -> bool {
+    for (i, token) in tokens.iter().enumerate() {
+        if token.kind != PpKind::Ident || !matches!(pp.macros.get(&token.text), Some(Macro::Function(_, _, _))) {
+            continue;
+        }
+        match tokens.get(i + 1) {
+            None => return true,
+            Some(next) if next.is_punct("(") => {
+                let mut depth = 0;
+                for inner in tokens[i + 1..].iter() {
+                    if inner.is_punct("(") {
+                        depth += 1;
+                    } else if inner.is_punct(")") {
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                }
+                if depth > 0 {
+                    return true;
+                }
+            },
+            _ => (),
+        }
+    }
+
+    false
+}
+
+//Blank lines or a line marker bring the output back in step with the source line
+fn emit(text: &str, line: u32, pp: &mut Preprocessor) {
+    if text.trim().is_empty() {
+        return;
+    }
+
+    if pp.out_file != pp.file || line < pp.out_line || line - pp.out_line > 8 {
+        pp.output.push_str(&format!("# {} {}\n", line, string_literal(&pp.file)));
+    } else {
+        for _ in pp.out_line..line {
+            pp.output.push('\n');
+        }
+    }
+    pp.output.push_str(text);
+    pp.output.push('\n');
+
+    pp.out_file = pp.file.clone();
+    pp.out_line = line + 1;
+}
+
+fn directive(text: &str, path: &Path, conds: &mut Vec<Conditional>, active: bool, pp: &mut Preprocessor) -> Result<(), String> {
+    let tokens = tokenize(text);
+    let name = match tokens.first() {
+        Some(token) => token.text.clone(),
+        None => return Ok(()),
+    };
+    let rest = &tokens[1..];
+
+    match name.as_str() {
+        "if" => {
+            let value = active && condition(rest, pp)?;
+            conds.push(Conditional { active: value, parent_active: active, taken: value, seen_else: false });
+        },
+        "ifdef" | "ifndef" => {
+            let value = active && (is_defined(macro_name(rest, pp)?, pp) == (name == "ifdef"));
+            conds.push(Conditional { active: value, parent_active: active, taken: value, seen_else: false });
+        },
+        "elif" => {
+            let (parent_active, taken) = match conds.last() {
+                Some(Conditional { seen_else: true, .. }) => return Err(pp_error(pp, "#elif after #else")),
+                Some(cond) => (cond.parent_active, cond.taken),
+                None => return Err(pp_error(pp, "#elif without #if")),
+            };
+            let value = parent_active && !taken && condition(rest, pp)?;
+            if let Some(cond) = conds.last_mut() {
+                cond.active = value;
+                cond.taken = taken || value;
+            }
+        },
+        "else" => match conds.last_mut() {
+            Some(Conditional { seen_else: true, .. }) => return Err(pp_error(pp, "#else after #else")),
+            Some(cond) => {
+                cond.active = cond.parent_active && !cond.taken;
+                cond.taken = true;
+                cond.seen_else = true;
+            },
+            None => return Err(pp_error(pp, "#else without #if")),
+        },
+        "endif" => {
+            if conds.pop().is_none() {
+                return Err(pp_error(pp, "#endif without #if"));
+            }
+        },
+        //Every other directive is skipped along with the group it is in
+        _ if !active => (),
+        "define" => define(rest, pp)?,
+        "undef" => {
+            let name = macro_name(rest, pp)?.to_string();
+            pp.macros.remove(&name);
+        },
+        "include" => {
+            let spec = text.trim_start().trim_start_matches("include").trim();
+            include(spec, path, pp)?;
+        },
+        "error" => return Err(pp_error(pp, &format!("#error {}", tokens_to_string(rest)))),
+        "pragma" => {
+            if rest.len() == 1 && rest[0].text == "once" && let Ok(canonical) = fs::canonicalize(path) {
+                pp.pragma_once.insert(canonical);
+            }
+        },
+        _ => return Err(pp_error(pp, &format!("Invalid preprocessing directive #{}", name))),
+    }
+
+    Ok(())
+}
+
+fn macro_name<'a>(tokens: &'a [PpToken], pp: &Preprocessor) -> Result<&'a str, String> {
+    match tokens.first() {
+        Some(token) if token.kind == PpKind::Ident => Ok(&token.text),
+        _ => Err(pp_error(pp, "Macro names must be identifiers")),
+    }
+}
+
+fn is_defined(name: &str, pp: &Preprocessor) -> bool {
+    pp.macros.contains_key(name) || name == "__LINE__" || name == "__FILE__"
+}
+
+//Replaces each defined operator before expansion, so it sees the macro names themselves
+fn condition(tokens: &[PpToken], pp: &Preprocessor) -> Result<bool, String> {
+    if tokens.is_empty() {
+        return Err(pp_error(pp, "#if with no expression"));
+    }
+
+    let mut replaced: Vec<PpToken> = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        if tokens[i].kind != PpKind::Ident || tokens[i].text != "defined" {
+            replaced.push(tokens[i].clone());
+            i += 1;
+            continue;
+        }
+
+        let parenthesized = tokens.get(i + 1).is_some_and(|t| t.is_punct("("));
+        let name_pos = if parenthesized { i + 2 } else { i + 1 };
+        let name = match tokens.get(name_pos) {
+            Some(token) if token.kind == PpKind::Ident => &token.text,
+            _ => return Err(pp_error(pp, "Macro name expected after defined")),
+        };
+        if parenthesized && !tokens.get(name_pos + 1).is_some_and(|t| t.is_punct(")")) {
+            return Err(pp_error(pp, "Expected ')' after defined"));
+        }
+
+        let value = if is_defined(name, pp) { "1" } else { "0" };
+        replaced.push(PpToken::new(PpKind::Number, value, tokens[i].space_before));
+        i = if parenthesized { name_pos + 2 } else { name_pos + 1 };
+    }
+
+    Ok(evaluate(&expand(replaced, pp)?, pp)? != 0)
+}
+
+fn define(tokens: &[PpToken], pp: &mut Preprocessor) -> Result<(), String> {
+    let name = macro_name(tokens, pp)?.to_string();
+    if name == "defined" {
+        return Err(pp_error(pp, "\"defined\" cannot be used as a macro name"));
+    }
+
+    //A function-like macro needs its '(' directly after the name
+    let is_function = tokens.get(1).is_some_and(|t| t.is_punct("(") && !t.space_before);
+    if !is_function {
+        let body = tokens[1..].to_vec();
+        check_paste_ends(&body, pp)?;
+        pp.macros.insert(name, Macro::Object(body));
+        return Ok(());
+    }
+
+    let mut params: Vec<String> = Vec::new();
+    let mut variadic = false;
+    let mut i = 2;
+    if tokens.get(i).is_some_and(|t| t.is_punct(")")) {
+        i += 1;
+    } else {
+        loop {
+            let param = match tokens.get(i) {
+                Some(token) if token.kind == PpKind::Ident && !variadic => token.text.clone(),
+                Some(token) if token.is_punct("...") && !variadic => {
+                    variadic = true;
+                    String::from("__VA_ARGS__")
+                },
+                _ => return Err(pp_error(pp, &format!("Invalid parameter list for macro {}", name))),
+            };
+            if params.contains(&param) {
+                return Err(pp_error(pp, &format!("Duplicate macro parameter {}", param)));
+            }
+            params.push(param);
+
+            i += 2;
+            match tokens.get(i - 1) {
+                Some(token) if token.is_punct(",") => (),
+                Some(token) if token.is_punct(")") => break,
+                _ => return Err(pp_error(pp, &format!("Invalid parameter list for macro {}", name))),
+            }
+        }
+    }
+
+    let body = tokens[i..].to_vec();
+    check_paste_ends(&body, pp)?;
+    for (j, token) in body.iter().enumerate() {
+        let is_param = body.get(j + 1).is_some_and(|next| next.kind == PpKind::Ident && params.contains(&next.text));
+        if token.is_punct("#") && !is_param {
+            return Err(pp_error(pp, "'#' is not followed by a macro parameter"));
+        }
+    }
+    pp.macros.insert(name, Macro::Function(params, variadic, body));
+
+    Ok(())
+}
+
+fn check_paste_ends(body: &[PpToken], pp: &Preprocessor) -> Result<(), String> {
+    if body.first().is_some_and(|t| t.is_punct("##")) || body.last().is_some_and(|t| t.is_punct("##")) {
+        return Err(pp_error(pp, "'##' cannot appear at either end of a macro expansion"));
+    }
+
+    Ok(())
+}
+
+fn include(spec: &str, path: &Path, pp: &mut Preprocessor) -> Result<(), String> {
+    //Anything other than a header name is macro expanded first
+    let spec = if spec.starts_with('"') || spec.starts_with('<') {
+        spec.to_string()
+    } else {
+        tokens_to_string(&expand(tokenize(spec), pp)?)
+    };
+
+    let (header, is_angled) = match spec.chars().next() {
+        Some('"') => (spec[1..].split_once('"').map(|(header, _)| header), false),
+        Some('<') => (spec[1..].split_once('>').map(|(header, _)| header), true),
+        _ => (None, false),
+    };
+    let header = match header {
+        Some(header) if !header.is_empty() => header,
+        _ => return Err(pp_error(pp, "#include expects \"FILENAME\" or <FILENAME>")),
+    };
+
+    let mut search_dirs: Vec<PathBuf> = Vec::new();
+    if !is_angled {
+        search_dirs.push(path.parent().map_or(PathBuf::new(), |dir| dir.to_path_buf()));
+    }
+    search_dirs.extend(pp.include_dirs.iter().cloned());
+    search_dirs.extend(SYSTEM_DIRS.iter().map(PathBuf::from));
+
+    let found = search_dirs.iter().map(|dir| dir.join(header)).find(|candidate| candidate.is_file());
+    let builtin = BUILTIN_HEADERS.iter().find(|(name, _)| *name == header).map(|(_, text)| *text);
+    if found.as_ref().and_then(|found| fs::canonicalize(found).ok()).is_some_and(|canonical| pp.pragma_once.contains(&canonical)) {
+        return Ok(());
+    }
+    if pp.depth >= MAX_INCLUDE_DEPTH {
+        return Err(pp_error(pp, "#include nested too deeply"));
+    }
+
+    pp.depth += 1;
+    match (found, builtin) {
+        (Some(found), _) => process_file(&found, &found.display().to_string(), pp)?,
+        (None, Some(text)) => process_source(text, Path::new(header), &format!("<{}>", header), pp)?,
+        (None, None) => return Err(pp_error(pp, &format!("{}: No such file or directory", header))),
+    }
+    pp.depth -= 1;
+
+    Ok(())
+}
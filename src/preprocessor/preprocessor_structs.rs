@@ -0,0 +1,88 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum PpKind {
+    Ident,
+    Number,
+    CharLit,
+    StrLit,
+    Punct,
+    //Stands in for an empty macro argument next to '##'
+    Placemarker,
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct PpToken {
+    pub kind: PpKind,
+    pub text: String,
+    pub space_before: bool,
+    //Macros that must not be expanded again inside this token's expansion
+    pub hideset: Vec<String>,
+}
+
+impl PpToken {
+    pub fn new(kind: PpKind, text: &str, space_before: bool) -> Self {
+        Self { kind, text: text.to_string(), space_before, hideset: Vec::new() }
+    }
+
+    pub fn is_punct(&self, text: &str) -> bool {
+        self.kind == PpKind::Punct && self.text == text
+    }
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub enum Macro {
+    Object(Vec<PpToken>),
+    //Parameters, whether the last one is __VA_ARGS__, and the replacement list
+    Function(Vec<String>, bool, Vec<PpToken>),
+}
+
+//A line after continuations are joined and comments removed, with the physical line it starts on
+#[derive(Debug)]
+pub struct SourceLine(pub u32, pub String);
+
+#[derive(Debug)]
+pub struct Conditional {
+    pub active: bool,
+    pub parent_active: bool,
+    pub taken: bool,
+    pub seen_else: bool,
+}
+
+pub struct Preprocessor {
+    pub macros: HashMap<String, Macro>,
+    pub include_dirs: Vec<PathBuf>,
+    pub pragma_once: HashSet<PathBuf>,
+    pub output: String,
+    pub depth: usize,
+    //The file and line the lexer will attribute the next output line to
+    pub out_file: String,
+    pub out_line: u32,
+    //The file and line being expanded, for __FILE__, __LINE__ and diagnostics
+    pub file: String,
+    pub line: u32,
+    //Under C23 true and false are keywords, so #if sees them as 1 and 0
+    pub c23: bool,
+}
+
+impl Preprocessor {
+    pub fn new(include_dirs: Vec<PathBuf>, c23: bool) -> Self {
+        Self {
+            macros: HashMap::new(),
+            include_dirs,
+            pragma_once: HashSet::new(),
+            output: String::new(),
+            depth: 0,
+            out_file: String::new(),
+            out_line: 1,
+            file: String::new(),
+            line: 1,
+            c23,
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use crate::parser::ast::*;
+use crate::resolver::type_checker::{SymbolTable, common_type, type_alignment, type_size};
+
+static NON_CONSTANT: &str = "Non-constant expression";
+static INVALID_OPERAND: &str = "Invalid operand";
+
+//Folds an expression with C's conversion rules, so 1u - 2 wraps around while INT_MAX + 1 is an error.
+//Without a symbol table, sizeof only works on expressions and types whose size is fixed
+pub fn eval_constant(expr: &Expr, symbols: Option<&SymbolTable>) -> Result<Const, String> {
+    match &expr.kind {
+        ExprKind::Constant(val) => Ok(val.clone()),
+        ExprKind::Cast(target, inner) => convert(&eval_constant(inner, symbols)?, target),
+        ExprKind::Unary(op, inner) => unary(op, promote(eval_constant(inner, symbols)?)),
+        //The right operand of && and || is only evaluated when it decides the result
+        ExprKind::Binary(BinaryOp::And, left, right) => {
+            if is_zero(&eval_constant(left, symbols)?) {
+                return Ok(Const::Int(0));
+            }
+            Ok(Const::Int(!is_zero(&eval_constant(right, symbols)?) as i32))
+        },
+        ExprKind::Binary(BinaryOp::Or, left, right) => {
+            if !is_zero(&eval_constant(left, symbols)?) {
+                return Ok(Const::Int(1));
+            }
+            Ok(Const::Int(!is_zero(&eval_constant(right, symbols)?) as i32))
+        },
+        ExprKind::Binary(op, left, right) => binary(op, eval_constant(left, symbols)?, eval_constant(right, symbols)?),
+        ExprKind::Conditional(cond, then, otherwise) => {
+            let (chosen, other) = if is_zero(&eval_constant(cond, symbols)?) { (otherwise, then) } else { (then, otherwise) };
+            let value = promote(eval_constant(chosen, symbols)?);
+            //The branch that isn't taken only contributes its type
+            match eval_constant(other, symbols) {
+                Ok(other) => convert(&value, &common_type(&const_type(&value), &const_type(&promote(other)))),
+                Err(_) => Ok(value),
+            }
+        },
+        ExprKind::SizeOf(inner) => match &inner.ty {
+            Some(inner_type) => measure(inner_type, symbols, false),
+            None => measure(&const_type(&eval_constant(inner, symbols)?), symbols, false),
+        },
+        ExprKind::SizeOfType(target) => measure(target, symbols, false),
+        ExprKind::AlignOf(target) => measure(target, symbols, true),
+        ExprKind::Builtin(Builtin::Expect, args) => convert(&eval_constant(&args[0], symbols)?, &Type::Long),
+        ExprKind::Builtin(Builtin::ConstantP, args) => Ok(Const::Int(eval_constant(&args[0], symbols).is_ok() as i32)),
+        _ => Err(String::from(NON_CONSTANT)),
+    }
+}
+
+//Enumerators, case labels and static assertions need integer constant expressions
+pub fn eval_integer(expr: &Expr, symbols: Option<&SymbolTable>) -> Result<Const, String> {
+    match promote(eval_constant(expr, symbols)?) {
+        Const::Double(_) => Err(String::from("Non-integer constant expression")),
+        val => Ok(val),
+    }
+}
+
+pub fn integer_value(val: &Const) -> i128 {
+    match val {
+        Const::Int(v) => *v as i128,
+        Const::Long(v) => *v as i128,
+        Const::UInt(v) => *v as i128,
+        Const::ULong(v) => *v as i128,
+        Const::Char(v) => *v as i128,
+        Const::Double(v) => *v as i128,
+    }
+}
+
+fn const_type(val: &Const) -> Type {
+    match val {
+        Const::Int(_) => Type::Int,
+        Const::Long(_) => Type::Long,
+        Const::UInt(_) => Type::UInt,
+        Const::ULong(_) => Type::ULong,
+        Const::Double(_) => Type::Double,
+        Const::Char(_) => Type::Char,
+    }
+}
+
+fn promote(val: Const) -> Const {
+    match val {
+        Const::Char(v) => Const::Int(v as i32),
+        _ => val,
+    }
+}
+
+fn is_zero(val: &Const) -> bool {
+    match val {
+        Const::Double(v) => *v == 0.0,
+        _ => integer_value(val) == 0,
+    }
+}
+
+//Truncates a value to the width of an integer type, the way conversions between integer types do
+fn wrap(value: i128, target: &Type) -> Const {
+    match target {
+        Type::Char | Type::SChar => Const::Char(value as i8),
+        Type::UChar => Const::Int(value as u8 as i32),
+        Type::Bool => Const::Int((value != 0) as i32),
+        Type::Int => Const::Int(value as i32),
+        Type::Long => Const::Long(value as i64),
+        Type::UInt => Const::UInt(value as u32),
+        _ => Const::ULong(value as u64),
+    }
+}
+
+//Unsigned arithmetic wraps around, but a signed result that doesn't fit is undefined
+fn checked(value: i128, target: &Type) -> Result<Const, String> {
+    let result = wrap(value, target);
+    if target.is_signed() && integer_value(&result) != value {
+        return Err(String::from("Integer overflow"));
+    }
+
+    Ok(result)
+}
+
+fn convert(val: &Const, target: &Type) -> Result<Const, String> {
+    let target = target.unqualified();
+    match (val, target) {
+        (_, Type::Bool) => Ok(Const::Int(!is_zero(val) as i32)),
+        (Const::Double(_), Type::Double) => Ok(val.clone()),
+        (Const::Double(_), Type::Pointer(_)) => Err(String::from(INVALID_OPERAND)),
+        //A double that is out of range for the integer type has no defined value
+        (Const::Double(v), _) if target.is_integer() => {
+            let result = wrap(v.trunc() as i128, target);
+            if integer_value(&result) as f64 != v.trunc() {
+                return Err(String::from("Integer overflow"));
+            }
+            Ok(result)
+        },
+        (_, Type::Double) => Ok(Const::Double(integer_value(val) as f64)),
+        (_, Type::Pointer(_)) => Ok(wrap(integer_value(val), &Type::ULong)),
+        _ if target.is_integer() => Ok(wrap(integer_value(val), target)),
+        _ => Err(String::from(NON_CONSTANT)),
+    }
+}
+
+fn unary(op: &UnaryOp, val: Const) -> Result<Const, String> {
+    match (op, &val) {
+        (UnaryOp::Not, _) => Ok(Const::Int(is_zero(&val) as i32)),
+        (UnaryOp::Negate, Const::Double(v)) => Ok(Const::Double(-v)),
+        (UnaryOp::Negate, _) => checked(-integer_value(&val), &const_type(&val)),
+        (UnaryOp::Complement, Const::Double(_)) => Err(String::from(INVALID_OPERAND)),
+        (UnaryOp::Complement, _) => Ok(wrap(!integer_value(&val), &const_type(&val))),
+    }
+}
+
+fn binary(op: &BinaryOp, left: Const, right: Const) -> Result<Const, String> {
+    let (left, right) = (promote(left), promote(right));
+    if let BinaryOp::ShiftLeft | BinaryOp::ShiftRight = op {
+        return shift(op, left, right);
+    }
+
+    let common = common_type(&const_type(&left), &const_type(&right));
+    let (left, right) = (convert(&left, &common)?, convert(&right, &common)?);
+    if let (Const::Double(l), Const::Double(r)) = (&left, &right) {
+        return double_binary(op, *l, *r);
+    }
+
+    //Operands are at most 64 bits wide, so only an unsigned product can overflow i128, and it wraps anyway
+    let (l, r) = (integer_value(&left), integer_value(&right));
+    match op {
+        BinaryOp::Add => checked(l + r, &common),
+        BinaryOp::Subtract => checked(l - r, &common),
+        BinaryOp::Multiply => checked(l.wrapping_mul(r), &common),
+        BinaryOp::Divide | BinaryOp::Remainder if r == 0 => Err(String::from("Division by zero")),
+        BinaryOp::Divide => checked(l / r, &common),
+        //The remainder is defined through the quotient, so INT_MIN % -1 overflows just like INT_MIN / -1
+        BinaryOp::Remainder => checked(l / r, &common).and_then(|_| checked(l % r, &common)),
+        BinaryOp::BitAnd => Ok(wrap(l & r, &common)),
+        BinaryOp::BitOr => Ok(wrap(l | r, &common)),
+        BinaryOp::BitXor => Ok(wrap(l ^ r, &common)),
+        BinaryOp::Equal => Ok(Const::Int((l == r) as i32)),
+        BinaryOp::NotEqual => Ok(Const::Int((l != r) as i32)),
+        BinaryOp::LessThan => Ok(Const::Int((l < r) as i32)),
+        BinaryOp::LessEqual => Ok(Const::Int((l <= r) as i32)),
+        BinaryOp::GreatThan => Ok(Const::Int((l > r) as i32)),
+        BinaryOp::GreatEqual => Ok(Const::Int((l >= r) as i32)),
+        _ => Err(String::from(NON_CONSTANT)),
+    }
+}
+
+fn double_binary(op: &BinaryOp, l: f64, r: f64) -> Result<Const, String> {
+    match op {
+        BinaryOp::Add => Ok(Const::Double(l + r)),
+        BinaryOp::Subtract => Ok(Const::Double(l - r)),
+        BinaryOp::Multiply => Ok(Const::Double(l * r)),
+        BinaryOp::Divide => Ok(Const::Double(l / r)),
+        BinaryOp::Equal => Ok(Const::Int((l == r) as i32)),
+        BinaryOp::NotEqual => Ok(Const::Int((l != r) as i32)),
+        BinaryOp::LessThan => Ok(Const::Int((l < r) as i32)),
+        BinaryOp::LessEqual => Ok(Const::Int((l <= r) as i32)),
+        BinaryOp::GreatThan => Ok(Const::Int((l > r) as i32)),
+        BinaryOp::GreatEqual => Ok(Const::Int((l >= r) as i32)),
+        _ => Err(String::from(INVALID_OPERAND)),
+    }
+}
+
+//The result has the type of the left operand, and the count has to be less than its width
+fn shift(op: &BinaryOp, left: Const, right: Const) -> Result<Const, String> {
+    if let Const::Double(_) = left {
+        return Err(String::from(INVALID_OPERAND));
+    }
+    if let Const::Double(_) = right {
+        return Err(String::from(INVALID_OPERAND));
+    }
+
+    let left_type = const_type(&left);
+    let (l, count) = (integer_value(&left), integer_value(&right));
+    if count < 0 || count >= left_type.size() as i128 * 8 {
+        return Err(String::from("Shift count out of range"));
+    }
+
+    match op {
+        BinaryOp::ShiftLeft if left_type.is_signed() && l < 0 => Err(String::from("Integer overflow")),
+        BinaryOp::ShiftLeft => checked(l << count, &left_type),
+        _ => Ok(wrap(l >> count, &left_type)),
+    }
+}
+
+fn measure(target: &Type, symbols: Option<&SymbolTable>, alignment: bool) -> Result<Const, String> {
+    let value = match symbols {
+        Some(symbols) if alignment => type_alignment(target, symbols),
+        Some(symbols) => type_size(target, symbols),
+        None if has_layout(target) => return Err(String::from(NON_CONSTANT)),
+        None if alignment => target.alignment(),
+        None => target.size(),
+    };
+
+    Ok(Const::ULong(value as u64))
+}
+
+//Structure and union layouts aren't known until type checking
+fn has_layout(target: &Type) -> bool {
+    match target.unqualified() {
+        Type::Structure(_) | Type::Union(_) => true,
+        Type::Array(elem, _) => has_layout(elem),
+        _ => false,
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::parser::ast::*;
+use crate::utilities::error_handler::resolver_error;
+
+static STMT_EXPR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+//Maps each label in a function to its unique name and the statement expressions around it, innermost last
+type LabelMap = HashMap<String, (String, Vec<usize>)>;
+//The label of each goto in a function and the statement expressions around the goto
+type Gotos = Vec<(String, Vec<usize>)>;
+
+pub fn resolve_labels(ast: &mut Program) {
+    match ast {
+        Program::Program(decls) => {
+            for decl in decls.iter_mut() {
+                if let Decl::Func(FuncDecl::Function(name, _, Some(body), _, _)) = decl {
+                    //Labels have function scope, so all of them are collected before any goto is resolved
+                    let mut label_map: LabelMap = HashMap::new();
+                    let mut gotos: Gotos = Vec::new();
+                    collect_block(body, name, &mut label_map, &mut gotos, &[]);
+                    check_gotos(&gotos, &label_map);
+                    *body = rename_block(body, &label_map);
+                }
+            }
+        }
+    }
+}
+
+//Jumping out of a statement expression is allowed, but jumping into one from outside it is not
+fn check_gotos(gotos: &Gotos, label_map: &LabelMap) {
+    for (label, stmt_exprs) in gotos.iter() {
+        if let Some((_, label_stmt_exprs)) = label_map.get(label) && !stmt_exprs.starts_with(label_stmt_exprs) {
+            resolver_error(format!("goto {} jumps into a statement expression", label).as_str());
+        }
+    }
+}
+
+fn collect_block(items: &Block, func: &str, label_map: &mut LabelMap, gotos: &mut Gotos, stmt_exprs: &[usize]) {
+    match items {
+        Block::Block(items) => {
+            for item in items.iter() {
+                match item {
+                    BlockItem::S(stmt) => collect_stmt(stmt, func, label_map, gotos, stmt_exprs),
+                    BlockItem::D(Decl::Var(decl)) => collect_decl(decl, func, label_map, gotos, stmt_exprs),
+                    BlockItem::D(_) => (),
+                }
+            }
+        }
+    }
+}
+
+fn collect_decl(decl: &VarDecl, func: &str, label_map: &mut LabelMap, gotos: &mut Gotos, stmt_exprs: &[usize]) {
+    match decl {
+        VarDecl::Declaration(_, init, _, _) => if let Some(init) = init {
+            init.map_blocks(&mut |block| {
+                collect_stmt_expr(block, func, label_map, gotos, stmt_exprs);
+                block.clone()
+            });
+        },
+    }
+}
+
+//Labels inside statement expressions belong to the enclosing function like any other
+fn collect_expr(expr: &Expr, func: &str, label_map: &mut LabelMap, gotos: &mut Gotos, stmt_exprs: &[usize]) {
+    expr.map_blocks(&mut |block| {
+        collect_stmt_expr(block, func, label_map, gotos, stmt_exprs);
+        block.clone()
+    });
+}
+
+fn collect_stmt_expr(block: &Block, func: &str, label_map: &mut LabelMap, gotos: &mut Gotos, stmt_exprs: &[usize]) {
+    let mut inner = stmt_exprs.to_vec();
+    inner.push(STMT_EXPR_COUNTER.fetch_add(1, Ordering::Relaxed));
+    collect_block(block, func, label_map, gotos, &inner);
+}
+
+fn collect_stmt(stmt: &Stmt, func: &str, label_map: &mut LabelMap, gotos: &mut Gotos, stmt_exprs: &[usize]) {
+    match stmt {
+        Stmt::Labeled(label, body) => {
+            if label_map.contains_key(label) {
+                resolver_error(format!("Duplicate label {} in function {}", label, func).as_str());
+            }
+            //Identifiers never contain dots, so these names can't clash with generated labels
+            label_map.insert(label.clone(), (format!("label.{}.{}", func, label), stmt_exprs.to_vec()));
+            collect_stmt(body, func, label_map, gotos, stmt_exprs);
+        },
+        Stmt::If(cond, then, otherwise) => {
+            collect_expr(cond, func, label_map, gotos, stmt_exprs);
+            collect_stmt(then, func, label_map, gotos, stmt_exprs);
+            if let Some(otherwise) = otherwise {
+                collect_stmt(otherwise, func, label_map, gotos, stmt_exprs);
+            }
+        },
+        Stmt::Compound(block) => collect_block(block, func, label_map, gotos, stmt_exprs),
+        Stmt::While(cond, body, _) | Stmt::DoWhile(body, cond, _) | Stmt::Switch(cond, body, _, _) => {
+            collect_expr(cond, func, label_map, gotos, stmt_exprs);
+            collect_stmt(body, func, label_map, gotos, stmt_exprs);
+        },
+        Stmt::For(init, cond, post, body, _) => {
+            match init {
+                ForInit::InitDecl(decls) => decls.iter().for_each(|d| collect_decl(d, func, label_map, gotos, stmt_exprs)),
+                ForInit::InitExpr(e) => e.iter().for_each(|e| collect_expr(e, func, label_map, gotos, stmt_exprs)),
+            }
+            cond.iter().chain(post.iter()).for_each(|e| collect_expr(e, func, label_map, gotos, stmt_exprs));
+            collect_stmt(body, func, label_map, gotos, stmt_exprs);
+        },
+        Stmt::Case(_, body, _) | Stmt::Default(body, _) => collect_stmt(body, func, label_map, gotos, stmt_exprs),
+        Stmt::Return(e) | Stmt::Expression(e) => collect_expr(e, func, label_map, gotos, stmt_exprs),
+        Stmt::Goto(label) => gotos.push((label.clone(), stmt_exprs.to_vec())),
+        Stmt::Break(_) | Stmt::Continue(_) | Stmt::Null => (),
+    }
+}
+
+fn rename_block(items: &Block, label_map: &LabelMap) -> Block {
+    match items {
+        Block::Block(items) => Block::Block(items.iter().map(|item| match item {
+            BlockItem::S(stmt) => BlockItem::S(rename_stmt(stmt, label_map)),
+            BlockItem::D(Decl::Var(decl)) => BlockItem::D(Decl::Var(rename_decl(decl, label_map))),
+            BlockItem::D(_) => item.clone(),
+        }).collect()),
+    }
+}
+
+fn rename_decl(decl: &VarDecl, label_map: &LabelMap) -> VarDecl {
+    match decl {
+        VarDecl::Declaration(name, init, var_type, storage) => {
+            let new_init = init.as_ref().map(|i| i.map_blocks(&mut |block| rename_block(block, label_map)));
+            VarDecl::Declaration(name.clone(), new_init, var_type.clone(), storage.clone())
+        }
+    }
+}
+
+fn rename_expr(expr: &Expr, label_map: &LabelMap) -> Expr {
+    expr.map_blocks(&mut |block| rename_block(block, label_map))
+}
+
+fn rename_optional_expr(expr: &Option<Expr>, label_map: &LabelMap) -> Option<Expr> {
+    expr.as_ref().map(|e| rename_expr(e, label_map))
+}
+
+fn rename_stmt(stmt: &Stmt, label_map: &LabelMap) -> Stmt {
+    match stmt {
+        Stmt::Labeled(label, body) => Stmt::Labeled(label_map[label].0.clone(), Box::from(rename_stmt(body, label_map))),
+        Stmt::Goto(label) => match label_map.get(label) {
+            Some((new_label, _)) => Stmt::Goto(new_label.clone()),
+            None => resolver_error(format!("goto to undefined label {}", label).as_str()),
+        },
+        Stmt::If(cond, then, otherwise) => Stmt::If(
+            rename_expr(cond, label_map),
+            Box::from(rename_stmt(then, label_map)),
+            otherwise.as_ref().map(|s| Box::from(rename_stmt(s, label_map)))),
+        Stmt::Compound(block) => Stmt::Compound(rename_block(block, label_map)),
+        Stmt::While(cond, body, label) =>
+            Stmt::While(rename_expr(cond, label_map), Box::from(rename_stmt(body, label_map)), label.clone()),
+        Stmt::DoWhile(body, cond, label) =>
+            Stmt::DoWhile(Box::from(rename_stmt(body, label_map)), rename_expr(cond, label_map), label.clone()),
+        Stmt::For(init, cond, post, body, label) => {
+            let new_init = match init {
+                ForInit::InitDecl(decls) => ForInit::InitDecl(decls.iter().map(|d| rename_decl(d, label_map)).collect()),
+                ForInit::InitExpr(e) => ForInit::InitExpr(rename_optional_expr(e, label_map)),
+            };
+            Stmt::For(new_init, rename_optional_expr(cond, label_map), rename_optional_expr(post, label_map),
+                Box::from(rename_stmt(body, label_map)), label.clone())
+        },
+        Stmt::Switch(cond, body, label, cases) =>
+            Stmt::Switch(rename_expr(cond, label_map), Box::from(rename_stmt(body, label_map)), label.clone(), cases.clone()),
+        Stmt::Case(value, body, label) => Stmt::Case(value.clone(), Box::from(rename_stmt(body, label_map)), label.clone()),
+        Stmt::Default(body, label) => Stmt::Default(Box::from(rename_stmt(body, label_map)), label.clone()),
+        Stmt::Return(e) => Stmt::Return(rename_expr(e, label_map)),
+        Stmt::Expression(e) => Stmt::Expression(rename_expr(e, label_map)),
+        Stmt::Break(_) | Stmt::Continue(_) | Stmt::Null => stmt.clone(),
+    }
+}
@@ -0,0 +1,132 @@
+use crate::parser::ast::*;
+use crate::utilities::error_handler::resolver_error;
+
+pub fn label_loops(ast: &mut Program) {
+    let mut counter: u32 = 0;
+
+    match ast {
+        Program::Program(decls) => {
+            for decl in decls.iter_mut() {
+                if let Decl::Func(FuncDecl::Function(_, _, Some(body), _, _)) = decl {
+                    *body = label_block(body, None, None, &mut counter);
+                }
+            }
+        }
+    }
+}
+
+fn label_block(items: &Block, break_label: Option<&String>, continue_label: Option<&String>, counter: &mut u32) -> Block {
+    let mut block_items: Vec<BlockItem> = Vec::new();
+
+    match items {
+        Block::Block(items) => {
+            for item in items.iter() {
+                block_items.push(label_item(item, break_label, continue_label, counter));
+            }
+        }
+    }
+
+    Block::Block(block_items)
+}
+
+fn label_item(item: &BlockItem, break_label: Option<&String>, continue_label: Option<&String>, counter: &mut u32) -> BlockItem {
+    match item {
+        BlockItem::S(stmt) => BlockItem::S(label_stmt(stmt, break_label, continue_label, counter)),
+        BlockItem::D(Decl::Var(decl)) => BlockItem::D(Decl::Var(label_decl(decl, break_label, continue_label, counter))),
+        BlockItem::D(_) => item.clone(),
+    }
+}
+
+fn label_decl(decl: &VarDecl, break_label: Option<&String>, continue_label: Option<&String>, counter: &mut u32) -> VarDecl {
+    match decl {
+        VarDecl::Declaration(name, init, var_type, storage) => {
+            let new_init = init.as_ref()
+                .map(|i| i.map_blocks(&mut |block| label_block(block, break_label, continue_label, counter)));
+            VarDecl::Declaration(name.clone(), new_init, var_type.clone(), storage.clone())
+        }
+    }
+}
+
+//Break and continue inside a statement expression can leave it for an enclosing loop.
+//Loop conditions are outside the loop body, so they only see the labels around the loop
+fn label_expr(expr: &Expr, break_label: Option<&String>, continue_label: Option<&String>, counter: &mut u32) -> Expr {
+    expr.map_blocks(&mut |block| label_block(block, break_label, continue_label, counter))
+}
+
+fn label_optional_expr(expr: &Option<Expr>, break_label: Option<&String>, continue_label: Option<&String>,
+    counter: &mut u32) -> Option<Expr> {
+    expr.as_ref().map(|e| label_expr(e, break_label, continue_label, counter))
+}
+
+//Break targets the innermost loop or switch, while continue skips past switches to the innermost loop.
+//Case labels are matched to their switch by the type checker, once their values can be evaluated
+fn label_stmt(stmt: &Stmt, break_label: Option<&String>, continue_label: Option<&String>, counter: &mut u32) -> Stmt {
+    match stmt {
+        Stmt::Break(_) => match break_label {
+            Some(label) => Stmt::Break(label.clone()),
+            None => resolver_error("break statement outside of a loop or switch"),
+        },
+        Stmt::Continue(_) => match continue_label {
+            Some(label) => Stmt::Continue(label.clone()),
+            None => resolver_error("continue statement outside of a loop"),
+        },
+        Stmt::While(cond, body, _) => {
+            let new_cond = label_expr(cond, break_label, continue_label, counter);
+            let label = new_label("loop", counter);
+            let new_body = label_stmt(body, Some(&label), Some(&label), counter);
+            Stmt::While(new_cond, Box::from(new_body), label)
+        },
+        Stmt::DoWhile(body, cond, _) => {
+            let label = new_label("loop", counter);
+            let new_body = label_stmt(body, Some(&label), Some(&label), counter);
+            let new_cond = label_expr(cond, break_label, continue_label, counter);
+            Stmt::DoWhile(Box::from(new_body), new_cond, label)
+        },
+        Stmt::For(init, cond, post, body, _) => {
+            let new_init = match init {
+                ForInit::InitDecl(decls) =>
+                    ForInit::InitDecl(decls.iter().map(|d| label_decl(d, break_label, continue_label, counter)).collect()),
+                ForInit::InitExpr(e) => ForInit::InitExpr(label_optional_expr(e, break_label, continue_label, counter)),
+            };
+            let label = new_label("loop", counter);
+            let new_cond = label_optional_expr(cond, break_label, continue_label, counter);
+            let new_post = label_optional_expr(post, break_label, continue_label, counter);
+            let new_body = label_stmt(body, Some(&label), Some(&label), counter);
+            Stmt::For(new_init, new_cond, new_post, Box::from(new_body), label)
+        },
+        Stmt::Switch(cond, body, _, cases) => {
+            let new_cond = label_expr(cond, break_label, continue_label, counter);
+            let label = new_label("switch", counter);
+            let new_body = label_stmt(body, Some(&label), continue_label, counter);
+            Stmt::Switch(new_cond, Box::from(new_body), label, cases.clone())
+        },
+        Stmt::Case(value, body, _) => {
+            let label = new_label("case", counter);
+            let new_body = label_stmt(body, break_label, continue_label, counter);
+            Stmt::Case(value.clone(), Box::from(new_body), label)
+        },
+        Stmt::Default(body, _) => {
+            let label = new_label("default", counter);
+            let new_body = label_stmt(body, break_label, continue_label, counter);
+            Stmt::Default(Box::from(new_body), label)
+        },
+        Stmt::If(cond, then, otherwise) => {
+            let new_cond = label_expr(cond, break_label, continue_label, counter);
+            let new_then = label_stmt(then, break_label, continue_label, counter);
+            let new_otherwise = otherwise.as_ref()
+                .map(|s| Box::from(label_stmt(s, break_label, continue_label, counter)));
+            Stmt::If(new_cond, Box::from(new_then), new_otherwise)
+        },
+        Stmt::Compound(block) => Stmt::Compound(label_block(block, break_label, continue_label, counter)),
+        Stmt::Labeled(label, body) =>
+            Stmt::Labeled(label.clone(), Box::from(label_stmt(body, break_label, continue_label, counter))),
+        Stmt::Return(e) => Stmt::Return(label_expr(e, break_label, continue_label, counter)),
+        Stmt::Expression(e) => Stmt::Expression(label_expr(e, break_label, continue_label, counter)),
+        Stmt::Goto(_) | Stmt::Null => stmt.clone(),
+    }
+}
+
+fn new_label(start: &str, counter: &mut u32) -> String {
+    *counter += 1;
+    format!("{}.{}", start, counter)
+}
@@ -1,3 +1,6 @@
-
 pub mod resolution;
-mod var_resolver;
\ No newline at end of file
+pub mod type_checker;
+mod var_resolver;
+mod loop_labeler;
+mod label_resolver;
+mod const_evaluator;
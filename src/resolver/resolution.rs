@@ -1,7 +1,12 @@
-
 use crate::parser::ast::*;
 use crate::resolver::var_resolver::*;
+use crate::resolver::loop_labeler::*;
+use crate::resolver::label_resolver::*;
+use crate::resolver::type_checker::*;
 
-pub fn resolve(ast: &mut FuncDef) {
+pub fn resolve(ast: &mut Program) -> SymbolTable {
     resolve_vars(ast);
-}
\ No newline at end of file
+    label_loops(ast);
+    resolve_labels(ast);
+    check_types(ast)
+}
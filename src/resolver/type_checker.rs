@@ -0,0 +1,1424 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::parser::ast::*;
+use crate::resolver::const_evaluator::{eval_constant, eval_integer, integer_value};
+use crate::resolver::var_resolver::source_name;
+use crate::utilities::error_handler::type_error;
+
+static STRING_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum StaticInit {
+    Char(i8),
+    UChar(u8),
+    Int(i32),
+    Long(i64),
+    UInt(u32),
+    ULong(u64),
+    Double(f64),
+    Zero(i32),
+    StringInit(Vec<u8>, bool),
+    PointerInit(String, i64),
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+#[derive(PartialEq)]
+pub enum InitialValue {
+    Tentative,
+    Initial(Vec<StaticInit>),
+    NoInitializer,
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct StructMember {
+    pub name: String,
+    pub member_type: Type,
+    pub offset: i32,
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct StructDef {
+    pub alignment: i32,
+    pub size: i32,
+    pub members: Vec<StructMember>,
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub enum IdentAttrs {
+    Fun(bool, bool),
+    Static(InitialValue, bool),
+    Constant(StaticInit),
+    Struct(StructDef),
+    Enumerator(i32),
+    Local,
+}
+
+#[derive(Debug)]
+#[derive(Clone)]
+pub struct Symbol {
+    pub sym_type: Type,
+    pub attrs: IdentAttrs,
+}
+
+pub type SymbolTable = HashMap<String, Symbol>;
+
+pub fn check_types(ast: &mut Program) -> SymbolTable {
+    let mut symbols: SymbolTable = HashMap::new();
+
+    match ast {
+        Program::Program(decls) => {
+            for decl in decls.iter_mut() {
+                *decl = match decl {
+                    Decl::Func(func) => Decl::Func(check_function(func, &mut symbols)),
+                    Decl::Var(var) => Decl::Var(check_file_scope_var(var, &mut symbols)),
+                    Decl::Struct(decl) => Decl::Struct(check_struct_decl(decl, &mut symbols, None)),
+                    Decl::StaticAssert(decl) => {
+                        check_static_assert(decl, &mut symbols, None);
+                        Decl::StaticAssert(decl.clone())
+                    },
+                    Decl::Enum(decl) => Decl::Enum(check_enum_decl(decl, &mut symbols, None)),
+                    Decl::Typedef(decl) => Decl::Typedef(check_typedef_decl(decl, &mut symbols, None)),
+                };
+            }
+        }
+    }
+
+    for symbol in symbols.values_mut() {
+        symbol.sym_type = symbol.sym_type.strip_qualifiers();
+        if let IdentAttrs::Struct(def) = &mut symbol.attrs {
+            for member in def.members.iter_mut() {
+                member.member_type = member.member_type.strip_qualifiers();
+            }
+        }
+    }
+
+    symbols
+}
+
+fn check_function(func: &FuncDecl, symbols: &mut SymbolTable) -> FuncDecl {
+    match func {
+        FuncDecl::Function(name, params, body, fun_type, storage) => {
+            let fun_type = &adjust_param_types(&complete_type(fun_type, symbols, None));
+            let has_body = body.is_some();
+            if let Type::Function(param_types, ret_type) = fun_type {
+                if matches!(**ret_type, Type::Array(_, _) | Type::Function(_, _)) {
+                    type_error(format!("Function {} returns an array or function", name).as_str());
+                }
+                //Structures passed or returned by value need their layout once the function is defined
+                let incomplete = !is_complete(ret_type, symbols) || param_types.iter().any(|t| !is_complete(t, symbols));
+                if has_body && incomplete {
+                    type_error(format!("Function {} has a parameter or return value of incomplete type", name).as_str());
+                }
+            }
+            let mut defined = has_body;
+            let mut global = *storage != Some(StorageClass::Static);
+
+            if let Some(prev) = symbols.get(name) {
+                if without_param_qualifiers(&prev.sym_type) != without_param_qualifiers(fun_type) {
+                    type_error(format!("Incompatible declarations of function {}", name).as_str());
+                }
+                if let IdentAttrs::Fun(prev_defined, prev_global) = prev.attrs {
+                    if prev_defined && has_body {
+                        type_error(format!("Function {} is defined more than once", name).as_str());
+                    }
+                    if prev_global && *storage == Some(StorageClass::Static) {
+                        type_error(format!("Static function {} follows a non-static declaration", name).as_str());
+                    }
+                    defined = prev_defined || has_body;
+                    global = prev_global;
+                }
+            }
+
+            symbols.insert(name.clone(), Symbol { sym_type: fun_type.clone(), attrs: IdentAttrs::Fun(defined, global) });
+
+            let new_body = match (body, fun_type) {
+                (Some(body), Type::Function(param_types, ret_type)) => {
+                    for (param, param_type) in params.iter().zip(param_types.iter()) {
+                        symbols.insert(param.clone(), Symbol { sym_type: param_type.clone(), attrs: IdentAttrs::Local });
+                    }
+                    Some(check_block(body, symbols, ret_type, None))
+                },
+                _ => None,
+            };
+
+            FuncDecl::Function(name.clone(), params.clone(), new_body, fun_type.strip_qualifiers(), storage.clone())
+        }
+    }
+}
+
+//A const parameter is read-only inside the function body, but callers can't tell the difference
+fn without_param_qualifiers(fun_type: &Type) -> Type {
+    match fun_type {
+        Type::Function(params, ret) => Type::Function(params.iter().map(|p| p.unqualified().clone()).collect(), ret.clone()),
+        _ => fun_type.clone(),
+    }
+}
+
+fn adjust_param_types(fun_type: &Type) -> Type {
+    match fun_type {
+        Type::Function(params, ret) => {
+            let adjusted = params.iter().map(|param| match param {
+                Type::Array(elem, _) => Type::Pointer(elem.clone()),
+                _ => param.clone(),
+            }).collect();
+            Type::Function(adjusted, ret.clone())
+        },
+        _ => fun_type.clone(),
+    }
+}
+
+fn check_struct_decl(decl: &StructDecl, symbols: &mut SymbolTable, ret_type: Option<&Type>) -> StructDecl {
+    let (tag, members, is_union) = match decl {
+        StructDecl::Struct(tag, Some(members)) => (tag, members, false),
+        StructDecl::Union(tag, Some(members)) => (tag, members, true),
+        StructDecl::Struct(_, None) | StructDecl::Union(_, None) => return decl.clone(),
+    };
+    if symbols.contains_key(tag) {
+        type_error(format!("Type {} redefined", source_name(tag)).as_str());
+    }
+
+    let mut struct_members: Vec<StructMember> = Vec::new();
+    let mut size = 0;
+    let mut alignment = 1;
+    let mut new_members: Vec<MemberDecl> = Vec::new();
+    for member in members.iter() {
+        let MemberDecl::Member(name, member_type) = member;
+        let member_type = &complete_type(member_type, symbols, ret_type);
+        if struct_members.iter().any(|prev| prev.name == *name) {
+            type_error(format!("Duplicate member {} in {}", name, source_name(tag)).as_str());
+        }
+        if !is_complete(member_type, symbols) {
+            type_error(format!("Member {} of {} has an incomplete type", name, source_name(tag)).as_str());
+        }
+
+        //Union members all start at offset zero, so a union is as large as its largest member
+        let member_alignment = type_alignment(member_type, symbols);
+        let offset = if is_union { 0 } else { round_up(size, member_alignment) };
+        struct_members.push(StructMember { name: name.clone(), member_type: member_type.clone(), offset });
+        new_members.push(MemberDecl::Member(name.clone(), member_type.clone()));
+        alignment = alignment.max(member_alignment);
+        size = size.max(offset + type_size(member_type, symbols));
+    }
+
+    let sym_type = if is_union { Type::Union(tag.clone()) } else { Type::Structure(tag.clone()) };
+    let def = StructDef { alignment, size: round_up(size, alignment), members: struct_members };
+    symbols.insert(tag.clone(), Symbol { sym_type, attrs: IdentAttrs::Struct(def) });
+
+    if is_union { StructDecl::Union(tag.clone(), Some(new_members)) } else { StructDecl::Struct(tag.clone(), Some(new_members)) }
+}
+
+//Each enumerator without an explicit value is one more than the previous one
+fn check_enum_decl(decl: &EnumDecl, symbols: &mut SymbolTable, ret_type: Option<&Type>) -> EnumDecl {
+    match decl {
+        EnumDecl::Enum(tag, enumerators) => {
+            let mut next_value: i64 = 0;
+            let mut new_enumerators: Vec<Enumerator> = Vec::new();
+            for Enumerator::Enumerator(name, value) in enumerators.iter() {
+                let value = match value {
+                    Some(expr) => enum_value(expr, name, symbols, ret_type),
+                    None if next_value > i32::MAX as i64 =>
+                        type_error(format!("Value of enumerator {} is out of range for int", source_name(name)).as_str()),
+                    None => next_value as i32,
+                };
+                symbols.insert(name.clone(), Symbol { sym_type: Type::Int, attrs: IdentAttrs::Enumerator(value) });
+                next_value = value as i64 + 1;
+
+                let constant = Expr::typed(ExprKind::Constant(Const::Int(value)), Type::Int);
+                new_enumerators.push(Enumerator::Enumerator(name.clone(), Some(constant)));
+            }
+
+            EnumDecl::Enum(tag.clone(), new_enumerators)
+        }
+    }
+}
+
+fn enum_value(expr: &Expr, name: &str, symbols: &mut SymbolTable, ret_type: Option<&Type>) -> i32 {
+    let checked = check_expr(expr, symbols, ret_type);
+    match eval_integer(&checked, Some(symbols)).map(|val| integer_value(&val)) {
+        Ok(value) if value >= i32::MIN as i128 && value <= i32::MAX as i128 => value as i32,
+        Ok(_) => type_error(format!("Value of enumerator {} is out of range for int", source_name(name)).as_str()),
+        Err(msg) => type_error(format!("{} in value of enumerator {}", msg, source_name(name)).as_str()),
+    }
+}
+
+//Uses of a typedef name already carry its type, but the dimensions in it are checked where it is declared
+fn check_typedef_decl(decl: &TypedefDecl, symbols: &mut SymbolTable, ret_type: Option<&Type>) -> TypedefDecl {
+    match decl {
+        TypedefDecl::Typedef(name, target) => TypedefDecl::Typedef(name.clone(), complete_type(target, symbols, ret_type)),
+    }
+}
+
+//Array dimensions are folded once their operands have types, so sizeof can measure structures and variables
+fn complete_type(var_type: &Type, symbols: &mut SymbolTable, ret_type: Option<&Type>) -> Type {
+    match var_type {
+        Type::ArrayExpr(elem, Some(dim)) => {
+            let count = array_size(dim, symbols, ret_type);
+            Type::Array(Box::from(complete_type(elem, symbols, ret_type)), count)
+        },
+        Type::ArrayExpr(elem, None) => Type::ArrayExpr(Box::from(complete_type(elem, symbols, ret_type)), None),
+        Type::Array(elem, count) => Type::Array(Box::from(complete_type(elem, symbols, ret_type)), *count),
+        Type::Pointer(referenced) => Type::Pointer(Box::from(complete_type(referenced, symbols, ret_type))),
+        Type::Const(inner) => Type::Const(Box::from(complete_type(inner, symbols, ret_type))),
+        Type::Function(params, ret) => Type::Function(
+            params.iter().map(|param| complete_type(param, symbols, ret_type)).collect(),
+            Box::from(complete_type(ret, symbols, ret_type))),
+        _ => var_type.clone(),
+    }
+}
+
+fn array_size(dim: &Expr, symbols: &mut SymbolTable, ret_type: Option<&Type>) -> i32 {
+    let checked = check_expr(dim, symbols, ret_type);
+    match eval_integer(&checked, Some(symbols)).map(|val| integer_value(&val)) {
+        Ok(size) if size <= 0 => type_error("Array size must be greater than zero"),
+        Ok(size) if size <= i32::MAX as i128 => size as i32,
+        Ok(_) => type_error("Array size is too large"),
+        Err(msg) => type_error(format!("{} in array size", msg).as_str()),
+    }
+}
+
+fn round_up(value: i32, alignment: i32) -> i32 {
+    (value + alignment - 1) / alignment * alignment
+}
+
+pub fn struct_def(tag: &str, symbols: &SymbolTable) -> StructDef {
+    match symbols.get(tag) {
+        Some(Symbol { attrs: IdentAttrs::Struct(def), .. }) => def.clone(),
+        _ => type_error(format!("Type {} is incomplete", source_name(tag)).as_str()),
+    }
+}
+
+pub fn find_member(tag: &str, name: &str, symbols: &SymbolTable) -> StructMember {
+    match struct_def(tag, symbols).members.into_iter().find(|member| member.name == name) {
+        Some(member) => member,
+        None => type_error(format!("Type {} has no member named {}", source_name(tag), name).as_str()),
+    }
+}
+
+fn is_complete(var_type: &Type, symbols: &SymbolTable) -> bool {
+    match var_type.unqualified() {
+        Type::Void => false,
+        Type::Structure(tag) | Type::Union(tag) =>
+            matches!(symbols.get(tag), Some(Symbol { attrs: IdentAttrs::Struct(_), .. })),
+        Type::Array(elem, _) => is_complete(elem, symbols),
+        Type::ArrayExpr(_, _) => false,
+        _ => true,
+    }
+}
+
+//Structure and union layouts live in the symbol table, so sizes of aggregate types have to be looked up there
+pub fn type_size(var_type: &Type, symbols: &SymbolTable) -> i32 {
+    match var_type {
+        Type::Structure(tag) | Type::Union(tag) => struct_def(tag, symbols).size,
+        Type::Array(elem, count) => type_size(elem, symbols) * count,
+        Type::Const(inner) => type_size(inner, symbols),
+        _ => var_type.size(),
+    }
+}
+
+pub fn type_alignment(var_type: &Type, symbols: &SymbolTable) -> i32 {
+    match var_type {
+        Type::Structure(tag) | Type::Union(tag) => struct_def(tag, symbols).alignment,
+        Type::Array(elem, _) => type_alignment(elem, symbols),
+        Type::Const(inner) => type_alignment(inner, symbols),
+        _ => var_type.alignment(),
+    }
+}
+
+fn check_file_scope_var(decl: &VarDecl, symbols: &mut SymbolTable) -> VarDecl {
+    match &with_array_size(decl, symbols, None) {
+        VarDecl::Declaration(name, init, var_type, storage) => {
+            if *storage != Some(StorageClass::Extern) && !is_complete(var_type, symbols) {
+                type_error(format!("Variable {} has an incomplete type", source_name(name)).as_str());
+            }
+            let mut initial = match init {
+                Some(init) => InitialValue::Initial(static_init(name, init, var_type, symbols, None)),
+                None if *storage == Some(StorageClass::Extern) => InitialValue::NoInitializer,
+                None => InitialValue::Tentative,
+            };
+            let mut global = *storage != Some(StorageClass::Static);
+
+            let mut sym_type = var_type.clone();
+            if let Some(prev) = symbols.get(name) {
+                sym_type = match composite_type(&prev.sym_type, var_type) {
+                    Some(sym_type) => sym_type,
+                    None => type_error(format!("Conflicting types for variable {}", source_name(name)).as_str()),
+                };
+                let (prev_init, prev_global) = match &prev.attrs {
+                    IdentAttrs::Static(prev_init, prev_global) => (prev_init, *prev_global),
+                    _ => type_error(format!("Function {} redeclared as a variable", name).as_str()),
+                };
+
+                if *storage == Some(StorageClass::Extern) {
+                    global = prev_global;
+                } else if prev_global != global {
+                    type_error(format!("Conflicting linkage for variable {}", name).as_str());
+                }
+
+                match prev_init {
+                    InitialValue::Initial(_) => {
+                        if let InitialValue::Initial(_) = initial {
+                            type_error(format!("Conflicting definitions of variable {}", source_name(name)).as_str());
+                        }
+                        initial = prev_init.clone();
+                    },
+                    InitialValue::Tentative => {
+                        if initial == InitialValue::NoInitializer {
+                            initial = InitialValue::Tentative;
+                        }
+                    },
+                    InitialValue::NoInitializer => (),
+                }
+            }
+
+            let attrs = IdentAttrs::Static(initial, global);
+            symbols.insert(name.clone(), Symbol { sym_type, attrs });
+
+            VarDecl::Declaration(name.clone(), init.clone(), var_type.strip_qualifiers(), storage.clone())
+        }
+    }
+}
+
+fn check_local_var(decl: &VarDecl, symbols: &mut SymbolTable, ret_type: Option<&Type>) -> VarDecl {
+    match &with_array_size(decl, symbols, ret_type) {
+        VarDecl::Declaration(name, init, var_type, Some(StorageClass::Extern)) => {
+            if init.is_some() {
+                type_error(format!("Initializer on local extern variable {}", source_name(name)).as_str());
+            }
+            match symbols.get(name) {
+                Some(prev) => if composite_type(&prev.sym_type, var_type).is_none() {
+                    type_error(format!("Conflicting types for variable {}", source_name(name)).as_str());
+                },
+                None => {
+                    let attrs = IdentAttrs::Static(InitialValue::NoInitializer, true);
+                    symbols.insert(name.clone(), Symbol { sym_type: var_type.clone(), attrs });
+                },
+            }
+
+            VarDecl::Declaration(name.clone(), None, var_type.strip_qualifiers(), Some(StorageClass::Extern))
+        },
+        VarDecl::Declaration(name, _, var_type, _) if !is_complete(var_type, symbols) =>
+            type_error(format!("Variable {} has an incomplete type", source_name(name)).as_str()),
+        VarDecl::Declaration(name, init, var_type, Some(StorageClass::Static)) => {
+            let initial = match init {
+                Some(init) => static_init(name, init, var_type, symbols, ret_type),
+                None => zero_static_init(var_type, symbols),
+            };
+            let attrs = IdentAttrs::Static(InitialValue::Initial(initial), false);
+            symbols.insert(name.clone(), Symbol { sym_type: var_type.clone(), attrs });
+
+            VarDecl::Declaration(name.clone(), init.clone(), var_type.strip_qualifiers(), Some(StorageClass::Static))
+        },
+        VarDecl::Declaration(name, init, var_type, None) => {
+            //Nothing can give an automatic const object a value later, so it has to be initialized
+            if init.is_none() && has_const_elements(var_type) {
+                type_error(format!("Const variable {} is declared without an initializer", source_name(name)).as_str());
+            }
+            symbols.insert(name.clone(), Symbol { sym_type: var_type.clone(), attrs: IdentAttrs::Local });
+            let new_init = init.as_ref().map(|init| check_initializer(init, var_type, symbols, ret_type));
+
+            VarDecl::Declaration(name.clone(), new_init, var_type.strip_qualifiers(), None)
+        },
+        VarDecl::Declaration(_, _, _, Some(StorageClass::Typedef)) => panic!("Typedefs are never variable declarations"),
+    }
+}
+
+//An extern array declared without a size is incomplete until a later declaration gives it one, and keeps a size
+//given earlier
+fn composite_type(prev: &Type, current: &Type) -> Option<Type> {
+    match (prev, current) {
+        (Type::ArrayExpr(prev_elem, None), Type::Array(elem, _)) if prev_elem == elem => Some(current.clone()),
+        (Type::Array(prev_elem, _), Type::ArrayExpr(elem, None)) if prev_elem == elem => Some(prev.clone()),
+        _ if prev == current => Some(current.clone()),
+        _ => None,
+    }
+}
+
+//An array declared without a size takes it from the initializer: one element per item, or the string and its null
+fn with_array_size(decl: &VarDecl, symbols: &mut SymbolTable, ret_type: Option<&Type>) -> VarDecl {
+    let VarDecl::Declaration(name, init, var_type, storage) = decl;
+    match complete_type(var_type, symbols, ret_type) {
+        Type::ArrayExpr(elem, None) => {
+            let count = match init {
+                Some(Initializer::CompoundInit(inits)) => inits.len() as i32,
+                Some(Initializer::SingleInit(Expr { kind: ExprKind::String(bytes), .. })) => bytes.len() as i32 + 1,
+                Some(Initializer::SingleInit(_)) => type_error("Cannot initialize an array with a scalar"),
+                None if *storage == Some(StorageClass::Extern) =>
+                    return VarDecl::Declaration(name.clone(), None, Type::ArrayExpr(elem, None), storage.clone()),
+                None => type_error(format!("Array size missing in declaration of {}", source_name(name)).as_str()),
+            };
+            VarDecl::Declaration(name.clone(), init.clone(), Type::Array(elem, count), storage.clone())
+        },
+        var_type => VarDecl::Declaration(name.clone(), init.clone(), var_type, storage.clone()),
+    }
+}
+
+fn has_const_elements(var_type: &Type) -> bool {
+    match var_type {
+        Type::Array(elem, _) => has_const_elements(elem),
+        _ => var_type.is_const(),
+    }
+}
+
+fn check_initializer(init: &Initializer, var_type: &Type, symbols: &mut SymbolTable, ret_type: Option<&Type>)
+    -> Initializer {
+    let var_type = var_type.unqualified();
+    match (init, var_type) {
+        (Initializer::SingleInit(Expr { kind: ExprKind::String(bytes), .. }), Type::Array(elem, count)) => {
+            check_string_init(bytes, elem, *count);
+            Initializer::SingleInit(Expr::typed(ExprKind::String(bytes.clone()), var_type.strip_qualifiers()))
+        },
+        (Initializer::SingleInit(_), Type::Array(_, _)) => type_error("Cannot initialize an array with a scalar"),
+        (Initializer::SingleInit(expr), _) =>
+            Initializer::SingleInit(convert_by_assignment(check_expr(expr, symbols, ret_type), var_type)),
+        (Initializer::CompoundInit(inits), Type::Array(elem, count)) => {
+            if inits.len() > *count as usize {
+                type_error("Too many elements in array initializer");
+            }
+            let mut new_inits: Vec<Initializer> = inits.iter()
+                .map(|init| check_initializer(init, elem, symbols, ret_type))
+                .collect();
+            while new_inits.len() < *count as usize {
+                new_inits.push(zero_initializer(elem, symbols));
+            }
+            Initializer::CompoundInit(new_inits)
+        },
+        (Initializer::CompoundInit(inits), Type::Structure(tag)) => {
+            let members = struct_def(tag, symbols).members;
+            if inits.len() > members.len() {
+                type_error("Too many elements in structure initializer");
+            }
+            let mut new_inits: Vec<Initializer> = inits.iter().zip(members.iter())
+                .map(|(init, member)| check_initializer(init, &member.member_type, symbols, ret_type))
+                .collect();
+            for member in members.iter().skip(inits.len()) {
+                new_inits.push(zero_initializer(&member.member_type, symbols));
+            }
+            Initializer::CompoundInit(new_inits)
+        },
+        (Initializer::CompoundInit(inits), Type::Union(tag)) => {
+            let first = union_first_member(tag, inits.len(), symbols);
+            let new_init = match inits.first() {
+                Some(init) => check_initializer(init, &first.member_type, symbols, ret_type),
+                None => zero_initializer(&first.member_type, symbols),
+            };
+            Initializer::CompoundInit(vec![new_init])
+        },
+        (Initializer::CompoundInit(_), _) => type_error("Compound initializer for a scalar variable"),
+    }
+}
+
+fn check_string_init(bytes: &[u8], elem: &Type, count: i32) {
+    if !elem.is_character() {
+        type_error("Cannot initialize a non-character array with a string literal");
+    }
+    if bytes.len() > count as usize {
+        type_error("Too many characters in string literal initializer");
+    }
+}
+
+fn zero_initializer(var_type: &Type, symbols: &SymbolTable) -> Initializer {
+    match var_type.unqualified() {
+        Type::Array(elem, count) => Initializer::CompoundInit(vec![zero_initializer(elem, symbols); *count as usize]),
+        Type::Structure(tag) => Initializer::CompoundInit(struct_def(tag, symbols).members.iter()
+            .map(|member| zero_initializer(&member.member_type, symbols))
+            .collect()),
+        Type::Union(tag) => {
+            let first = union_first_member(tag, 0, symbols);
+            Initializer::CompoundInit(vec![zero_initializer(&first.member_type, symbols)])
+        },
+        _ => {
+            let zero = Expr::typed(ExprKind::Constant(Const::Int(0)), Type::Int);
+            Initializer::SingleInit(convert_to(zero, var_type))
+        },
+    }
+}
+
+fn static_init(name: &str, init: &Initializer, var_type: &Type, symbols: &mut SymbolTable, ret_type: Option<&Type>)
+    -> Vec<StaticInit> {
+    let var_type = var_type.unqualified();
+    match (init, var_type) {
+        (Initializer::SingleInit(Expr { kind: ExprKind::String(bytes), .. }), Type::Array(elem, count)) => {
+            check_string_init(bytes, elem, *count);
+            let padding = *count - bytes.len() as i32;
+            let mut static_inits = vec![StaticInit::StringInit(bytes.clone(), padding > 0)];
+            if padding > 1 {
+                static_inits.push(StaticInit::Zero(padding - 1));
+            }
+            static_inits
+        },
+        (Initializer::SingleInit(Expr { kind: ExprKind::String(bytes), .. }), Type::Pointer(referenced)) => {
+            if *referenced.unqualified() != Type::Char {
+                type_error("Invalid static initializer for a pointer");
+            }
+            vec![StaticInit::PointerInit(string_constant(bytes, symbols), 0)]
+        },
+        (Initializer::SingleInit(_), Type::Array(_, _)) => type_error("Cannot initialize an array with a scalar"),
+        (Initializer::SingleInit(expr), Type::Pointer(_)) => {
+            let checked = convert_by_assignment(check_expr(expr, symbols, ret_type), var_type);
+            if let Some((target, offset)) = address_constant(&checked, symbols) {
+                return vec![StaticInit::PointerInit(target, offset)];
+            }
+            match eval_constant(&checked, Some(symbols)) {
+                Ok(val) => vec![const_to_init(&val, var_type)],
+                Err(msg) => type_error(format!("{} in initializer for static variable {}", msg, source_name(name)).as_str()),
+            }
+        },
+        (Initializer::SingleInit(expr), _) => {
+            let checked = check_expr(expr, symbols, ret_type);
+            match eval_constant(&checked, Some(symbols)) {
+                Ok(val) => vec![const_to_init(&val, var_type)],
+                Err(msg) => type_error(format!("{} in initializer for static variable {}", msg, source_name(name)).as_str()),
+            }
+        },
+        (Initializer::CompoundInit(inits), Type::Array(elem, count)) => {
+            if inits.len() > *count as usize {
+                type_error("Too many elements in array initializer");
+            }
+            let mut static_inits: Vec<StaticInit> = inits.iter()
+                .flat_map(|init| static_init(name, init, elem, symbols, ret_type))
+                .collect();
+            let padding = (*count - inits.len() as i32) * type_size(elem, symbols);
+            if padding > 0 {
+                static_inits.push(StaticInit::Zero(padding));
+            }
+            static_inits
+        },
+        (Initializer::CompoundInit(inits), Type::Structure(tag)) => {
+            let def = struct_def(tag, symbols);
+            if inits.len() > def.members.len() {
+                type_error("Too many elements in structure initializer");
+            }
+            let mut static_inits: Vec<StaticInit> = Vec::new();
+            let mut current = 0;
+            for (init, member) in inits.iter().zip(def.members.iter()) {
+                if member.offset > current {
+                    static_inits.push(StaticInit::Zero(member.offset - current));
+                }
+                static_inits.extend(static_init(name, init, &member.member_type, symbols, ret_type));
+                current = member.offset + type_size(&member.member_type, symbols);
+            }
+            if def.size > current {
+                static_inits.push(StaticInit::Zero(def.size - current));
+            }
+            static_inits
+        },
+        (Initializer::CompoundInit(inits), Type::Union(tag)) => {
+            let first = union_first_member(tag, inits.len(), symbols);
+            let mut static_inits = match inits.first() {
+                Some(init) => static_init(name, init, &first.member_type, symbols, ret_type),
+                None => zero_static_init(&first.member_type, symbols),
+            };
+            let padding = type_size(var_type, symbols) - type_size(&first.member_type, symbols);
+            if padding > 0 {
+                static_inits.push(StaticInit::Zero(padding));
+            }
+            static_inits
+        },
+        (Initializer::CompoundInit(_), _) => type_error("Compound initializer for a scalar variable"),
+    }
+}
+
+//An address constant is the address of an object with static storage duration, offset by an integer constant
+fn address_constant(expr: &Expr, symbols: &mut SymbolTable) -> Option<(String, i64)> {
+    match &expr.kind {
+        ExprKind::Cast(Type::Pointer(_), inner) if is_pointer(inner) => address_constant(inner, symbols),
+        ExprKind::AddrOf(inner) => static_address(inner, symbols),
+        ExprKind::Binary(op @ (BinaryOp::Add | BinaryOp::Subtract), left, right) if is_pointer(expr) => {
+            let (ptr, index) = if is_pointer(left) { (left, right) } else { (right, left) };
+            let (target, offset) = address_constant(ptr, symbols)?;
+            let index = integer_value(&eval_integer(index, Some(symbols)).ok()?) as i64;
+            let delta = match ptr.get_type() {
+                Type::Pointer(referenced) => index * type_size(&referenced, symbols) as i64,
+                _ => unreachable!(),
+            };
+            Some((target, if *op == BinaryOp::Add { offset + delta } else { offset - delta }))
+        },
+        _ => None,
+    }
+}
+
+//Where an lvalue designating (part of) a static object lives, as a symbol and a byte offset
+fn static_address(expr: &Expr, symbols: &mut SymbolTable) -> Option<(String, i64)> {
+    match &expr.kind {
+        ExprKind::Var(name) if matches!(symbols.get(name), Some(Symbol { attrs: IdentAttrs::Static(_, _), .. })) =>
+            Some((name.clone(), 0)),
+        ExprKind::String(bytes) => Some((string_constant(bytes, symbols), 0)),
+        ExprKind::Dereference(ptr) => address_constant(ptr, symbols),
+        ExprKind::Subscript(ptr, index) => {
+            let (target, offset) = address_constant(ptr, symbols)?;
+            let index = integer_value(&eval_integer(index, Some(symbols)).ok()?) as i64;
+            Some((target, offset + index * type_size(&expr.get_type(), symbols) as i64))
+        },
+        ExprKind::Dot(inner, member) => {
+            let (target, offset) = static_address(inner, symbols)?;
+            match inner.get_type() {
+                Type::Structure(tag) | Type::Union(tag) => Some((target, offset + find_member(&tag, member, symbols).offset as i64)),
+                _ => None,
+            }
+        },
+        ExprKind::Arrow(ptr, member) => {
+            let (target, offset) = address_constant(ptr, symbols)?;
+            match ptr.get_type() {
+                Type::Pointer(referenced) => match referenced.unqualified() {
+                    Type::Structure(tag) | Type::Union(tag) =>
+                        Some((target, offset + find_member(tag, member, symbols).offset as i64)),
+                    _ => None,
+                },
+                _ => None,
+            }
+        },
+        _ => None,
+    }
+}
+
+//Only the first member of a union can be initialized
+fn union_first_member(tag: &str, init_count: usize, symbols: &SymbolTable) -> StructMember {
+    if init_count > 1 {
+        type_error("Too many elements in union initializer");
+    }
+
+    struct_def(tag, symbols).members.swap_remove(0)
+}
+
+//String literals that are not used to initialize an array become read-only constants
+pub fn string_constant(bytes: &[u8], symbols: &mut SymbolTable) -> String {
+    let name = format!(".Lstring.{}", STRING_COUNTER.fetch_add(1, Ordering::Relaxed));
+    let sym_type = Type::Array(Box::from(Type::Char), bytes.len() as i32 + 1);
+    let attrs = IdentAttrs::Constant(StaticInit::StringInit(bytes.to_vec(), true));
+    symbols.insert(name.clone(), Symbol { sym_type, attrs });
+
+    name
+}
+
+pub fn zero_static_init(var_type: &Type, symbols: &SymbolTable) -> Vec<StaticInit> {
+    match var_type.unqualified() {
+        Type::Array(_, _) | Type::Structure(_) | Type::Union(_) => vec![StaticInit::Zero(type_size(var_type, symbols))],
+        _ => vec![const_to_init(&Const::Int(0), var_type)],
+    }
+}
+
+fn const_to_init(val: &Const, var_type: &Type) -> StaticInit {
+    let var_type = var_type.unqualified();
+    let value = match val {
+        Const::Int(v) => *v as u64,
+        Const::Long(v) => *v as u64,
+        Const::UInt(v) => *v as u64,
+        Const::ULong(v) => *v,
+        Const::Double(v) if *var_type == Type::ULong => *v as u64,
+        Const::Double(v) => *v as i64 as u64,
+        Const::Char(v) => *v as u64,
+    };
+
+    //Any nonzero value converts to 1, even a double whose integer part is 0
+    if *var_type == Type::Bool {
+        let is_zero = match val {
+            Const::Double(v) => *v == 0.0,
+            _ => value == 0,
+        };
+        return StaticInit::UChar(!is_zero as u8);
+    }
+
+    if *var_type == Type::Double {
+        return StaticInit::Double(match val {
+            Const::Int(v) => *v as f64,
+            Const::Long(v) => *v as f64,
+            Const::UInt(v) => *v as f64,
+            Const::ULong(v) => *v as f64,
+            Const::Double(v) => *v,
+            Const::Char(v) => *v as f64,
+        });
+    }
+
+    match var_type {
+        Type::Char | Type::SChar => StaticInit::Char(value as i8),
+        Type::Bool | Type::UChar => StaticInit::UChar(value as u8),
+        Type::Int => StaticInit::Int(value as i32),
+        Type::Long => StaticInit::Long(value as i64),
+        Type::UInt => StaticInit::UInt(value as u32),
+        Type::ULong => StaticInit::ULong(value),
+        Type::Double => StaticInit::Double(value as f64),
+        Type::Pointer(_) if value == 0 && !matches!(val, Const::Double(_)) => StaticInit::ULong(0),
+        Type::Pointer(_) => type_error("Invalid static initializer for a pointer"),
+        Type::Array(_, _) | Type::ArrayExpr(_, _) => type_error("Cannot initialize an array with a scalar"),
+        Type::Structure(_) | Type::Union(_) => type_error("Cannot initialize a structure or union with a scalar"),
+        Type::Function(_, _) => type_error("Function type used as a static initializer"),
+        Type::Enum(_) | Type::Typedef(_) => panic!("Enumeration and typedef types are replaced during resolution"),
+        Type::Const(_) | Type::Void => unreachable!(),
+    }
+}
+
+//Assertions are checked once their operands have types, so sizeof can measure structures
+fn check_static_assert(decl: &StaticAssertDecl, symbols: &mut SymbolTable, ret_type: Option<&Type>) {
+    match decl {
+        StaticAssertDecl::StaticAssert(cond, msg, location) => {
+            let checked = check_expr(cond, symbols, ret_type);
+            let value = match eval_integer(&checked, Some(symbols)) {
+                Ok(value) => value,
+                Err(err) => type_error(format!("{}:{}: {} in static assertion", location.file, location.line, err).as_str()),
+            };
+            if integer_value(&value) == 0 {
+                match msg {
+                    Some(msg) => type_error(format!("{}:{}: Static assertion failed: {}", location.file, location.line,
+                        String::from_utf8_lossy(msg)).as_str()),
+                    None => type_error(format!("{}:{}: Static assertion failed", location.file, location.line).as_str()),
+                }
+            }
+        }
+    }
+}
+
+//Case labels found in the body of the switch statement currently being checked
+type SwitchCases = Vec<(Option<Const>, String)>;
+
+fn check_block(items: &Block, symbols: &mut SymbolTable, ret_type: &Type, mut cases: Option<&mut SwitchCases>) -> Block {
+    let mut block_items: Vec<BlockItem> = Vec::new();
+
+    match items {
+        Block::Block(items) => {
+            for item in items.iter() {
+                block_items.push(match item {
+                    BlockItem::D(Decl::Var(decl)) =>
+                        BlockItem::D(Decl::Var(check_local_var(decl, symbols, Some(ret_type)))),
+                    BlockItem::D(Decl::Func(func)) => BlockItem::D(Decl::Func(check_function(func, symbols))),
+                    BlockItem::D(Decl::Struct(decl)) => BlockItem::D(Decl::Struct(check_struct_decl(decl, symbols, Some(ret_type)))),
+                    BlockItem::D(Decl::StaticAssert(decl)) => {
+                        check_static_assert(decl, symbols, Some(ret_type));
+                        item.clone()
+                    },
+                    BlockItem::D(Decl::Typedef(decl)) =>
+                        BlockItem::D(Decl::Typedef(check_typedef_decl(decl, symbols, Some(ret_type)))),
+                    BlockItem::D(Decl::Enum(decl)) => BlockItem::D(Decl::Enum(check_enum_decl(decl, symbols, Some(ret_type)))),
+                    BlockItem::S(stmt) => BlockItem::S(check_stmt(stmt, symbols, ret_type, cases.as_deref_mut())),
+                });
+            }
+        }
+    }
+
+    Block::Block(block_items)
+}
+
+fn check_stmt(stmt: &Stmt, symbols: &mut SymbolTable, ret_type: &Type, mut cases: Option<&mut SwitchCases>) -> Stmt {
+    match stmt {
+        Stmt::Return(expr) => Stmt::Return(convert_by_assignment(check_expr(expr, symbols, Some(ret_type)), ret_type)),
+        Stmt::Expression(expr) => Stmt::Expression(check_discarded(expr, symbols, Some(ret_type))),
+        Stmt::If(cond, then, otherwise) => {
+            let new_cond = check_condition(cond, symbols, Some(ret_type));
+            let new_then = Box::from(check_stmt(then, symbols, ret_type, cases.as_deref_mut()));
+            let new_otherwise = otherwise.as_ref().map(|s| Box::from(check_stmt(s, symbols, ret_type, cases)));
+            Stmt::If(new_cond, new_then, new_otherwise)
+        },
+        Stmt::Compound(block) => Stmt::Compound(check_block(block, symbols, ret_type, cases)),
+        Stmt::While(cond, body, label) => Stmt::While(
+            check_condition(cond, symbols, Some(ret_type)),
+            Box::from(check_stmt(body, symbols, ret_type, cases)),
+            label.clone()),
+        Stmt::DoWhile(body, cond, label) => Stmt::DoWhile(
+            Box::from(check_stmt(body, symbols, ret_type, cases)),
+            check_condition(cond, symbols, Some(ret_type)),
+            label.clone()),
+        Stmt::For(init, cond, post, body, label) => {
+            let new_init = match init {
+                ForInit::InitDecl(decls) =>
+                    ForInit::InitDecl(decls.iter().map(|decl| check_local_var(decl, symbols, Some(ret_type))).collect()),
+                ForInit::InitExpr(expr) =>
+                    ForInit::InitExpr(expr.as_ref().map(|e| check_discarded(e, symbols, Some(ret_type)))),
+            };
+            let new_cond = cond.as_ref().map(|e| check_condition(e, symbols, Some(ret_type)));
+            let new_post = post.as_ref().map(|e| check_discarded(e, symbols, Some(ret_type)));
+            let new_body = Box::from(check_stmt(body, symbols, ret_type, cases));
+            Stmt::For(new_init, new_cond, new_post, new_body, label.clone())
+        },
+        Stmt::Switch(cond, body, label, _) => {
+            let mut new_cond = check_expr(cond, symbols, Some(ret_type));
+            if !new_cond.get_type().is_integer() {
+                type_error("Switch controlling expression must have an integer type");
+            }
+            if new_cond.get_type().promotes_to_int() {
+                new_cond = convert_to(new_cond, &Type::Int);
+            }
+
+            let mut switch_cases: SwitchCases = Vec::new();
+            let new_body = Box::from(check_stmt(body, symbols, ret_type, Some(&mut switch_cases)));
+
+            let cond_type = new_cond.get_type();
+            let mut new_cases: SwitchCases = Vec::new();
+            for (value, case_label) in switch_cases.into_iter() {
+                let new_value = value.as_ref().map(|value| convert_const(value, &cond_type));
+                if new_value.is_some() && new_cases.iter().any(|(prev, _)| *prev == new_value) {
+                    type_error("Duplicate case value in switch");
+                }
+                new_cases.push((new_value, case_label));
+            }
+
+            Stmt::Switch(new_cond, new_body, label.clone(), new_cases)
+        },
+        //Case values have to be integer constant expressions
+        Stmt::Case(value, body, label) => {
+            let new_value = check_expr(value, symbols, Some(ret_type));
+            let case_value = match eval_integer(&new_value, Some(symbols)) {
+                Ok(val) => val,
+                Err(msg) => type_error(format!("{} in case value", msg).as_str()),
+            };
+            match cases.as_deref_mut() {
+                Some(switch_cases) => switch_cases.push((Some(case_value), label.clone())),
+                None => type_error("case label outside of a switch"),
+            }
+            Stmt::Case(new_value, Box::from(check_stmt(body, symbols, ret_type, cases)), label.clone())
+        },
+        Stmt::Default(body, label) => {
+            match cases.as_deref_mut() {
+                Some(switch_cases) if switch_cases.iter().any(|(value, _)| value.is_none()) =>
+                    type_error("Multiple default labels in one switch"),
+                Some(switch_cases) => switch_cases.push((None, label.clone())),
+                None => type_error("default label outside of a switch"),
+            }
+            Stmt::Default(Box::from(check_stmt(body, symbols, ret_type, cases)), label.clone())
+        },
+        Stmt::Labeled(label, body) => Stmt::Labeled(label.clone(), Box::from(check_stmt(body, symbols, ret_type, cases))),
+        Stmt::Break(_) | Stmt::Continue(_) | Stmt::Goto(_) | Stmt::Null => stmt.clone(),
+    }
+}
+
+//Case values are converted to the type of the controlling expression before they are compared
+fn convert_const(val: &Const, target: &Type) -> Const {
+    match const_to_init(val, target) {
+        StaticInit::Int(v) => Const::Int(v),
+        StaticInit::Long(v) => Const::Long(v),
+        StaticInit::UInt(v) => Const::UInt(v),
+        StaticInit::ULong(v) => Const::ULong(v),
+        _ => unreachable!(),
+    }
+}
+
+fn check_condition(expr: &Expr, symbols: &mut SymbolTable, ret_type: Option<&Type>) -> Expr {
+    let checked = check_expr(expr, symbols, ret_type);
+    if !checked.get_type().is_scalar() {
+        type_error("Controlling expression must have a scalar type");
+    }
+
+    checked
+}
+
+fn check_expr(expr: &Expr, symbols: &mut SymbolTable, ret_type: Option<&Type>) -> Expr {
+    let checked = check_discarded(expr, symbols, ret_type);
+    if checked.get_type() == Type::Void {
+        type_error("Expression without a value used as a value");
+    }
+
+    checked
+}
+
+//Expression statements, the clauses of a for loop and the left of a comma throw their value away, so they may have none
+fn check_discarded(expr: &Expr, symbols: &mut SymbolTable, ret_type: Option<&Type>) -> Expr {
+    let checked = check_expr_without_decay(expr, symbols, ret_type);
+
+    match checked.get_type() {
+        Type::Array(elem, _) | Type::ArrayExpr(elem, None) =>
+            Expr::typed(ExprKind::AddrOf(Box::from(checked)), Type::Pointer(elem)),
+        Type::Structure(tag) | Type::Union(tag) if !is_complete(&checked.get_type(), symbols) =>
+            type_error(format!("Type {} is incomplete", source_name(&tag)).as_str()),
+        _ => checked,
+    }
+}
+
+fn check_expr_without_decay(expr: &Expr, symbols: &mut SymbolTable, ret_type: Option<&Type>) -> Expr {
+    match &expr.kind {
+        ExprKind::Constant(val) => {
+            let const_type = match val {
+                Const::Int(_) => Type::Int,
+                Const::Long(_) => Type::Long,
+                Const::UInt(_) => Type::UInt,
+                Const::ULong(_) => Type::ULong,
+                Const::Double(_) => Type::Double,
+                Const::Char(_) => Type::Char,
+            };
+            Expr::typed(expr.kind.clone(), const_type)
+        },
+        ExprKind::String(bytes) => {
+            let string_type = Type::Array(Box::from(Type::Char), bytes.len() as i32 + 1);
+            Expr::typed(expr.kind.clone(), string_type)
+        },
+        ExprKind::Var(name) => {
+            let var_type = match symbols.get(name) {
+                //Enumerators are constants of type int
+                Some(Symbol { attrs: IdentAttrs::Enumerator(value), .. }) =>
+                    return Expr::typed(ExprKind::Constant(Const::Int(*value)), Type::Int),
+                Some(Symbol { sym_type: Type::Function(_, _), .. }) =>
+                    type_error(format!("Function {} used as a variable", name).as_str()),
+                Some(symbol) => symbol.sym_type.unqualified().clone(),
+                None => type_error(format!("{} is an undeclared variable", name).as_str()),
+            };
+            Expr::typed(expr.kind.clone(), var_type)
+        },
+        ExprKind::Cast(target, inner) => {
+            let target = complete_type(target, symbols, ret_type);
+            let target = target.unqualified();
+            let new_inner = check_expr(inner, symbols, ret_type);
+            match (target, new_inner.get_type()) {
+                (Type::Array(_, _), _) => type_error("Cannot cast to an array type"),
+                (target, inner_type) if !target.is_scalar() || !inner_type.is_scalar() =>
+                    type_error("Cast requires scalar operands"),
+                (Type::Double, Type::Pointer(_)) | (Type::Pointer(_), Type::Double) =>
+                    type_error("Cannot cast between a pointer and a double"),
+                _ => (),
+            }
+            Expr::typed(ExprKind::Cast(target.clone(), Box::from(new_inner)), target.clone())
+        },
+        ExprKind::SizeOf(inner) => {
+            let new_inner = check_expr_without_decay(inner, symbols, ret_type);
+            check_measurable(&new_inner.get_type(), symbols);
+            Expr::typed(ExprKind::SizeOf(Box::from(new_inner)), Type::ULong)
+        },
+        ExprKind::SizeOfType(target) => {
+            let target = complete_type(target, symbols, ret_type);
+            check_measurable(&target, symbols);
+            Expr::typed(ExprKind::SizeOfType(target), Type::ULong)
+        },
+        ExprKind::AlignOf(target) => {
+            let target = complete_type(target, symbols, ret_type);
+            check_measurable(&target, symbols);
+            Expr::typed(ExprKind::AlignOf(target), Type::ULong)
+        },
+        ExprKind::Unary(op, inner) => {
+            let mut new_inner = check_expr(inner, symbols, ret_type);
+            if !new_inner.get_type().is_scalar() {
+                type_error("Invalid operand to unary operator");
+            }
+            if let UnaryOp::Complement | UnaryOp::Negate = op && new_inner.get_type().promotes_to_int() {
+                new_inner = convert_to(new_inner, &Type::Int);
+            }
+            if let UnaryOp::Complement = op && new_inner.get_type() == Type::Double {
+                type_error("Bitwise complement applied to a double");
+            }
+            if let UnaryOp::Complement | UnaryOp::Negate = op && !new_inner.get_type().is_arithmetic() {
+                type_error("Invalid operand to unary operator");
+            }
+            let unary_type = match op {
+                UnaryOp::Not => Type::Int,
+                _ => new_inner.get_type(),
+            };
+            Expr::typed(ExprKind::Unary(op.clone(), Box::from(new_inner)), unary_type)
+        },
+        ExprKind::Binary(op, left, right) => check_binary(op, left, right, symbols, ret_type),
+        ExprKind::Assignment(left, right) => {
+            let new_left = check_expr_without_decay(left, symbols, ret_type);
+            let left_type = new_left.get_type();
+            if let Type::Array(_, _) = left_type {
+                type_error("Cannot assign to an array");
+            }
+            check_modifiable(&new_left, symbols);
+            let new_right = convert_by_assignment(check_expr(right, symbols, ret_type), &left_type);
+            Expr::typed(ExprKind::Assignment(Box::from(new_left), Box::from(new_right)), left_type)
+        },
+        ExprKind::CompoundAssignment(op, left, right) => check_compound_assignment(op, left, right, symbols, ret_type),
+        ExprKind::Postfix(op, inner) => {
+            let new_inner = check_expr_without_decay(inner, symbols, ret_type);
+            let inner_type = new_inner.get_type();
+            if !inner_type.is_scalar() {
+                type_error("Invalid operand to increment or decrement");
+            }
+            check_modifiable(&new_inner, symbols);
+            Expr::typed(ExprKind::Postfix(op.clone(), Box::from(new_inner)), inner_type)
+        },
+        ExprKind::Conditional(cond, then, otherwise) => {
+            let new_cond = check_condition(cond, symbols, ret_type);
+            let new_then = check_discarded(then, symbols, ret_type);
+            let new_otherwise = check_discarded(otherwise, symbols, ret_type);
+            let (then_type, otherwise_type) = (new_then.get_type(), new_otherwise.get_type());
+            let common = if !then_type.is_scalar() || !otherwise_type.is_scalar() {
+                if then_type != otherwise_type {
+                    type_error("Incompatible types in conditional expression");
+                }
+                then_type
+            } else if is_pointer(&new_then) || is_pointer(&new_otherwise) {
+                common_pointer_type(&new_then, &new_otherwise)
+            } else {
+                common_type(&new_then.get_type(), &new_otherwise.get_type())
+            };
+            let kind = ExprKind::Conditional(
+                Box::from(new_cond),
+                Box::from(convert_to(new_then, &common)),
+                Box::from(convert_to(new_otherwise, &common)));
+            Expr::typed(kind, common)
+        },
+        //The left operand is only evaluated for its side effects, and the result is never an lvalue
+        ExprKind::Comma(left, right) => {
+            let new_left = check_discarded(left, symbols, ret_type);
+            let new_right = check_discarded(right, symbols, ret_type);
+            let comma_type = new_right.get_type();
+            Expr::typed(ExprKind::Comma(Box::from(new_left), Box::from(new_right)), comma_type)
+        },
+        ExprKind::FunctionCall(name, args) => {
+            let (param_types, result_type) = match symbols.get(name) {
+                Some(Symbol { sym_type: Type::Function(params, ret), .. }) => (params.clone(), ret.unqualified().clone()),
+                _ => type_error(format!("Variable {} used as a function", source_name(name)).as_str()),
+            };
+            if param_types.len() != args.len() {
+                type_error(format!("Function {} called with the wrong number of arguments", name).as_str());
+            }
+
+            let new_args = args.iter().zip(param_types.iter())
+                .map(|(arg, param_type)| convert_by_assignment(check_expr(arg, symbols, ret_type), param_type))
+                .collect();
+            Expr::typed(ExprKind::FunctionCall(name.clone(), new_args), result_type)
+        },
+        ExprKind::Dereference(inner) => {
+            let new_inner = check_expr(inner, symbols, ret_type);
+            match new_inner.get_type() {
+                Type::Pointer(referenced) =>
+                    Expr::typed(ExprKind::Dereference(Box::from(new_inner)), referenced.unqualified().clone()),
+                _ => type_error("Cannot dereference a non-pointer"),
+            }
+        },
+        ExprKind::AddrOf(inner) => {
+            let new_inner = check_expr_without_decay(inner, symbols, ret_type);
+            let referenced = new_inner.get_type();
+            let referenced = if is_const_lvalue(&new_inner, symbols) { referenced.make_const() } else { referenced };
+            let ptr_type = Type::Pointer(Box::from(referenced));
+            Expr::typed(ExprKind::AddrOf(Box::from(new_inner)), ptr_type)
+        },
+        ExprKind::Subscript(array, index) => {
+            let new_array = check_expr(array, symbols, ret_type);
+            let new_index = check_expr(index, symbols, ret_type);
+            let (ptr, int) = match (new_array.get_type(), new_index.get_type()) {
+                (Type::Pointer(_), int_type) if int_type.is_integer() => (new_array, new_index),
+                (int_type, Type::Pointer(_)) if int_type.is_integer() => (new_index, new_array),
+                _ => type_error("Subscript requires a pointer and an integer operand"),
+            };
+            let elem_type = match ptr.get_type() {
+                Type::Pointer(referenced) => referenced.unqualified().clone(),
+                _ => unreachable!(),
+            };
+            let kind = ExprKind::Subscript(Box::from(ptr), Box::from(convert_to(int, &Type::Long)));
+            Expr::typed(kind, elem_type)
+        },
+        ExprKind::Dot(inner, member) => {
+            let new_inner = check_expr_without_decay(inner, symbols, ret_type);
+            let member_type = match new_inner.get_type() {
+                Type::Structure(tag) | Type::Union(tag) => find_member(&tag, member, symbols).member_type.unqualified().clone(),
+                _ => type_error(format!("Member {} accessed on a non-structure", member).as_str()),
+            };
+            Expr::typed(ExprKind::Dot(Box::from(new_inner), member.clone()), member_type)
+        },
+        ExprKind::Arrow(inner, member) => {
+            let new_inner = check_expr(inner, symbols, ret_type);
+            let member_type = match new_inner.get_type() {
+                Type::Pointer(referenced) => match referenced.unqualified() {
+                    Type::Structure(tag) | Type::Union(tag) => find_member(tag, member, symbols).member_type.unqualified().clone(),
+                    _ => type_error(format!("Member {} accessed through a non-structure pointer", member).as_str()),
+                },
+                _ => type_error(format!("Member {} accessed through a non-pointer", member).as_str()),
+            };
+            Expr::typed(ExprKind::Arrow(Box::from(new_inner), member.clone()), member_type)
+        },
+        //Return statements inside a statement expression return from the enclosing function, but case labels can't jump in
+        ExprKind::StmtExpr(block) => {
+            let new_block = match ret_type {
+                Some(ret_type) => check_block(block, symbols, ret_type, None),
+                None => type_error("Statement expression outside of a function"),
+            };
+            let Block::Block(items) = &new_block;
+            let stmt_type = match items.last() {
+                Some(BlockItem::S(last)) => value_type(last),
+                _ => Type::Void,
+            };
+            Expr::typed(ExprKind::StmtExpr(new_block), stmt_type)
+        },
+        ExprKind::Builtin(builtin, args) => check_builtin(builtin, args, symbols, ret_type),
+    }
+}
+
+fn value_type(stmt: &Stmt) -> Type {
+    match stmt {
+        Stmt::Expression(expr) => expr.get_type(),
+        Stmt::Labeled(_, body) => value_type(body),
+        _ => Type::Void,
+    }
+}
+
+//__builtin_expect(value, expected) is value converted to long, and __builtin_constant_p is folded to 0 or 1 here.
+//__builtin_trap and __builtin_unreachable don't return and have no value
+fn check_builtin(builtin: &Builtin, args: &[Expr], symbols: &mut SymbolTable, ret_type: Option<&Type>) -> Expr {
+    match builtin {
+        Builtin::Expect => {
+            let new_args = args.iter()
+                .map(|arg| convert_by_assignment(check_expr(arg, symbols, ret_type), &Type::Long))
+                .collect();
+            Expr::typed(ExprKind::Builtin(builtin.clone(), new_args), Type::Long)
+        },
+        Builtin::ConstantP => {
+            let checked = check_expr(&args[0], symbols, ret_type);
+            let is_constant = eval_constant(&checked, Some(symbols)).is_ok();
+            Expr::typed(ExprKind::Constant(Const::Int(is_constant as i32)), Type::Int)
+        },
+        Builtin::Trap | Builtin::Unreachable => Expr::typed(ExprKind::Builtin(builtin.clone(), Vec::new()), Type::Void),
+    }
+}
+
+//sizeof and _Alignof need a complete object type
+fn check_measurable(target: &Type, symbols: &SymbolTable) {
+    if let Type::Function(_, _) = target {
+        type_error("Cannot take the size of a function type");
+    }
+    if !is_complete(target, symbols) {
+        type_error("Cannot take the size of an incomplete type");
+    }
+}
+
+//Expression types drop top level qualifiers, so whether an lvalue is const depends on where it was found
+fn is_const_lvalue(expr: &Expr, symbols: &SymbolTable) -> bool {
+    match &expr.kind {
+        ExprKind::Var(name) => symbols.get(name).is_some_and(|symbol| symbol.sym_type.is_const()),
+        ExprKind::Dereference(inner) => points_to_const(inner),
+        ExprKind::Subscript(left, right) => points_to_const(left) || points_to_const(right),
+        ExprKind::Dot(inner, member) => is_const_lvalue(inner, symbols) || match inner.get_type() {
+            Type::Structure(tag) | Type::Union(tag) => find_member(&tag, member, symbols).member_type.is_const(),
+            _ => false,
+        },
+        ExprKind::Arrow(inner, member) => points_to_const(inner) || match inner.get_type() {
+            Type::Pointer(referenced) => match referenced.unqualified() {
+                Type::Structure(tag) | Type::Union(tag) => find_member(tag, member, symbols).member_type.is_const(),
+                _ => false,
+            },
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn points_to_const(expr: &Expr) -> bool {
+    matches!(expr.get_type(), Type::Pointer(referenced) if referenced.is_const())
+}
+
+fn check_modifiable(lvalue: &Expr, symbols: &SymbolTable) {
+    if is_const_lvalue(lvalue, symbols) {
+        match &lvalue.kind {
+            ExprKind::Var(name) => type_error(format!("Cannot assign to const variable {}", source_name(name)).as_str()),
+            _ => type_error("Cannot assign to a const-qualified lvalue"),
+        }
+    }
+    if has_const_member(&lvalue.get_type(), symbols) {
+        type_error("Cannot assign to a structure or union with a const member");
+    }
+}
+
+//Assigning a whole structure or union would also write any const member, however deeply it is nested
+fn has_const_member(var_type: &Type, symbols: &SymbolTable) -> bool {
+    match var_type.unqualified() {
+        Type::Structure(tag) | Type::Union(tag) => struct_def(tag, symbols).members.iter()
+            .any(|member| has_const_elements(&member.member_type) || has_const_member(&member.member_type, symbols)),
+        Type::Array(elem, _) => has_const_member(elem, symbols),
+        _ => false,
+    }
+}
+
+fn check_binary(op: &BinaryOp, left: &Expr, right: &Expr, symbols: &mut SymbolTable, ret_type: Option<&Type>) -> Expr {
+    let new_left = check_expr(left, symbols, ret_type);
+    let new_right = check_expr(right, symbols, ret_type);
+
+    if !new_left.get_type().is_scalar() || !new_right.get_type().is_scalar() {
+        type_error("Invalid operands to binary operator");
+    }
+
+    if let BinaryOp::And | BinaryOp::Or = op {
+        return Expr::typed(ExprKind::Binary(op.clone(), Box::from(new_left), Box::from(new_right)), Type::Int);
+    }
+
+    if let BinaryOp::ShiftLeft | BinaryOp::ShiftRight = op {
+        return check_shift(op, new_left, new_right);
+    }
+
+    let has_pointer = is_pointer(&new_left) || is_pointer(&new_right);
+    if let BinaryOp::Add | BinaryOp::Subtract = op && has_pointer {
+        return check_pointer_arithmetic(op, new_left, new_right);
+    }
+
+    let common = match op {
+        BinaryOp::Equal | BinaryOp::NotEqual if has_pointer => common_pointer_type(&new_left, &new_right),
+        BinaryOp::LessThan | BinaryOp::LessEqual | BinaryOp::GreatThan | BinaryOp::GreatEqual if has_pointer => {
+            common_pointer_type(&new_left, &new_right)
+        },
+        _ if has_pointer => type_error("Invalid operands to arithmetic operator"),
+        _ => common_type(&new_left.get_type(), &new_right.get_type()),
+    };
+    if let BinaryOp::Remainder = op && common == Type::Double {
+        type_error("Remainder operator applied to a double");
+    }
+    if is_bitwise(op) && common == Type::Double {
+        type_error("Bitwise operator applied to a double");
+    }
+    let kind = ExprKind::Binary(
+        op.clone(),
+        Box::from(convert_to(new_left, &common)),
+        Box::from(convert_to(new_right, &common)));
+
+    match op {
+        BinaryOp::Add | BinaryOp::Subtract | BinaryOp::Multiply | BinaryOp::Divide | BinaryOp::Remainder
+            | BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor => Expr::typed(kind, common),
+        _ => Expr::typed(kind, Type::Int),
+    }
+}
+
+//The result of a shift has the promoted type of its left operand, and the count is converted to match it
+fn check_shift(op: &BinaryOp, left: Expr, right: Expr) -> Expr {
+    if !left.get_type().is_integer() || !right.get_type().is_integer() {
+        type_error("Shift operands must be integers");
+    }
+
+    let left = if left.get_type().promotes_to_int() { convert_to(left, &Type::Int) } else { left };
+    let left_type = left.get_type();
+    let kind = ExprKind::Binary(op.clone(), Box::from(left), Box::from(convert_to(right, &left_type)));
+    Expr::typed(kind, left_type)
+}
+
+fn is_bitwise(op: &BinaryOp) -> bool {
+    matches!(op, BinaryOp::BitAnd | BinaryOp::BitOr | BinaryOp::BitXor | BinaryOp::ShiftLeft | BinaryOp::ShiftRight)
+}
+
+//The operation is carried out in the type of the converted right operand, then converted back to the left type
+fn check_compound_assignment(op: &BinaryOp, left: &Expr, right: &Expr, symbols: &mut SymbolTable, ret_type: Option<&Type>)
+    -> Expr {
+    let new_left = check_expr_without_decay(left, symbols, ret_type);
+    let left_type = new_left.get_type();
+    if let Type::Array(_, _) = left_type {
+        type_error("Cannot assign to an array");
+    }
+    check_modifiable(&new_left, symbols);
+    let new_right = check_expr(right, symbols, ret_type);
+    let right_type = new_right.get_type();
+    if !left_type.is_scalar() || !right_type.is_scalar() {
+        type_error("Invalid operands to compound assignment");
+    }
+
+    let new_right = match op {
+        BinaryOp::Add | BinaryOp::Subtract if is_pointer(&new_left) => {
+            if !right_type.is_integer() {
+                type_error("Invalid operands to pointer arithmetic");
+            }
+            convert_to(new_right, &Type::Long)
+        },
+        _ if is_pointer(&new_left) || is_pointer(&new_right) => type_error("Invalid operands to compound assignment"),
+        _ if is_bitwise(op) && (!left_type.is_integer() || !right_type.is_integer()) =>
+            type_error("Bitwise operator applied to a double"),
+        BinaryOp::ShiftLeft | BinaryOp::ShiftRight if left_type.promotes_to_int() => convert_to(new_right, &Type::Int),
+        BinaryOp::ShiftLeft | BinaryOp::ShiftRight => convert_to(new_right, &left_type),
+        _ => {
+            let common = common_type(&left_type, &right_type);
+            if let BinaryOp::Remainder = op && common == Type::Double {
+                type_error("Remainder operator applied to a double");
+            }
+            convert_to(new_right, &common)
+        },
+    };
+
+    Expr::typed(ExprKind::CompoundAssignment(op.clone(), Box::from(new_left), Box::from(new_right)), left_type)
+}
+
+fn check_pointer_arithmetic(op: &BinaryOp, left: Expr, right: Expr) -> Expr {
+    let (left_type, right_type) = (left.get_type(), right.get_type());
+
+    match op {
+        BinaryOp::Add if is_pointer(&left) && right_type.is_integer() => {
+            let kind = ExprKind::Binary(op.clone(), Box::from(left), Box::from(convert_to(right, &Type::Long)));
+            Expr::typed(kind, left_type)
+        },
+        BinaryOp::Add if left_type.is_integer() && is_pointer(&right) => {
+            let kind = ExprKind::Binary(op.clone(), Box::from(convert_to(left, &Type::Long)), Box::from(right));
+            Expr::typed(kind, right_type)
+        },
+        BinaryOp::Subtract if is_pointer(&left) && right_type.is_integer() => {
+            let kind = ExprKind::Binary(op.clone(), Box::from(left), Box::from(convert_to(right, &Type::Long)));
+            Expr::typed(kind, left_type)
+        },
+        BinaryOp::Subtract if same_referenced_type(&left_type, &right_type) => {
+            Expr::typed(ExprKind::Binary(op.clone(), Box::from(left), Box::from(right)), Type::Long)
+        },
+        _ => type_error("Invalid operands to pointer arithmetic"),
+    }
+}
+
+pub fn common_type(first: &Type, second: &Type) -> Type {
+    let first = if first.promotes_to_int() { &Type::Int } else { first };
+    let second = if second.promotes_to_int() { &Type::Int } else { second };
+
+    if first == second {
+        first.clone()
+    } else if *first == Type::Double || *second == Type::Double {
+        Type::Double
+    } else if first.size() == second.size() {
+        if first.is_signed() { second.clone() } else { first.clone() }
+    } else if first.size() > second.size() {
+        first.clone()
+    } else {
+        second.clone()
+    }
+}
+
+fn is_pointer(expr: &Expr) -> bool {
+    matches!(expr.get_type(), Type::Pointer(_))
+}
+
+//Any integer constant expression with the value 0, such as (0) or 1 - 1
+fn is_null_pointer_constant(expr: &Expr) -> bool {
+    expr.get_type().is_integer() && matches!(eval_integer(expr, None), Ok(val) if integer_value(&val) == 0)
+}
+
+//Pointers to differently qualified versions of a type are compatible
+fn same_referenced_type(first: &Type, second: &Type) -> bool {
+    matches!((first, second), (Type::Pointer(a), Type::Pointer(b)) if a.unqualified() == b.unqualified())
+}
+
+//The common type points to a type with the qualifiers of both
+fn common_pointer_type(first: &Expr, second: &Expr) -> Type {
+    let (first_type, second_type) = (first.get_type(), second.get_type());
+
+    if first_type == second_type || is_null_pointer_constant(second) {
+        first_type
+    } else if is_null_pointer_constant(first) {
+        second_type
+    } else if same_referenced_type(&first_type, &second_type) {
+        match first_type {
+            Type::Pointer(referenced) => Type::Pointer(Box::from(referenced.make_const())),
+            _ => unreachable!(),
+        }
+    } else {
+        type_error("Expressions have incompatible pointer types")
+    }
+}
+
+fn convert_by_assignment(expr: Expr, target: &Type) -> Expr {
+    let expr_type = expr.get_type();
+    let target = target.unqualified();
+
+    if expr_type == *target {
+        expr
+    } else if same_referenced_type(&expr_type, target) {
+        //Qualifiers can be added to the referenced type, but not taken away
+        if let (Type::Pointer(from), Type::Pointer(to)) = (&expr_type, target) && from.is_const() && !to.is_const() {
+            type_error("Conversion discards const qualifier from pointer target type");
+        }
+        convert_to(expr, target)
+    } else if (expr_type.is_arithmetic() && target.is_arithmetic())
+        || (matches!(target, Type::Pointer(_)) && is_null_pointer_constant(&expr))
+        || (*target == Type::Bool && matches!(expr_type, Type::Pointer(_))) {
+        convert_to(expr, target)
+    } else {
+        type_error("Cannot convert type for assignment")
+    }
+}
+
+fn convert_to(expr: Expr, target: &Type) -> Expr {
+    let target = target.unqualified();
+    if expr.get_type() == *target {
+        expr
+    } else {
+        Expr::typed(ExprKind::Cast(target.clone(), Box::from(expr)), target.clone())
+    }
+}
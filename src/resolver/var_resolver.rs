@@ -1,27 +1,122 @@
 
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use crate::parser::ast::*;
 use crate::utilities::error_handler::resolver_error;
 
-pub fn resolve_vars(ast: &mut FuncDef) {
-    let mut var_map: HashMap<String, (String, u32)> = HashMap::new();
+static NAME_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+//Enumerators and typedef names share the ordinary namespace with variables and functions.
+//An enumerator's value is only known to the type checker, since it can measure a structure
+#[derive(Clone)]
+enum IdentKind {
+    Var,
+    Enumerator,
+    Typedef(Type),
+}
+
+#[derive(Clone)]
+struct VarEntry {
+    name: String,
+    scope: u32,
+    has_linkage: bool,
+    kind: IdentKind,
+}
+
+type VarMap = HashMap<String, VarEntry>;
+
+#[derive(Clone)]
+#[derive(PartialEq)]
+enum TagKind {
+    Struct,
+    Union,
+    Enum,
+}
+
+//Structure, union and enumeration tags share their own namespace, scoped the same way as variables
+#[derive(Clone)]
+struct TagEntry {
+    name: String,
+    scope: u32,
+    kind: TagKind,
+}
+
+type TagMap = HashMap<String, TagEntry>;
+
+pub fn resolve_vars(ast: &mut Program) {
+    let mut var_map: VarMap = HashMap::new();
+    let mut tag_map: TagMap = HashMap::new();
 
     match ast {
-        FuncDef::Function(_, items) => {
-            *items = resolve_block(items, &mut var_map, 0);
+        Program::Program(decls) => {
+            for decl in decls.iter_mut() {
+                *decl = match decl {
+                    Decl::Func(func) => Decl::Func(resolve_function(func, &mut var_map, &mut tag_map, 0)),
+                    Decl::Var(var) => Decl::Var(resolve_file_scope_decl(var, &mut var_map, &mut tag_map)),
+                    Decl::Struct(decl) => Decl::Struct(resolve_struct_decl(decl, &var_map, &mut tag_map, 0)),
+                    Decl::Enum(decl) => Decl::Enum(resolve_enum_decl(decl, &mut var_map, &mut tag_map, 0)),
+                    Decl::Typedef(decl) => Decl::Typedef(resolve_typedef_decl(decl, &mut var_map, &mut tag_map, 0)),
+                    Decl::StaticAssert(decl) => Decl::StaticAssert(resolve_static_assert(decl, &mut var_map, &mut tag_map, 0)),
+                };
+            }
         }
     }
 }
 
-fn resolve_block(items: &Block, var_map: &mut HashMap<String, (String, u32)>, scope: u32) -> Block {
-    let mut block_items: Vec<BlockItem> = Vec::new();
+fn resolve_file_scope_decl(decl: &VarDecl, var_map: &mut VarMap, tag_map: &mut TagMap) -> VarDecl {
+    match decl {
+        VarDecl::Declaration(name, init, var_type, storage) => {
+            if let Some(VarEntry { kind: IdentKind::Enumerator | IdentKind::Typedef(_), .. }) = var_map.get(name) {
+                resolver_error(format!("{} redeclared as a different kind of symbol", name).as_str());
+            }
+            var_map.insert(name.clone(), VarEntry { name: name.clone(), scope: 0, has_linkage: true, kind: IdentKind::Var });
+
+            //Initializers can name enumerators, which the type checker turns into constants
+            let new_init = init.as_ref().map(|init| resolve_initializer(init, var_map, tag_map, 0));
+
+            VarDecl::Declaration(name.clone(), new_init, resolve_var_type(var_type, var_map, tag_map, 0), storage.clone())
+        }
+    }
+}
+
+fn resolve_function(func: &FuncDecl, var_map: &mut VarMap, tag_map: &mut TagMap, scope: u32) -> FuncDecl {
+    match func {
+        FuncDecl::Function(name, params, body, fun_type, storage) => {
+            if let Some(prev) = var_map.get(name) && prev.scope == scope && !prev.has_linkage {
+                resolver_error(format!("{} is a duplicate declaration", name).as_str());
+            }
+            var_map.insert(name.clone(), VarEntry { name: name.clone(), scope, has_linkage: true, kind: IdentKind::Var });
+
+            let new_type = resolve_type(fun_type, var_map, tag_map, scope);
+            let mut scoped_var_map = var_map.clone();
+            let mut scoped_tag_map = tag_map.clone();
+            let mut new_params: Vec<String> = Vec::new();
+            for param in params.iter() {
+                new_params.push(declare_var(param, &mut scoped_var_map, scope + 1));
+            }
+
+            let new_body = body.as_ref().map(|b| resolve_items(b, &mut scoped_var_map, &mut scoped_tag_map, scope + 1));
+
+            FuncDecl::Function(name.clone(), new_params, new_body, new_type, storage.clone())
+        }
+    }
+}
+
+fn resolve_block(items: &Block, var_map: &mut VarMap, tag_map: &mut TagMap, scope: u32) -> Block {
     let mut scoped_var_map = var_map.clone();
+    let mut scoped_tag_map = tag_map.clone();
+
+    resolve_items(items, &mut scoped_var_map, &mut scoped_tag_map, scope + 1)
+}
+
+fn resolve_items(items: &Block, var_map: &mut VarMap, tag_map: &mut TagMap, scope: u32) -> Block {
+    let mut block_items: Vec<BlockItem> = Vec::new();
 
     match items {
         Block::Block(items) => {
             for item in items.iter() {
-                block_items.push(resolve_item(item, &mut scoped_var_map, scope + 1));
+                block_items.push(resolve_item(item, var_map, tag_map, scope));
             }
         }
     }
@@ -29,92 +124,390 @@ fn resolve_block(items: &Block, var_map: &mut HashMap<String, (String, u32)>, sc
     Block::Block(block_items)
 }
 
-fn resolve_item(item: &BlockItem, var_map: &mut HashMap<String, (String, u32)>, scope: u32) -> BlockItem {
+fn resolve_item(item: &BlockItem, var_map: &mut VarMap, tag_map: &mut TagMap, scope: u32) -> BlockItem {
     match item {
-        BlockItem::D(decl) => BlockItem::D(resolve_decl(decl, var_map, scope)),
-        BlockItem::S(stmt) => BlockItem::S(resolve_stmt(stmt, var_map, scope)),
+        BlockItem::D(Decl::Var(decl)) => BlockItem::D(Decl::Var(resolve_decl(decl, var_map, tag_map, scope))),
+        BlockItem::D(Decl::Func(func)) => {
+            match func {
+                FuncDecl::Function(name, _, Some(_), _, _) =>
+                    resolver_error(format!("{} is a nested function definition", name).as_str()),
+                FuncDecl::Function(name, _, _, _, Some(StorageClass::Static)) =>
+                    resolver_error(format!("{} is a static function declared at block scope", name).as_str()),
+                _ => (),
+            }
+            BlockItem::D(Decl::Func(resolve_function(func, var_map, tag_map, scope)))
+        },
+        BlockItem::D(Decl::Struct(decl)) => BlockItem::D(Decl::Struct(resolve_struct_decl(decl, var_map, tag_map, scope))),
+        BlockItem::D(Decl::Enum(decl)) => BlockItem::D(Decl::Enum(resolve_enum_decl(decl, var_map, tag_map, scope))),
+        BlockItem::D(Decl::Typedef(decl)) => BlockItem::D(Decl::Typedef(resolve_typedef_decl(decl, var_map, tag_map, scope))),
+        BlockItem::D(Decl::StaticAssert(decl)) => BlockItem::D(Decl::StaticAssert(resolve_static_assert(decl, var_map, tag_map, scope))),
+        BlockItem::S(stmt) => BlockItem::S(resolve_stmt(stmt, var_map, tag_map, scope)),
     }
 }
 
-fn resolve_decl(decl: &Decl, var_map: &mut HashMap<String, (String, u32)>, scope: u32) -> Decl {
+fn resolve_struct_decl(decl: &StructDecl, var_map: &VarMap, tag_map: &mut TagMap, scope: u32) -> StructDecl {
+    let (tag, members, kind) = match decl {
+        StructDecl::Struct(tag, members) => (tag, members, TagKind::Struct),
+        StructDecl::Union(tag, members) => (tag, members, TagKind::Union),
+    };
+    let is_union = kind == TagKind::Union;
+    let kind_name = if is_union { "union" } else { "struct" };
+
+    let new_tag = match tag_map.get(tag) {
+        Some(prev) if prev.scope == scope && prev.kind != kind =>
+            resolver_error(format!("{} {} redeclared as a different kind of tag", kind_name, tag).as_str()),
+        Some(prev) if prev.scope == scope => prev.name.clone(),
+        _ => {
+            let new_tag = unique_name(&format!("{}.{}", kind_name, tag));
+            tag_map.insert(tag.clone(), TagEntry { name: new_tag.clone(), scope, kind });
+            new_tag
+        },
+    };
+
+    let new_members = members.as_ref().map(|members| members.iter().map(|member| match member {
+        MemberDecl::Member(name, member_type) => MemberDecl::Member(name.clone(), resolve_type(member_type, var_map, tag_map, scope)),
+    }).collect());
+
+    if is_union { StructDecl::Union(new_tag, new_members) } else { StructDecl::Struct(new_tag, new_members) }
+}
+
+fn resolve_enum_decl(decl: &EnumDecl, var_map: &mut VarMap, tag_map: &mut TagMap, scope: u32) -> EnumDecl {
     match decl {
-        Decl::Declaration(name, init) => {
-            if var_map.contains_key(name) && var_map.get(name).unwrap().1 == scope {
-                resolver_error(format!("{} is a duplicate variable declaration", name).as_str());
+        EnumDecl::Enum(tag, enumerators) => {
+            let new_tag = tag.as_ref().map(|tag| {
+                if let Some(prev) = tag_map.get(tag) && prev.scope == scope {
+                    match prev.kind {
+                        TagKind::Enum => resolver_error(format!("enum {} redefined", tag).as_str()),
+                        _ => resolver_error(format!("enum {} redeclared as a different kind of tag", tag).as_str()),
+                    }
+                }
+                let new_tag = unique_name(&format!("enum.{}", tag));
+                tag_map.insert(tag.clone(), TagEntry { name: new_tag.clone(), scope, kind: TagKind::Enum });
+                new_tag
+            });
+
+            let mut new_enumerators: Vec<Enumerator> = Vec::new();
+            for Enumerator::Enumerator(name, value) in enumerators.iter() {
+                let new_value = value.as_ref().map(|expr| resolve_expr(expr, var_map, tag_map, scope));
+                match var_map.get(name) {
+                    Some(prev) if prev.scope == scope && matches!(prev.kind, IdentKind::Enumerator) =>
+                        resolver_error(format!("Enumerator {} redeclared", name).as_str()),
+                    Some(prev) if prev.scope == scope =>
+                        resolver_error(format!("{} redeclared as a different kind of symbol", name).as_str()),
+                    _ => (),
+                }
+                let new_name = unique_name(name);
+                var_map.insert(name.clone(), VarEntry { name: new_name.clone(), scope, has_linkage: false, kind: IdentKind::Enumerator });
+                new_enumerators.push(Enumerator::Enumerator(new_name, new_value));
             }
 
-            let new_name = unique_name(&name, var_map.len());
-            var_map.insert(name.clone(), (new_name.clone(), scope));
+            EnumDecl::Enum(new_tag, new_enumerators)
+        }
+    }
+}
+
+fn resolve_typedef_decl(decl: &TypedefDecl, var_map: &mut VarMap, tag_map: &mut TagMap, scope: u32) -> TypedefDecl {
+    match decl {
+        TypedefDecl::Typedef(name, target) => {
+            let new_target = resolve_type(target, var_map, tag_map, scope);
+            match var_map.get(name) {
+                //A typedef can be repeated in the same scope as long as it names the same type
+                Some(VarEntry { kind: IdentKind::Typedef(prev), scope: prev_scope, .. })
+                    if *prev_scope == scope && *prev != new_target =>
+                    resolver_error(format!("Conflicting types for typedef {}", name).as_str()),
+                Some(VarEntry { kind: IdentKind::Typedef(_), .. }) => (),
+                Some(prev) if prev.scope == scope =>
+                    resolver_error(format!("{} redeclared as a different kind of symbol", name).as_str()),
+                _ => (),
+            }
+            let kind = IdentKind::Typedef(new_target.clone());
+            var_map.insert(name.clone(), VarEntry { name: name.clone(), scope, has_linkage: false, kind });
 
-            let mut resolved_init = None;
+            TypedefDecl::Typedef(name.clone(), new_target)
+        }
+    }
+}
 
-            match init {
-                Some(expr) => {
-                    resolved_init = Some(resolve_expr(expr, var_map));
-                },
-                None => (),
+fn resolve_static_assert(decl: &StaticAssertDecl, var_map: &mut VarMap, tag_map: &mut TagMap, scope: u32) -> StaticAssertDecl {
+    match decl {
+        StaticAssertDecl::StaticAssert(cond, msg, location) =>
+            StaticAssertDecl::StaticAssert(resolve_expr(cond, var_map, tag_map, scope), msg.clone(), location.clone()),
+    }
+}
+
+//A structure or union tag used before any declaration of it declares an incomplete type in the current scope
+fn resolve_tag(tag: &str, kind: TagKind, tag_map: &mut TagMap, scope: u32) -> String {
+    let kind_name = if kind == TagKind::Union { "union" } else { "struct" };
+    match tag_map.get(tag) {
+        Some(entry) if entry.kind == kind => entry.name.clone(),
+        Some(_) => resolver_error(format!("{} is not a {} tag", tag, kind_name).as_str()),
+        None => {
+            let new_tag = unique_name(&format!("{}.{}", kind_name, tag));
+            tag_map.insert(tag.to_string(), TagEntry { name: new_tag.clone(), scope, kind });
+            new_tag
+        },
+    }
+}
+
+fn resolve_type(var_type: &Type, var_map: &VarMap, tag_map: &mut TagMap, scope: u32) -> Type {
+    match var_type {
+        Type::Structure(tag) => Type::Structure(resolve_tag(tag, TagKind::Struct, tag_map, scope)),
+        Type::Union(tag) => Type::Union(resolve_tag(tag, TagKind::Union, tag_map, scope)),
+        //Enumerated types are compatible with int, so nothing past the resolver needs to know about them
+        Type::Enum(tag) => match tag_map.get(tag) {
+            Some(TagEntry { kind: TagKind::Enum, .. }) => Type::Int,
+            Some(_) => resolver_error(format!("{} is not an enum tag", tag).as_str()),
+            None => resolver_error(format!("enum {} is an undeclared enumeration type", tag).as_str()),
+        },
+        Type::Typedef(name) => match var_map.get(name) {
+            Some(VarEntry { kind: IdentKind::Typedef(target), .. }) => target.clone(),
+            _ => resolver_error(format!("{} is not a type name", name).as_str()),
+        },
+        //A qualified typedef name can turn out to be an array, so the qualifier is applied again once it is replaced
+        Type::Const(inner) => resolve_type(inner, var_map, tag_map, scope).make_const(),
+        Type::Pointer(referenced) => Type::Pointer(Box::from(resolve_type(referenced, var_map, tag_map, scope))),
+        Type::Array(elem, count) => Type::Array(Box::from(resolve_type(elem, var_map, tag_map, scope)), *count),
+        Type::ArrayExpr(elem, Some(dim)) => Type::ArrayExpr(Box::from(resolve_type(elem, var_map, tag_map, scope)),
+            Some(Box::from(resolve_dimension(dim, var_map, tag_map, scope)))),
+        Type::ArrayExpr(_, None) => resolver_error("Array size missing"),
+        //A parameter declared as an array without a size is a pointer, so it needs no size
+        Type::Function(params, ret) => Type::Function(
+            params.iter().map(|param| match param {
+                Type::ArrayExpr(elem, None) => Type::Pointer(Box::from(resolve_type(elem, var_map, tag_map, scope))),
+                _ => resolve_type(param, var_map, tag_map, scope),
+            }).collect(),
+            Box::from(resolve_type(ret, var_map, tag_map, scope))),
+        _ => var_type.clone(),
+    }
+}
+
+//Only a variable's own type can leave the array size out, for the type checker to take it from the initializer
+fn resolve_var_type(var_type: &Type, var_map: &VarMap, tag_map: &mut TagMap, scope: u32) -> Type {
+    match var_type {
+        Type::ArrayExpr(elem, None) => Type::ArrayExpr(Box::from(resolve_type(elem, var_map, tag_map, scope)), None),
+        _ => resolve_type(var_type, var_map, tag_map, scope),
+    }
+}
+
+//Dimensions are resolved against copies of the maps, since a constant expression declares nothing.
+//They are folded by the type checker, once sizeof can measure structures and variables
+fn resolve_dimension(dim: &Expr, var_map: &VarMap, tag_map: &TagMap, scope: u32) -> Expr {
+    resolve_expr(dim, &mut var_map.clone(), &mut tag_map.clone(), scope)
+}
+
+fn resolve_decl(decl: &VarDecl, var_map: &mut VarMap, tag_map: &mut TagMap, scope: u32) -> VarDecl {
+    match decl {
+        VarDecl::Declaration(name, init, var_type, storage) => {
+            if let Some(prev) = var_map.get(name) && prev.scope == scope
+                && !(prev.has_linkage && *storage == Some(StorageClass::Extern)) {
+                resolver_error(format!("{} is a conflicting local declaration", name).as_str());
             }
 
-            Decl::Declaration(new_name, resolved_init)
+            let new_type = resolve_var_type(var_type, var_map, tag_map, scope);
+
+            if *storage == Some(StorageClass::Extern) {
+                var_map.insert(name.clone(), VarEntry { name: name.clone(), scope, has_linkage: true, kind: IdentKind::Var });
+                return VarDecl::Declaration(name.clone(), init.clone(), new_type, storage.clone());
+            }
+
+            let new_name = declare_var(name, var_map, scope);
+
+            let resolved_init = init.as_ref().map(|init| resolve_initializer(init, var_map, tag_map, scope));
+
+            VarDecl::Declaration(new_name, resolved_init, new_type, storage.clone())
         }
     }
 }
 
-fn resolve_stmt(stmt: &Stmt, var_map: &mut HashMap<String, (String, u32)>, scope: u32) -> Stmt {
+fn resolve_initializer(init: &Initializer, var_map: &mut VarMap, tag_map: &mut TagMap, scope: u32) -> Initializer {
+    match init {
+        Initializer::SingleInit(expr) => Initializer::SingleInit(resolve_expr(expr, var_map, tag_map, scope)),
+        Initializer::CompoundInit(inits) =>
+            Initializer::CompoundInit(inits.iter().map(|init| resolve_initializer(init, var_map, tag_map, scope)).collect()),
+    }
+}
+
+fn declare_var(name: &String, var_map: &mut VarMap, scope: u32) -> String {
+    if var_map.contains_key(name) && var_map.get(name).unwrap().scope == scope {
+        resolver_error(format!("{} is a duplicate variable declaration", name).as_str());
+    }
+
+    let new_name = unique_name(name);
+    var_map.insert(name.clone(), VarEntry { name: new_name.clone(), scope, has_linkage: false, kind: IdentKind::Var });
+
+    new_name
+}
+
+fn resolve_stmt(stmt: &Stmt, var_map: &mut VarMap, tag_map: &mut TagMap, scope: u32) -> Stmt {
     match stmt {
-        Stmt::Return(expr) => Stmt::Return(resolve_expr(expr, var_map)),
-        Stmt::Expression(expr) => Stmt::Expression(resolve_expr(expr, var_map)),
+        Stmt::Return(expr) => Stmt::Return(resolve_expr(expr, var_map, tag_map, scope)),
+        Stmt::Expression(expr) => Stmt::Expression(resolve_expr(expr, var_map, tag_map, scope)),
         Stmt::If(cond, true_stmt, else_stmt) => {
-            let else_res_stmt = match else_stmt {
-                Some(stmt) => Some(Box::from(resolve_stmt(stmt, var_map, scope))),
-                None => None,
-            };
+            let else_res_stmt = else_stmt.as_ref().map(|stmt| Box::from(resolve_stmt(stmt, var_map, tag_map, scope)));
 
-            Stmt::If(resolve_expr(cond, var_map), Box::from(resolve_stmt(true_stmt, var_map, scope)), else_res_stmt)
+            Stmt::If(resolve_expr(cond, var_map, tag_map, scope), Box::from(resolve_stmt(true_stmt, var_map, tag_map, scope)), else_res_stmt)
         },
         Stmt::Compound(block) => {
-            let new_block = resolve_block(block, var_map, scope);
+            let new_block = resolve_block(block, var_map, tag_map, scope);
             Stmt::Compound(new_block)
         },
-        Stmt::Null => Stmt::Null,
+        Stmt::Break(label) => Stmt::Break(label.clone()),
+        Stmt::Continue(label) => Stmt::Continue(label.clone()),
+        Stmt::While(cond, body, label) => Stmt::While(
+            resolve_expr(cond, var_map, tag_map, scope),
+            Box::from(resolve_stmt(body, var_map, tag_map, scope)),
+            label.clone()),
+        Stmt::DoWhile(body, cond, label) => Stmt::DoWhile(
+            Box::from(resolve_stmt(body, var_map, tag_map, scope)),
+            resolve_expr(cond, var_map, tag_map, scope),
+            label.clone()),
+        Stmt::For(init, cond, post, body, label) => {
+            let (new_init, new_cond, new_post, new_body) = resolve_for(init, cond, post, body, var_map, tag_map, scope);
+            Stmt::For(new_init, new_cond, new_post, Box::from(new_body), label.clone())
+        },
+        Stmt::Switch(cond, body, label, cases) => Stmt::Switch(
+            resolve_expr(cond, var_map, tag_map, scope),
+            Box::from(resolve_stmt(body, var_map, tag_map, scope)),
+            label.clone(),
+            cases.clone()),
+        Stmt::Case(value, body, label) => Stmt::Case(
+            resolve_expr(value, var_map, tag_map, scope),
+            Box::from(resolve_stmt(body, var_map, tag_map, scope)),
+            label.clone()),
+        Stmt::Default(body, label) => Stmt::Default(Box::from(resolve_stmt(body, var_map, tag_map, scope)), label.clone()),
+        Stmt::Labeled(label, body) => Stmt::Labeled(label.clone(), Box::from(resolve_stmt(body, var_map, tag_map, scope))),
+        Stmt::Goto(_) | Stmt::Null => stmt.clone(),
+    }
+}
+
+//The loop header and body share a scope of their own
+fn resolve_for(init: &ForInit, cond: &Option<Expr>, post: &Option<Expr>, body: &Stmt,
+    var_map: &mut VarMap, tag_map: &mut TagMap, scope: u32) -> (ForInit, Option<Expr>, Option<Expr>, Stmt) {
+    let mut scoped_var_map = var_map.clone();
+    let mut scoped_tag_map = tag_map.clone();
+
+    let resolved_init = match init {
+        ForInit::InitDecl(decls) => ForInit::InitDecl(decls.iter()
+            .map(|decl| resolve_decl(decl, &mut scoped_var_map, &mut scoped_tag_map, scope + 1))
+            .collect()),
+        ForInit::InitExpr(expr) => ForInit::InitExpr(resolve_optional_expr(expr, &mut scoped_var_map, &mut scoped_tag_map, scope + 1)),
+    };
+    let resolved_cond = resolve_optional_expr(cond, &mut scoped_var_map, &mut scoped_tag_map, scope + 1);
+    let resolved_post = resolve_optional_expr(post, &mut scoped_var_map, &mut scoped_tag_map, scope + 1);
+    let resolved_body = resolve_stmt(body, &mut scoped_var_map, &mut scoped_tag_map, scope + 1);
+
+    (resolved_init, resolved_cond, resolved_post, resolved_body)
+}
+
+fn resolve_optional_expr(expr: &Option<Expr>, var_map: &mut VarMap, tag_map: &mut TagMap, scope: u32) -> Option<Expr> {
+    expr.as_ref().map(|e| resolve_expr(e, var_map, tag_map, scope))
+}
+
+fn resolve_expr(expr: &Expr, var_map: &mut VarMap, tag_map: &mut TagMap, scope: u32) -> Expr {
+    let kind = match &expr.kind {
+        ExprKind::Assignment(left, right) => resolve_assignment(left, right, var_map, tag_map, scope),
+        ExprKind::CompoundAssignment(op, left, right) => ExprKind::CompoundAssignment(op.clone(),
+            Box::new(resolve_lvalue(left, var_map, tag_map, scope, "Invalid lvalue")),
+            Box::new(resolve_expr(right, var_map, tag_map, scope))),
+        ExprKind::Postfix(op, inner) =>
+            ExprKind::Postfix(op.clone(), Box::new(resolve_lvalue(inner, var_map, tag_map, scope, "Invalid lvalue"))),
+        ExprKind::Var(v) => resolve_var(v, var_map),
+        ExprKind::Binary(op, left, right) => ExprKind::Binary(
+            op.clone(),
+            Box::new(resolve_expr(left, var_map, tag_map, scope)),
+            Box::new(resolve_expr(right, var_map, tag_map, scope))),
+        ExprKind::Unary(op, oprnd) => ExprKind::Unary(op.clone(), Box::new(resolve_expr(oprnd, var_map, tag_map, scope))),
+        ExprKind::Constant(_) | ExprKind::String(_) => expr.kind.clone(),
+        ExprKind::SizeOf(inner) => ExprKind::SizeOf(Box::new(resolve_expr(inner, var_map, tag_map, scope))),
+        //A tag first named inside an expression is declared in the scope the expression appears in
+        ExprKind::SizeOfType(target) => ExprKind::SizeOfType(resolve_type(target, var_map, tag_map, scope)),
+        ExprKind::AlignOf(target) => ExprKind::AlignOf(resolve_type(target, var_map, tag_map, scope)),
+        ExprKind::Cast(target, inner) => ExprKind::Cast(
+            resolve_type(target, var_map, tag_map, scope),
+            Box::new(resolve_expr(inner, var_map, tag_map, scope))),
+        ExprKind::Conditional(left, middle, right) => ExprKind::Conditional(
+            Box::from(resolve_expr(left, var_map, tag_map, scope)),
+            Box::from(resolve_expr(middle, var_map, tag_map, scope)),
+            Box::from(resolve_expr(right, var_map, tag_map, scope))),
+        ExprKind::Comma(left, right) => ExprKind::Comma(
+            Box::from(resolve_expr(left, var_map, tag_map, scope)),
+            Box::from(resolve_expr(right, var_map, tag_map, scope))),
+        ExprKind::FunctionCall(name, args) => resolve_call(name, args, var_map, tag_map, scope),
+        ExprKind::Dereference(inner) => ExprKind::Dereference(Box::new(resolve_expr(inner, var_map, tag_map, scope))),
+        ExprKind::AddrOf(inner) =>
+            ExprKind::AddrOf(Box::new(resolve_lvalue(inner, var_map, tag_map, scope, "Cannot take the address of a non-lvalue"))),
+        ExprKind::Dot(inner, member) => ExprKind::Dot(Box::new(resolve_expr(inner, var_map, tag_map, scope)), member.clone()),
+        ExprKind::Arrow(inner, member) => ExprKind::Arrow(Box::new(resolve_expr(inner, var_map, tag_map, scope)), member.clone()),
+        ExprKind::Subscript(array, index) => ExprKind::Subscript(
+            Box::new(resolve_expr(array, var_map, tag_map, scope)),
+            Box::new(resolve_expr(index, var_map, tag_map, scope))),
+        ExprKind::Builtin(builtin, args) =>
+            ExprKind::Builtin(builtin.clone(), args.iter().map(|arg| resolve_expr(arg, var_map, tag_map, scope)).collect()),
+        ExprKind::StmtExpr(block) => ExprKind::StmtExpr(resolve_block(block, var_map, tag_map, scope)),
+    };
+
+    Expr::new(kind)
+}
+
+fn resolve_assignment(left: &Expr, right: &Expr, var_map: &mut VarMap, tag_map: &mut TagMap, scope: u32) -> ExprKind {
+    let new_left = resolve_lvalue(left, var_map, tag_map, scope, "Invalid lvalue");
+
+    ExprKind::Assignment(Box::new(new_left), Box::new(resolve_expr(right, var_map, tag_map, scope)))
+}
+
+fn resolve_lvalue(expr: &Expr, var_map: &mut VarMap, tag_map: &mut TagMap, scope: u32, error: &str) -> Expr {
+    if !is_lvalue(expr, var_map) {
+        resolver_error(error);
     }
+
+    resolve_expr(expr, var_map, tag_map, scope)
 }
 
-fn resolve_expr(expr: &Expr, var_map: &mut HashMap<String, (String, u32)>) -> Expr {
-    match expr {
-        Expr::Assignment(left, right) => resolve_assignment(left, right, var_map),
-        Expr::Var(v) => resolve_var(v, var_map),
-        Expr::Binary(op, left, right) => Expr::Binary(
-            op.clone(), 
-            Box::new(resolve_expr(left, var_map)), 
-            Box::new(resolve_expr(right, var_map))),
-        Expr::Unary(op, oprnd) => Expr::Unary(op.clone(), Box::new(resolve_expr(oprnd, var_map))),
-        Expr::Constant(_) => expr.clone(),
-        Expr::Conditional(left, middle, right) => Expr::Conditional(
-            Box::from(resolve_expr(left, var_map)), 
-            Box::from(resolve_expr(middle, var_map)), 
-            Box::from(resolve_expr(right, var_map))),
+//Enumerators are named like variables, but they are constants rather than objects
+fn is_lvalue(expr: &Expr, var_map: &VarMap) -> bool {
+    match &expr.kind {
+        ExprKind::Var(name) => !matches!(var_map.get(name), Some(VarEntry { kind: IdentKind::Enumerator, .. })),
+        ExprKind::Dot(inner, _) => is_lvalue(inner, var_map),
+        _ => matches!(expr.kind, ExprKind::Dereference(_) | ExprKind::Subscript(_, _)
+            | ExprKind::Arrow(_, _) | ExprKind::String(_)),
     }
 }
 
-fn resolve_assignment(left: &Expr, right: &Expr, var_map: &mut HashMap<String, (String, u32)>) -> Expr {
-    let to_assign = match left {
-        Expr::Var(_) => left.clone(),
-        _ => resolver_error("Invalid lvalue"),
+fn resolve_call(name: &String, args: &[Expr], var_map: &mut VarMap, tag_map: &mut TagMap, scope: u32) -> ExprKind {
+    let new_name = match var_map.get(name) {
+        Some(entry) => entry.name.clone(),
+        None => resolver_error(format!("{} is an undeclared function", name).as_str()),
     };
 
-    Expr::Assignment(Box::new(resolve_expr(&to_assign, var_map)), Box::new(resolve_expr(right, var_map)))
+    let new_args = args.iter().map(|arg| resolve_expr(arg, var_map, tag_map, scope)).collect();
+
+    ExprKind::FunctionCall(new_name, new_args)
 }
 
-fn resolve_var(var: &String, var_map: &mut HashMap<String, (String, u32)>) -> Expr {
-    if var_map.contains_key(var) {
-        let test = Expr::Var(var_map.get(var).unwrap().0.clone());
-        return test;
-    } else {
-        resolver_error(format!("{} is an undeclared variable", var).as_str())
+fn resolve_var(var: &String, var_map: &mut VarMap) -> ExprKind {
+    match var_map.get(var) {
+        Some(VarEntry { kind: IdentKind::Typedef(_), .. }) =>
+            resolver_error(format!("{} is a type name, not a variable", var).as_str()),
+        Some(entry) => ExprKind::Var(entry.name.clone()),
+        None => resolver_error(format!("{} is an undeclared variable", var).as_str()),
     }
 }
 
-fn unique_name(name: &String, postfix: usize) -> String {
-    String::from(name.to_owned() + "." + format!("{}", postfix).as_str())
-}
\ No newline at end of file
+//Recovers how the source spelled a renamed variable or tag, so diagnostics don't show the internal name
+pub fn source_name(name: &str) -> String {
+    let base = match name.rsplit_once('.') {
+        Some((base, counter)) if !counter.is_empty() && counter.bytes().all(|b| b.is_ascii_digit()) => base,
+        _ => name,
+    };
+
+    match base.split_once('.') {
+        Some((kind @ ("struct" | "union" | "enum"), tag)) if tag.starts_with('.') => format!("{} <anonymous>", kind),
+        Some((kind @ ("struct" | "union" | "enum"), tag)) => format!("{} {}", kind, tag),
+        _ => base.to_string(),
+    }
+}
+
+fn unique_name(name: &str) -> String {
+    let postfix = NAME_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("{}.{}", name, postfix)
+}
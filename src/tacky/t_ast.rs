@@ -1,3 +1,5 @@
+use crate::parser::ast::{Const, Type};
+use crate::resolver::type_checker::StaticInit;
 
 #[derive(Debug)]
 #[derive(Clone)]
@@ -15,6 +17,11 @@ pub enum TBinaryOp {
     Multiply,
     Divide,
     Remainder,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
     Equal,
     NotEqual,
     LessThan,
@@ -26,7 +33,7 @@ pub enum TBinaryOp {
 #[derive(Debug)]
 #[derive(Clone)]
 pub enum TVal {
-    Constant(i32),
+    Constant(Const),
     Var(String),
 }
 
@@ -41,9 +48,37 @@ pub enum TInstr {
     JumpIfZero(TVal, String),
     JumpIfNotZero(TVal, String),
     Label(String),
+    FunCall(String, Vec<TVal>, TVal),
+    SignExtend(TVal, TVal),
+    Truncate(TVal, TVal),
+    ZeroExtend(TVal, TVal),
+    DoubleToInt(TVal, TVal),
+    DoubleToUInt(TVal, TVal),
+    IntToDouble(TVal, TVal),
+    UIntToDouble(TVal, TVal),
+    GetAddress(TVal, TVal),
+    Load(TVal, TVal),
+    Store(TVal, TVal),
+    AddPtr(TVal, TVal, i32, TVal),
+    CopyToOffset(TVal, String, i32),
+    CopyFromOffset(String, i32, TVal),
+    JumpTable(TVal, i64, Vec<String>, String),
+    Trap,
 }
 
 #[derive(Debug)]
 pub enum TFuncDef {
-    Function(String, Vec<TInstr>),
+    Function(String, bool, Vec<String>, Vec<TInstr>),
+}
+
+#[derive(Debug)]
+pub enum TTopLevel {
+    Func(TFuncDef),
+    StaticVar(String, bool, Type, Vec<StaticInit>),
+    StaticConst(String, Type, StaticInit),
+}
+
+#[derive(Debug)]
+pub enum TProgram {
+    Program(Vec<TTopLevel>),
 }
\ No newline at end of file
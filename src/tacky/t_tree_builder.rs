@@ -1,192 +1,779 @@
 
+use std::sync::atomic::{AtomicUsize, Ordering};
+
 use crate::tacky::t_ast::*;
 use crate::parser::ast::*;
+use crate::resolver::type_checker::*;
+
+static TEMP_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+//Switches with fewer cases than this always use a compare chain
+const MIN_JUMP_TABLE_CASES: usize = 4;
+
+//An lvalue is either a plain object, a member at some offset inside one, or the object a pointer refers to
+enum ExprResult {
+    Operand(TVal),
+    SubObject(String, i32),
+    DereferencedPointer(TVal),
+}
+
+pub fn gen_tacky(ast: Program, symbols: &mut SymbolTable) -> TProgram {
+    let mut top_level: Vec<TTopLevel> = Vec::new();
 
-pub fn gen_tacky(ast: FuncDef) -> TFuncDef {
-    let tacky_tree = function_decl(&ast);
+    match ast {
+        Program::Program(decls) => {
+            for decl in decls.iter() {
+                if let Decl::Func(func) = decl && let Some(function) = function_decl(func, symbols) {
+                    top_level.push(TTopLevel::Func(function));
+                }
+            }
+        }
+    }
+
+    top_level.extend(static_vars(symbols));
 
-    tacky_tree
+    TProgram::Program(top_level)
 }
 
-fn function_decl(ast: &FuncDef) -> TFuncDef {
+fn function_decl(ast: &FuncDecl, symbols: &mut SymbolTable) -> Option<TFuncDef> {
     match ast {
-        FuncDef::Function(ident, body) => TFuncDef::Function(ident.to_string(), instrs(&body)),
+        FuncDecl::Function(ident, params, Some(body), _, _) => {
+            let global = match symbols.get(ident) {
+                Some(Symbol { attrs: IdentAttrs::Fun(_, global), .. }) => *global,
+                _ => true,
+            };
+            Some(TFuncDef::Function(ident.to_string(), global, params.clone(), instrs(body, symbols)))
+        },
+        FuncDecl::Function(_, _, None, _, _) => None,
     }
 }
 
-fn instrs(body: &Block) -> Vec<TInstr> {
+fn static_vars(symbols: &SymbolTable) -> Vec<TTopLevel> {
+    let mut names: Vec<&String> = symbols.keys().collect();
+    names.sort();
+
+    let mut vars: Vec<TTopLevel> = Vec::new();
+    for name in names {
+        let symbol = &symbols[name];
+        if let IdentAttrs::Constant(init) = &symbol.attrs {
+            vars.push(TTopLevel::StaticConst(name.clone(), symbol.sym_type.clone(), init.clone()));
+        }
+        if let IdentAttrs::Static(init, global) = &symbol.attrs {
+            match init {
+                InitialValue::Initial(inits) => {
+                    vars.push(TTopLevel::StaticVar(name.clone(), *global, symbol.sym_type.clone(), inits.clone()));
+                },
+                InitialValue::Tentative => {
+                    let zero = zero_static_init(&symbol.sym_type, symbols);
+                    vars.push(TTopLevel::StaticVar(name.clone(), *global, symbol.sym_type.clone(), zero));
+                },
+                InitialValue::NoInitializer => (),
+            }
+        }
+    }
+
+    vars
+}
+
+fn instrs(body: &Block, symbols: &mut SymbolTable) -> Vec<TInstr> {
     let mut instructions: Vec<TInstr> = Vec::new();
 
-    block(body, &mut instructions);
+    block(body, &mut instructions, symbols);
 
-    instructions.push(TInstr::Return(TVal::Constant(0)));
+    instructions.push(TInstr::Return(TVal::Constant(Const::Int(0))));
     
     instructions
 }
 
-fn block(items: &Block, instructions: &mut Vec<TInstr>) {
+fn block(items: &Block, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) {
     match items {
         Block::Block(items) => {
             for item in items.iter() {
-                block_item(item, instructions);
+                block_item(item, instructions, symbols);
             }
         }
     }
 }
 
-fn block_item(item: &BlockItem, instructions: &mut Vec<TInstr>) {
+fn block_item(item: &BlockItem, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) {
     match item {
-        BlockItem::S(stmt) => stmt_val(stmt, instructions),
-        BlockItem::D(decl) => decl_val(decl, instructions),
+        BlockItem::S(stmt) => stmt_val(stmt, instructions, symbols),
+        BlockItem::D(Decl::Var(decl)) => decl_val(decl, instructions, symbols),
+        BlockItem::D(Decl::Func(_)) | BlockItem::D(Decl::Struct(_)) | BlockItem::D(Decl::Enum(_))
+            | BlockItem::D(Decl::Typedef(_)) | BlockItem::D(Decl::StaticAssert(_)) => (),
     }
 }
 
-fn decl_val(decl: &Decl, instructions: &mut Vec<TInstr>) {
+fn decl_val(decl: &VarDecl, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) {
     match decl {
-        Decl::Declaration(var, Some(expr)) => {
-            let res = expr_val(expr, instructions);
+        VarDecl::Declaration(var, Some(Initializer::SingleInit(expr)), var_type, None) if var_type.is_scalar() => {
+            let res = expr_val(expr, instructions, symbols);
             instructions.push(TInstr::Copy(res, TVal::Var(var.to_string())));
         },
+        VarDecl::Declaration(var, Some(init), var_type, None) => {
+            compound_init(var, init, var_type, 0, instructions, symbols);
+        },
         _ => (),
     }
 }
 
-fn stmt_val(stmt: &Stmt, instructions: &mut Vec<TInstr>) {
+fn compound_init(var: &str, init: &Initializer, init_type: &Type, offset: i32, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) {
+    match (init, init_type) {
+        (Initializer::SingleInit(Expr { kind: ExprKind::String(bytes), .. }), Type::Array(_, count)) => {
+            string_init(var, bytes, *count, offset, instructions);
+        },
+        (Initializer::SingleInit(expr), _) => {
+            let res = expr_val(expr, instructions, symbols);
+            instructions.push(TInstr::CopyToOffset(res, var.to_string(), offset));
+        },
+        (Initializer::CompoundInit(inits), Type::Array(elem, _)) => {
+            let elem_size = type_size(elem, symbols);
+            for (i, init) in inits.iter().enumerate() {
+                compound_init(var, init, elem, offset + i as i32 * elem_size, instructions, symbols);
+            }
+        },
+        (Initializer::CompoundInit(inits), Type::Structure(tag)) => {
+            let members = struct_def(tag, symbols).members;
+            for (init, member) in inits.iter().zip(members.iter()) {
+                compound_init(var, init, &member.member_type, offset + member.offset, instructions, symbols);
+            }
+        },
+        (Initializer::CompoundInit(inits), Type::Union(tag)) => {
+            let member_type = struct_def(tag, symbols).members.swap_remove(0).member_type;
+            compound_init(var, &inits[0], &member_type, offset, instructions, symbols);
+            //Bytes past the initialized member are zeroed, as they would be for a static union
+            let member_size = type_size(&member_type, symbols);
+            string_init(var, &[], type_size(init_type, symbols) - member_size, offset + member_size, instructions);
+        },
+        _ => panic!("Compound initializer for a scalar type"),
+    }
+}
+
+//Copies the string into the array in the largest chunks that fit, padding the rest with zeroes
+fn string_init(var: &str, bytes: &[u8], count: i32, offset: i32, instructions: &mut Vec<TInstr>) {
+    let mut padded = bytes.to_vec();
+    padded.resize(count as usize, 0);
+
+    let mut i = 0;
+    while i < padded.len() {
+        let remaining = &padded[i..];
+        let (val, size) = if remaining.len() >= 8 {
+            (Const::Long(i64::from_le_bytes(remaining[..8].try_into().unwrap())), 8)
+        } else if remaining.len() >= 4 {
+            (Const::Int(i32::from_le_bytes(remaining[..4].try_into().unwrap())), 4)
+        } else {
+            (Const::Char(remaining[0] as i8), 1)
+        };
+        instructions.push(TInstr::CopyToOffset(TVal::Constant(val), var.to_string(), offset + i as i32));
+        i += size;
+    }
+}
+
+fn stmt_val(stmt: &Stmt, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) {
     match stmt {
         Stmt::Return(val) => {
-            let ret_val = expr_val(val, instructions);
+            let ret_val = expr_val(val, instructions, symbols);
             instructions.push(TInstr::Return(ret_val));
         },
         Stmt::Expression(expr) => {
-            let _ = expr_val(expr, instructions);
+            let _ = expr_val(expr, instructions, symbols);
+        },
+        Stmt::If(cond, then, None) => if_stmt(cond, then, instructions, symbols),
+        Stmt::If(cond, then, Some(else_stmt)) => if_else_stmt(cond, then, else_stmt, instructions, symbols),
+        Stmt::Compound(items) => block(items, instructions, symbols),
+        Stmt::Break(label) => instructions.push(TInstr::Jump(break_label(label))),
+        Stmt::Continue(label) => instructions.push(TInstr::Jump(continue_label(label))),
+        Stmt::While(cond, body, label) => while_stmt(cond, body, label, instructions, symbols),
+        Stmt::DoWhile(body, cond, label) => do_while_stmt(body, cond, label, instructions, symbols),
+        Stmt::For(init, cond, post, body, label) => for_stmt(init, cond, post, body, label, instructions, symbols),
+        Stmt::Switch(cond, body, label, cases) => switch_stmt(cond, body, label, cases, instructions, symbols),
+        Stmt::Goto(label) => instructions.push(TInstr::Jump(label.clone())),
+        Stmt::Case(_, body, label) | Stmt::Default(body, label) | Stmt::Labeled(label, body) => {
+            instructions.push(TInstr::Label(label.clone()));
+            stmt_val(body, instructions, symbols);
         },
-        Stmt::If(cond, then, None) => if_stmt(cond, then, instructions),
-        Stmt::If(cond, then, Some(else_stmt)) => if_else_stmt(cond, then, else_stmt, instructions),
-        Stmt::Compound(items) => block(items, instructions),
         Stmt::Null => (),
     }
 }
 
-fn if_stmt(cond: &Expr, then: &Stmt, instructions: &mut Vec<TInstr>) {
-    let res = expr_val(cond, instructions);
-    let if_end = make_temp("if_to_end", instructions.len());
+fn while_stmt(cond: &Expr, body: &Stmt, label: &str, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) {
+    instructions.push(TInstr::Label(continue_label(label)));
+    let res = expr_val(cond, instructions, symbols);
+    instructions.push(TInstr::JumpIfZero(res, break_label(label)));
+    stmt_val(body, instructions, symbols);
+    instructions.push(TInstr::Jump(continue_label(label)));
+    instructions.push(TInstr::Label(break_label(label)));
+}
+
+fn do_while_stmt(body: &Stmt, cond: &Expr, label: &str, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) {
+    instructions.push(TInstr::Label(start_label(label)));
+    stmt_val(body, instructions, symbols);
+    instructions.push(TInstr::Label(continue_label(label)));
+    let res = expr_val(cond, instructions, symbols);
+    instructions.push(TInstr::JumpIfNotZero(res, start_label(label)));
+    instructions.push(TInstr::Label(break_label(label)));
+}
+
+fn for_stmt(init: &ForInit, cond: &Option<Expr>, post: &Option<Expr>, body: &Stmt, label: &str, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) {
+    match init {
+        ForInit::InitDecl(decls) => {
+            for decl in decls.iter() {
+                decl_val(decl, instructions, symbols);
+            }
+        },
+        ForInit::InitExpr(Some(expr)) => {
+            let _ = expr_val(expr, instructions, symbols);
+        },
+        ForInit::InitExpr(None) => (),
+    }
+
+    instructions.push(TInstr::Label(start_label(label)));
+    if let Some(expr) = cond {
+        let res = expr_val(expr, instructions, symbols);
+        instructions.push(TInstr::JumpIfZero(res, break_label(label)));
+    }
+    stmt_val(body, instructions, symbols);
+    instructions.push(TInstr::Label(continue_label(label)));
+    if let Some(expr) = post {
+        let _ = expr_val(expr, instructions, symbols);
+    }
+    instructions.push(TInstr::Jump(start_label(label)));
+    instructions.push(TInstr::Label(break_label(label)));
+}
+
+//Dense case sets jump through a table indexed by the case value, sparse ones compare against each case in turn
+fn switch_stmt(cond: &Expr, body: &Stmt, label: &str, cases: &[(Option<Const>, String)], instructions: &mut Vec<TInstr>,
+    symbols: &mut SymbolTable) {
+    let val = expr_val(cond, instructions, symbols);
+    let default = cases.iter()
+        .find(|(value, _)| value.is_none())
+        .map_or(break_label(label), |(_, case_label)| case_label.clone());
+    let mut values: Vec<(i128, &String)> = cases.iter()
+        .filter_map(|(value, case_label)| value.as_ref().map(|value| (case_int(value), case_label)))
+        .collect();
+    values.sort();
+
+    if is_dense(&values) {
+        let min = values[0].0;
+        let mut targets = vec![default.clone(); (values[values.len() - 1].0 - min + 1) as usize];
+        for (value, case_label) in values.iter() {
+            targets[(value - min) as usize] = case_label.to_string();
+        }
+        instructions.push(TInstr::JumpTable(val, min as i64, targets, default));
+    } else {
+        for (value, case_label) in cases.iter() {
+            if let Some(value) = value {
+                let matched = make_tacky_var("case_match", Type::Int, symbols);
+                instructions.push(TInstr::Binary(TBinaryOp::Equal, val.clone(), TVal::Constant(value.clone()), matched.clone()));
+                instructions.push(TInstr::JumpIfNotZero(matched, case_label.clone()));
+            }
+        }
+        instructions.push(TInstr::Jump(default));
+    }
+
+    stmt_val(body, instructions, symbols);
+    instructions.push(TInstr::Label(break_label(label)));
+}
+
+//A table is only worth emitting when at least a third of its entries are real cases
+fn is_dense(values: &[(i128, &String)]) -> bool {
+    match (values.first(), values.last()) {
+        (Some((min, _)), Some((max, _))) =>
+            values.len() >= MIN_JUMP_TABLE_CASES && max - min < 3 * values.len() as i128,
+        _ => false,
+    }
+}
+
+fn case_int(val: &Const) -> i128 {
+    match val {
+        Const::Int(v) => *v as i128,
+        Const::Long(v) => *v as i128,
+        Const::UInt(v) => *v as i128,
+        Const::ULong(v) => *v as i128,
+        Const::Char(v) => *v as i128,
+        Const::Double(_) => panic!("Case values are always integers"),
+    }
+}
+
+fn start_label(label: &str) -> String {
+    format!("start_{}", label)
+}
+
+fn continue_label(label: &str) -> String {
+    format!("continue_{}", label)
+}
+
+fn break_label(label: &str) -> String {
+    format!("break_{}", label)
+}
+
+fn if_stmt(cond: &Expr, then: &Stmt, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) {
+    let res = expr_val(cond, instructions, symbols);
+    let if_end = make_temp("if_to_end");
     instructions.push(TInstr::JumpIfZero(res, if_end.clone()));
-    stmt_val(then, instructions);
+    stmt_val(then, instructions, symbols);
     instructions.push(TInstr::Label(if_end));
 }
 
-fn if_else_stmt(cond: &Expr, then: &Stmt, else_stmt: &Box<Stmt>, instructions: &mut Vec<TInstr>) {
-    let res = expr_val(cond, instructions);
-    let if_else = make_temp("if_to_else", instructions.len());
-    let if_end = make_temp("if_to_end", instructions.len());
+fn if_else_stmt(cond: &Expr, then: &Stmt, else_stmt: &Stmt, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) {
+    let res = expr_val(cond, instructions, symbols);
+    let if_else = make_temp("if_to_else");
+    let if_end = make_temp("if_to_end");
     instructions.push(TInstr::JumpIfZero(res, if_else.clone()));
-    stmt_val(then, instructions);
+    stmt_val(then, instructions, symbols);
     instructions.push(TInstr::Jump(if_end.clone()));
     instructions.push(TInstr::Label(if_else));
-    stmt_val(else_stmt, instructions);
+    stmt_val(else_stmt, instructions, symbols);
     instructions.push(TInstr::Label(if_end));
 }
 
-fn expr_val(expr: &Expr, instructions: &mut Vec<TInstr>) -> TVal {
-    match expr {
-        Expr::Constant(val) => TVal::Constant(*val),
-        Expr::Unary(op, inner) => {
-            let src = expr_val(inner, instructions);
-            let dst_name = make_temp("temp", instructions.len());
-            let dst = TVal::Var(dst_name);
+fn expr_val(expr: &Expr, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) -> TVal {
+    match &expr.kind {
+        ExprKind::Constant(val) => TVal::Constant(val.clone()),
+        ExprKind::String(bytes) => TVal::Var(string_constant(bytes, symbols)),
+        ExprKind::Unary(op, inner) => {
+            let src = expr_val(inner, instructions, symbols);
+            let dst = make_tacky_var("temp", expr.get_type(), symbols);
             let tacky_op = get_unary_op(op);
             instructions.push(TInstr::Unary(tacky_op, src, dst.clone()));
 
             dst
         },
-        Expr::Binary(op, left, right) => binary(&op, left, right, instructions),
-        Expr::Var(v) => TVal::Var(v.clone()),
-        Expr::Assignment(var, right) => {
-            let mut v = String::new();
-            if let Expr::Var(name) = &**var {
-                v = name.clone();
+        ExprKind::Binary(op, left, right) => binary(expr, op, left, right, instructions, symbols),
+        ExprKind::SizeOf(inner) => TVal::Constant(Const::ULong(type_size(&inner.get_type(), symbols) as u64)),
+        ExprKind::SizeOfType(target) => TVal::Constant(Const::ULong(type_size(target, symbols) as u64)),
+        ExprKind::AlignOf(target) => TVal::Constant(Const::ULong(type_alignment(target, symbols) as u64)),
+        ExprKind::Var(v) => TVal::Var(v.clone()),
+        ExprKind::Cast(target, inner) => cast(target, inner, instructions, symbols),
+        ExprKind::Comma(left, right) => {
+            let _ = expr_val(left, instructions, symbols);
+            expr_val(right, instructions, symbols)
+        },
+        ExprKind::Assignment(left, right) => {
+            let lhs = lvalue(left, instructions, symbols);
+            let res = expr_val(right, instructions, symbols);
+            store_lvalue(lhs, res, instructions)
+        },
+        ExprKind::CompoundAssignment(op, left, right) => {
+            let lhs = lvalue(left, instructions, symbols);
+            let res = expr_val(right, instructions, symbols);
+            update_lvalue(lhs, &left.get_type(), op, res, &right.get_type(), instructions, symbols)
+        },
+        ExprKind::Postfix(op, inner) => {
+            let lhs = lvalue(inner, instructions, symbols);
+            let inner_type = inner.get_type();
+            let current = read_lvalue(&lhs, &inner_type, instructions, symbols);
+            let old = make_tacky_var("postfix_old", inner_type.clone(), symbols);
+            instructions.push(TInstr::Copy(current, old.clone()));
+            let (step, step_type) = unit_step(&inner_type);
+            update_lvalue(lhs, &inner_type, op, step, &step_type, instructions, symbols);
+
+            old
+        },
+        ExprKind::Conditional(left, middle, right) => conditional(expr, left, middle, right, instructions, symbols),
+        ExprKind::FunctionCall(name, args) => {
+            let arg_vals = args.iter().map(|arg| expr_val(arg, instructions, symbols)).collect();
+            let dst = make_tacky_var("call_res", expr.get_type(), symbols);
+            instructions.push(TInstr::FunCall(name.clone(), arg_vals, dst.clone()));
+
+            dst
+        },
+        ExprKind::Dereference(inner) => {
+            let ptr = expr_val(inner, instructions, symbols);
+            let dst = make_tacky_var("load", expr.get_type(), symbols);
+            instructions.push(TInstr::Load(ptr, dst.clone()));
+
+            dst
+        },
+        ExprKind::AddrOf(inner) => match lvalue(inner, instructions, symbols) {
+            ExprResult::Operand(val) => {
+                let dst = make_tacky_var("addr", expr.get_type(), symbols);
+                instructions.push(TInstr::GetAddress(val, dst.clone()));
+
+                dst
+            },
+            ExprResult::SubObject(base, offset) => {
+                let dst = make_tacky_var("addr", expr.get_type(), symbols);
+                instructions.push(TInstr::GetAddress(TVal::Var(base), dst.clone()));
+
+                offset_pointer(dst, offset, expr.get_type(), instructions, symbols)
+            },
+            ExprResult::DereferencedPointer(ptr) => ptr,
+        },
+        ExprKind::Subscript(array, index) => {
+            let ptr = subscript(array, index, instructions, symbols);
+            let dst = make_tacky_var("load", expr.get_type(), symbols);
+            instructions.push(TInstr::Load(ptr, dst.clone()));
+
+            dst
+        },
+        ExprKind::Dot(_, _) | ExprKind::Arrow(_, _) => {
+            let dst = make_tacky_var("member", expr.get_type(), symbols);
+            match lvalue(expr, instructions, symbols) {
+                ExprResult::SubObject(base, offset) => instructions.push(TInstr::CopyFromOffset(base, offset, dst.clone())),
+                ExprResult::DereferencedPointer(ptr) => instructions.push(TInstr::Load(ptr, dst.clone())),
+                ExprResult::Operand(val) => instructions.push(TInstr::Copy(val, dst.clone())),
+            }
+
+            dst
+        },
+        ExprKind::StmtExpr(body) => stmt_expr(expr, body, instructions, symbols),
+        ExprKind::Builtin(builtin, args) => builtin_val(builtin, args, instructions, symbols),
+    }
+}
+
+//The value is copied out so that later statements in the enclosing expression can't change it
+fn stmt_expr(expr: &Expr, body: &Block, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) -> TVal {
+    let Block::Block(items) = body;
+    let Some((last, rest)) = items.split_last() else {
+        return no_value();
+    };
+
+    for item in rest.iter() {
+        block_item(item, instructions, symbols);
+    }
+    let res = match last {
+        BlockItem::S(stmt) => last_stmt_val(stmt, instructions, symbols),
+        BlockItem::D(_) => {
+            block_item(last, instructions, symbols);
+            None
+        },
+    };
+
+    match res {
+        Some(res) if expr.get_type() != Type::Void => {
+            let dst = make_tacky_var("stmt_res", expr.get_type(), symbols);
+            instructions.push(TInstr::Copy(res, dst.clone()));
+            dst
+        },
+        _ => no_value(),
+    }
+}
+
+//Stands in for the result of an expression without a value, which the type checker never lets anything use
+fn no_value() -> TVal {
+    TVal::Constant(Const::Int(0))
+}
+
+//A labeled expression statement still gives the statement expression its value
+fn last_stmt_val(stmt: &Stmt, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) -> Option<TVal> {
+    match stmt {
+        Stmt::Expression(expr) => Some(expr_val(expr, instructions, symbols)),
+        Stmt::Labeled(label, body) => {
+            instructions.push(TInstr::Label(label.clone()));
+            last_stmt_val(body, instructions, symbols)
+        },
+        _ => {
+            stmt_val(stmt, instructions, symbols);
+            None
+        },
+    }
+}
+
+//The hint given to __builtin_expect is evaluated but otherwise ignored
+fn builtin_val(builtin: &Builtin, args: &[Expr], instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) -> TVal {
+    match builtin {
+        Builtin::Expect => {
+            let res = expr_val(&args[0], instructions, symbols);
+            let _ = expr_val(&args[1], instructions, symbols);
+            res
+        },
+        Builtin::Trap => {
+            instructions.push(TInstr::Trap);
+            no_value()
+        },
+        Builtin::Unreachable => no_value(),
+        Builtin::ConstantP => TVal::Constant(Const::Int(0)),
+    }
+}
+
+fn lvalue(expr: &Expr, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) -> ExprResult {
+    match &expr.kind {
+        ExprKind::Dereference(inner) => ExprResult::DereferencedPointer(expr_val(inner, instructions, symbols)),
+        ExprKind::Subscript(array, index) => ExprResult::DereferencedPointer(subscript(array, index, instructions, symbols)),
+        ExprKind::Dot(inner, member) => {
+            let offset = member_offset(&inner.get_type(), member, symbols);
+            match lvalue(inner, instructions, symbols) {
+                ExprResult::Operand(TVal::Var(base)) => ExprResult::SubObject(base, offset),
+                ExprResult::Operand(TVal::Constant(_)) => panic!("Member access on a constant"),
+                ExprResult::SubObject(base, inner_offset) => ExprResult::SubObject(base, inner_offset + offset),
+                ExprResult::DereferencedPointer(ptr) => {
+                    let ptr_type = Type::Pointer(Box::from(expr.get_type()));
+                    ExprResult::DereferencedPointer(offset_pointer(ptr, offset, ptr_type, instructions, symbols))
+                },
             }
-            let res = expr_val(right, instructions);
-            instructions.push(TInstr::Copy(res, TVal::Var(v.clone())));
+        },
+        ExprKind::Arrow(inner, member) => {
+            let struct_type = match inner.get_type() {
+                Type::Pointer(referenced) => *referenced,
+                _ => panic!("Expected a pointer type"),
+            };
+            let offset = member_offset(&struct_type, member, symbols);
+            let ptr = expr_val(inner, instructions, symbols);
+            let ptr_type = Type::Pointer(Box::from(expr.get_type()));
+            ExprResult::DereferencedPointer(offset_pointer(ptr, offset, ptr_type, instructions, symbols))
+        },
+        _ => ExprResult::Operand(expr_val(expr, instructions, symbols)),
+    }
+}
+
+fn read_lvalue(lhs: &ExprResult, lhs_type: &Type, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) -> TVal {
+    match lhs {
+        ExprResult::Operand(val) => val.clone(),
+        ExprResult::SubObject(base, offset) => {
+            let dst = make_tacky_var("member", lhs_type.clone(), symbols);
+            instructions.push(TInstr::CopyFromOffset(base.clone(), *offset, dst.clone()));
+            dst
+        },
+        ExprResult::DereferencedPointer(ptr) => {
+            let dst = make_tacky_var("load", lhs_type.clone(), symbols);
+            instructions.push(TInstr::Load(ptr.clone(), dst.clone()));
+            dst
+        },
+    }
+}
 
-            TVal::Var(v)
+fn store_lvalue(lhs: ExprResult, res: TVal, instructions: &mut Vec<TInstr>) -> TVal {
+    match lhs {
+        ExprResult::Operand(var) => {
+            instructions.push(TInstr::Copy(res, var.clone()));
+            var
+        },
+        ExprResult::SubObject(base, offset) => {
+            instructions.push(TInstr::CopyToOffset(res.clone(), base, offset));
+            res
+        },
+        ExprResult::DereferencedPointer(ptr) => {
+            instructions.push(TInstr::Store(res.clone(), ptr));
+            res
         },
-        Expr::Conditional(left, middle, right) => conditional(left, middle, right, instructions),
     }
 }
 
-fn binary(op: &BinaryOp, left: &Box<Expr>, right: &Box<Expr>, instructions: &mut Vec<TInstr>) -> TVal {
+//Applies op to the current value of an lvalue, computing in the type of rhs, and stores the result back
+fn update_lvalue(lhs: ExprResult, lhs_type: &Type, op: &BinaryOp, rhs: TVal, rhs_type: &Type, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) -> TVal {
+    let current = read_lvalue(&lhs, lhs_type, instructions, symbols);
+
+    let res = if let Type::Pointer(_) = lhs_type {
+        let mut idx = rhs;
+        if let BinaryOp::Subtract = op {
+            let negated = make_tacky_var("neg_idx", Type::Long, symbols);
+            instructions.push(TInstr::Unary(TUnaryOp::Negate, idx, negated.clone()));
+            idx = negated;
+        }
+        let dst = make_tacky_var("ptr_arith", lhs_type.clone(), symbols);
+        instructions.push(TInstr::AddPtr(current, idx, referenced_size(lhs_type, symbols), dst.clone()));
+        dst
+    } else {
+        let converted = convert_val(current, lhs_type, rhs_type, instructions, symbols);
+        let dst = make_tacky_var("temp", rhs_type.clone(), symbols);
+        instructions.push(TInstr::Binary(get_binary_op(op), converted, rhs, dst.clone()));
+        convert_val(dst, rhs_type, lhs_type, instructions, symbols)
+    };
+
+    store_lvalue(lhs, res, instructions)
+}
+
+//The constant one that ++ and -- add to a value of the given type, along with the type it is added in
+fn unit_step(val_type: &Type) -> (TVal, Type) {
+    match val_type {
+        Type::Pointer(_) | Type::Long => (TVal::Constant(Const::Long(1)), Type::Long),
+        Type::UInt => (TVal::Constant(Const::UInt(1)), Type::UInt),
+        Type::ULong => (TVal::Constant(Const::ULong(1)), Type::ULong),
+        Type::Double => (TVal::Constant(Const::Double(1.0)), Type::Double),
+        _ => (TVal::Constant(Const::Int(1)), Type::Int),
+    }
+}
+
+fn member_offset(struct_type: &Type, member: &str, symbols: &SymbolTable) -> i32 {
+    match struct_type.unqualified() {
+        Type::Structure(tag) | Type::Union(tag) => find_member(tag, member, symbols).offset,
+        _ => panic!("Expected a structure type"),
+    }
+}
+
+fn offset_pointer(ptr: TVal, offset: i32, ptr_type: Type, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) -> TVal {
+    if offset == 0 {
+        return ptr;
+    }
+
+    let dst = make_tacky_var("member_ptr", ptr_type, symbols);
+    let idx = TVal::Constant(Const::Long(offset as i64));
+    instructions.push(TInstr::AddPtr(ptr, idx, 1, dst.clone()));
+
+    dst
+}
+
+fn subscript(array: &Expr, index: &Expr, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) -> TVal {
+    let ptr = expr_val(array, instructions, symbols);
+    let idx = expr_val(index, instructions, symbols);
+    let dst = make_tacky_var("elem_ptr", array.get_type(), symbols);
+    instructions.push(TInstr::AddPtr(ptr, idx, referenced_size(&array.get_type(), symbols), dst.clone()));
+
+    dst
+}
+
+fn referenced_size(ptr_type: &Type, symbols: &SymbolTable) -> i32 {
+    match ptr_type {
+        Type::Pointer(referenced) => type_size(referenced, symbols),
+        _ => panic!("Expected a pointer type"),
+    }
+}
+
+fn cast(target: &Type, inner: &Expr, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) -> TVal {
+    let src = expr_val(inner, instructions, symbols);
+    convert_val(src, &inner.get_type(), target, instructions, symbols)
+}
+
+fn convert_val(src: TVal, inner_type: &Type, target: &Type, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) -> TVal {
+    if target == inner_type {
+        return src;
+    }
+
+    let dst = make_tacky_var("cast", target.clone(), symbols);
+    //Converting to _Bool compares against zero instead of truncating, so 256 becomes 1 rather than 0
+    if *target == Type::Bool {
+        let zero = match inner_type {
+            Type::Double => Const::Double(0.0),
+            Type::Pointer(_) | Type::Long | Type::ULong => Const::Long(0),
+            _ => Const::Int(0),
+        };
+        instructions.push(TInstr::Binary(TBinaryOp::NotEqual, src, TVal::Constant(zero), dst.clone()));
+    } else if *target == Type::Double && inner_type.is_signed() {
+        instructions.push(TInstr::IntToDouble(src, dst.clone()));
+    } else if *target == Type::Double {
+        instructions.push(TInstr::UIntToDouble(src, dst.clone()));
+    } else if *inner_type == Type::Double && target.is_signed() {
+        instructions.push(TInstr::DoubleToInt(src, dst.clone()));
+    } else if *inner_type == Type::Double {
+        instructions.push(TInstr::DoubleToUInt(src, dst.clone()));
+    } else if target.size() == inner_type.size() {
+        instructions.push(TInstr::Copy(src, dst.clone()));
+    } else if target.size() < inner_type.size() {
+        instructions.push(TInstr::Truncate(src, dst.clone()));
+    } else if inner_type.is_signed() {
+        instructions.push(TInstr::SignExtend(src, dst.clone()));
+    } else {
+        instructions.push(TInstr::ZeroExtend(src, dst.clone()));
+    }
+
+    dst
+}
+
+fn binary(expr: &Expr, op: &BinaryOp, left: &Expr, right: &Expr, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) -> TVal {
     match op {
-        BinaryOp::And | BinaryOp::Or => and_or(op, left, right, instructions),
-        _ => binary_normal(op, left, right, instructions),
+        BinaryOp::And | BinaryOp::Or => and_or(op, left, right, instructions, symbols),
+        BinaryOp::Add | BinaryOp::Subtract if matches!(expr.get_type(), Type::Pointer(_)) =>
+            pointer_arithmetic(expr, op, left, right, instructions, symbols),
+        BinaryOp::Subtract if matches!(left.get_type(), Type::Pointer(_)) =>
+            pointer_difference(left, right, instructions, symbols),
+        _ => binary_normal(expr, op, left, right, instructions, symbols),
     }
 }
 
-fn and_or(op: &BinaryOp, left: &Box<Expr>, right: &Box<Expr>, instructions: &mut Vec<TInstr>) -> TVal {
-    let result_name = make_temp("and_or_result", instructions.len());
-    let result = TVal::Var(result_name);
+fn pointer_arithmetic(expr: &Expr, op: &BinaryOp, left: &Expr, right: &Expr, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) -> TVal {
+    let v1 = expr_val(left, instructions, symbols);
+    let v2 = expr_val(right, instructions, symbols);
+    let (ptr, mut idx) = match left.get_type() {
+        Type::Pointer(_) => (v1, v2),
+        _ => (v2, v1),
+    };
+
+    if let BinaryOp::Subtract = op {
+        let negated = make_tacky_var("neg_idx", Type::Long, symbols);
+        instructions.push(TInstr::Unary(TUnaryOp::Negate, idx, negated.clone()));
+        idx = negated;
+    }
+
+    let dst = make_tacky_var("ptr_arith", expr.get_type(), symbols);
+    instructions.push(TInstr::AddPtr(ptr, idx, referenced_size(&expr.get_type(), symbols), dst.clone()));
+
+    dst
+}
+
+fn pointer_difference(left: &Expr, right: &Expr, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) -> TVal {
+    let v1 = expr_val(left, instructions, symbols);
+    let v2 = expr_val(right, instructions, symbols);
+    let diff = make_tacky_var("ptr_diff", Type::Long, symbols);
+    instructions.push(TInstr::Binary(TBinaryOp::Subtract, v1, v2, diff.clone()));
+
+    let scale = TVal::Constant(Const::Long(referenced_size(&left.get_type(), symbols) as i64));
+    let dst = make_tacky_var("temp", Type::Long, symbols);
+    instructions.push(TInstr::Binary(TBinaryOp::Divide, diff, scale, dst.clone()));
+
+    dst
+}
+
+fn and_or(op: &BinaryOp, left: &Expr, right: &Expr, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) -> TVal {
+    let result = make_tacky_var("and_or_result", Type::Int, symbols);
     let short_cond;
     let final_val;
 
     match op {
         BinaryOp::And => {
-            let v1 = expr_val(left, instructions);
-            short_cond = make_temp("short_cond", instructions.len());
+            let v1 = expr_val(left, instructions, symbols);
+            short_cond = make_temp("short_cond");
             instructions.push(TInstr::JumpIfZero(v1, short_cond.clone()));
-            let v2 = expr_val(right, instructions);
+            let v2 = expr_val(right, instructions, symbols);
             instructions.push(TInstr::JumpIfZero(v2,  short_cond.clone()));
-            instructions.push(TInstr::Copy(TVal::Constant(1), result.clone()));
+            instructions.push(TInstr::Copy(TVal::Constant(Const::Int(1)), result.clone()));
             final_val = 0;
         },
         BinaryOp::Or => {
-            let v1 = expr_val(left, instructions);
-            short_cond = make_temp("short_cond", instructions.len());
+            let v1 = expr_val(left, instructions, symbols);
+            short_cond = make_temp("short_cond");
             instructions.push(TInstr::JumpIfNotZero(v1, short_cond.clone()));
-            let v2 = expr_val(right, instructions);
+            let v2 = expr_val(right, instructions, symbols);
             instructions.push(TInstr::JumpIfNotZero(v2, short_cond.clone()));
-            instructions.push(TInstr::Copy(TVal::Constant(0), result.clone()));
+            instructions.push(TInstr::Copy(TVal::Constant(Const::Int(0)), result.clone()));
             final_val = 1;
         },
         _ => panic!(), //Unreachable
     }
 
-    let jmp_cond = make_temp("jmp_cond", instructions.len());
+    let jmp_cond = make_temp("jmp_cond");
     instructions.push(TInstr::Jump(jmp_cond.clone()));
     instructions.push(TInstr::Label(short_cond));
-    instructions.push(TInstr::Copy(TVal::Constant(final_val), result.clone()));
+    instructions.push(TInstr::Copy(TVal::Constant(Const::Int(final_val)), result.clone()));
     instructions.push(TInstr::Label(jmp_cond));
 
     result
 }
 
-fn binary_normal(op: &BinaryOp, left: &Box<Expr>, right: &Box<Expr>, instructions: &mut Vec<TInstr>) -> TVal {
-    let v1 = expr_val(left, instructions);
-    let v2 = expr_val(right, instructions);
-    let dst_name = make_temp("temp", instructions.len());
-    let dst = TVal::Var(dst_name);
+fn binary_normal(expr: &Expr, op: &BinaryOp, left: &Expr, right: &Expr, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) -> TVal {
+    let v1 = expr_val(left, instructions, symbols);
+    let v2 = expr_val(right, instructions, symbols);
+    let dst = make_tacky_var("temp", expr.get_type(), symbols);
     let tacky_op = get_binary_op(op);
     instructions.push(TInstr::Binary(tacky_op, v1, v2, dst.clone()));
 
     dst
 }
 
-fn conditional(left: &Box<Expr>, middle: &Box<Expr>, right: &Box<Expr>, instructions: &mut Vec<TInstr>) -> TVal {
-    let res_name = make_temp("cond_res", instructions.len());
-    let final_res = TVal::Var(res_name);
+fn conditional(expr: &Expr, left: &Expr, middle: &Expr, right: &Expr, instructions: &mut Vec<TInstr>, symbols: &mut SymbolTable) -> TVal {
+    //When both branches have no value there is nothing to copy
+    let final_res = match expr.get_type() {
+        Type::Void => None,
+        cond_type => Some(make_tacky_var("cond_res", cond_type, symbols)),
+    };
 
-    let res = expr_val(left, instructions);
-    let e2 = make_temp("e2", instructions.len());
-    let end = make_temp("cond_end", instructions.len());
+    let res = expr_val(left, instructions, symbols);
+    let e2 = make_temp("e2");
+    let end = make_temp("cond_end");
     instructions.push(TInstr::JumpIfZero(res, e2.clone()));
-    let v1 = expr_val(middle, instructions);
-    instructions.push(TInstr::Copy(v1, final_res.clone()));
+    let v1 = expr_val(middle, instructions, symbols);
+    if let Some(final_res) = &final_res {
+        instructions.push(TInstr::Copy(v1, final_res.clone()));
+    }
     instructions.push(TInstr::Jump(end.clone()));
     instructions.push(TInstr::Label(e2));
-    let v2 = expr_val(right, instructions);
-    instructions.push(TInstr::Copy(v2, final_res.clone()));
+    let v2 = expr_val(right, instructions, symbols);
+    if let Some(final_res) = &final_res {
+        instructions.push(TInstr::Copy(v2, final_res.clone()));
+    }
     instructions.push(TInstr::Label(end));
 
-    final_res
+    final_res.unwrap_or_else(no_value)
 }
 
 fn get_unary_op(op: &UnaryOp) -> TUnaryOp {
@@ -204,6 +791,11 @@ fn get_binary_op(op: &BinaryOp) -> TBinaryOp {
         BinaryOp::Multiply => TBinaryOp::Multiply,
         BinaryOp::Divide => TBinaryOp::Divide,
         BinaryOp::Remainder => TBinaryOp::Remainder,
+        BinaryOp::BitAnd => TBinaryOp::BitAnd,
+        BinaryOp::BitOr => TBinaryOp::BitOr,
+        BinaryOp::BitXor => TBinaryOp::BitXor,
+        BinaryOp::ShiftLeft => TBinaryOp::ShiftLeft,
+        BinaryOp::ShiftRight => TBinaryOp::ShiftRight,
         BinaryOp::Equal => TBinaryOp::Equal,
         BinaryOp::NotEqual => TBinaryOp::NotEqual,
         BinaryOp::LessThan => TBinaryOp::LessThan,
@@ -214,6 +806,14 @@ fn get_binary_op(op: &BinaryOp) -> TBinaryOp {
     }
 }
 
-fn make_temp(start: &str, ident: usize) -> String {
+fn make_tacky_var(start: &str, var_type: Type, symbols: &mut SymbolTable) -> TVal {
+    let name = make_temp(start);
+    symbols.insert(name.clone(), Symbol { sym_type: var_type, attrs: IdentAttrs::Local });
+
+    TVal::Var(name)
+}
+
+fn make_temp(start: &str) -> String {
+    let ident = TEMP_COUNTER.fetch_add(1, Ordering::Relaxed);
     format!("{}.{}", start, ident)
 }
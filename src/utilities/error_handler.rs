@@ -1,7 +1,7 @@
 
 use std::process;
 
-use crate::utilities::file_cleanup::*;
+use crate::lexer::lexer_structs::Location;
 
 pub fn emission_error(msg: &str) -> ! {
     println!("Emission Error:\n{}", msg);
@@ -15,8 +15,8 @@ pub fn parser_error_no_line(msg: &str) -> ! {
     process::exit(1);
 }
 
-pub fn parser_error(line: u32, msg: &str) -> ! {
-    println!("Parser Error:\nLine {}: {}", line, msg);
+pub fn parser_error(location: &Location, msg: &str) -> ! {
+    println!("Parser Error:\n{}:{}: {}", location.file, location.line, msg);
 
     process::exit(1);
 }
@@ -27,12 +27,21 @@ pub fn resolver_error(msg: &str) -> ! {
     process::exit(1);
 }
 
-pub fn fmt_lexer_error(line: u32, msg: &str) -> String {
-    format!("Lexer Error:\nLine {}: {}", line, msg)
+pub fn type_error(msg: &str) -> ! {
+    println!("Type Error:\n{}", msg);
+
+    process::exit(1);
+}
+
+pub fn fmt_lexer_error(file: &str, line: u32, msg: &str) -> String {
+    format!("Lexer Error:\n{}:{}: {}", file, line, msg)
+}
+
+pub fn fmt_preprocessor_error(file: &str, line: u32, msg: &str) -> String {
+    format!("Preprocessor Error:\n{}:{}: {}", file, line, msg)
 }
 
-pub fn error_and_clean(msg: &str, file: &str) -> ! {
-    clean_file(file);
+pub fn stage_error(msg: &str) -> ! {
     println!("{}", msg);
 
     process::exit(1);
@@ -2,5 +2,5 @@
 use std::fs;
 
 pub fn clean_file(name: &str) {
-    let _ = fs::remove_file(name.to_string());
+    let _ = fs::remove_file(name);
 }
\ No newline at end of file
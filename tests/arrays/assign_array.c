@@ -0,0 +1,7 @@
+//expect error: Cannot assign to an array
+int main(void) {
+    int a[2] = {1, 2};
+    int b[2] = {3, 4};
+    a = b;
+    return 0;
+}
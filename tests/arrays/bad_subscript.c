@@ -0,0 +1,6 @@
+//expect error: Subscript requires a pointer and an integer operand
+int main(void) {
+    int a[2] = {1, 2};
+    double d = 1.0;
+    return a[d];
+}
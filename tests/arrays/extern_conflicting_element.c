@@ -0,0 +1,7 @@
+//expect error: Conflicting types for variable values
+extern int values[];
+long values[2];
+
+int main(void) {
+    return 0;
+}
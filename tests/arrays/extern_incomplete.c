@@ -0,0 +1,25 @@
+//link: extern_incomplete_lib.c
+//expect exit: 0
+//A header can declare an array without its size, and the definition completes it
+extern int values[];
+extern long defined_elsewhere[];
+
+int first(void) {
+    return values[0];
+}
+
+int values[3] = {1, 2, 3};
+extern int values[];
+
+int main(void) {
+    extern int values[];
+    int *p = values;
+    if (first() != 1 || p[2] != 3)
+        return 1;
+    values[1] = 5;
+    if (first() + p[1] != 6)
+        return 2;
+    if (defined_elsewhere[0] != 10 || defined_elsewhere[3] != 40)
+        return 3;
+    return 0;
+}
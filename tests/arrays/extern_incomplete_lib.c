@@ -0,0 +1 @@
+long defined_elsewhere[4] = {10, 20, 30, 40};
@@ -0,0 +1,14 @@
+//link: gcc_abi_lib.c
+//expect exit: 0
+long total(long *values, int count);
+int numbers[3] = {10, 20, 30};
+int middle(void);
+
+int main(void) {
+    long values[3] = {100, 200, 300};
+    if (total(values, 3) != 600)
+        return 1;
+    if (middle() != 20)
+        return 2;
+    return 0;
+}
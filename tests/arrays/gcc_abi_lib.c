@@ -0,0 +1,12 @@
+extern int numbers[3];
+
+long total(long *values, int count) {
+    long sum = 0;
+    for (int i = 0; i < count; i++)
+        sum += values[i];
+    return sum;
+}
+
+int middle(void) {
+    return numbers[1];
+}
@@ -0,0 +1,17 @@
+//expect exit: 0
+int global[4] = {1, 2};
+double doubles[] = {1.5, 2.5, 3.5};
+
+int main(void) {
+    int partial[5] = {7, 8};
+    if (partial[1] != 8 || partial[4] != 0)
+        return 1;
+    if (global[1] != 2 || global[3] != 0)
+        return 2;
+    if (doubles[0] != 1.5 || doubles[2] != 3.5)
+        return 3;
+    static int nested[2][2] = {{1}, {3, 4}};
+    if (nested[0][1] != 0 || nested[1][0] != 3)
+        return 4;
+    return 0;
+}
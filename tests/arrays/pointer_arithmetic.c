@@ -0,0 +1,24 @@
+//expect exit: 0
+int sum(int *values, int count) {
+    int total = 0;
+    for (int *p = values; p < values + count; p = p + 1)
+        total = total + *p;
+    return total;
+}
+
+int main(void) {
+    int values[4] = {1, 2, 3, 4};
+    if (sum(values, 4) != 10)
+        return 1;
+    int *end = values + 4;
+    if (end - values != 4)
+        return 2;
+    int *second = &values[1];
+    if (*(second + 2) != 4 || second[-1] != 1)
+        return 3;
+    long rows[3][2] = {{0, 1}, {2, 3}, {4, 5}};
+    long (*row)[2] = rows + 2;
+    if ((*row)[1] != 5 || row - rows != 2)
+        return 4;
+    return 0;
+}
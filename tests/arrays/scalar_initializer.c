@@ -0,0 +1,5 @@
+//expect error: Cannot initialize an array with a scalar
+int main(void) {
+    int values[2] = 5;
+    return 0;
+}
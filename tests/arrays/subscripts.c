@@ -0,0 +1,12 @@
+//expect exit: 0
+int main(void) {
+    int numbers[5];
+    for (int i = 0; i < 5; i = i + 1)
+        numbers[i] = i * i;
+    if (numbers[4] != 16 || 3[numbers] != 9)
+        return 1;
+    long grid[2][3] = {{1, 2, 3}, {4, 5, 6}};
+    if (grid[1][2] != 6 || grid[0][1] != 2)
+        return 2;
+    return 0;
+}
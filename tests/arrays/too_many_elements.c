@@ -0,0 +1,5 @@
+//expect error: Too many elements in array initializer
+int main(void) {
+    int values[2] = {1, 2, 3};
+    return 0;
+}
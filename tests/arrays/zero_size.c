@@ -0,0 +1,5 @@
+//expect error: Array size must be greater than zero
+int main(void) {
+    int values[0];
+    return 0;
+}
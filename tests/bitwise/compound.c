@@ -0,0 +1,24 @@
+//expect exit: 0
+int main(void) {
+    int flags = 0;
+    flags |= 5;
+    flags &= 6;
+    flags ^= 3;
+    if (flags != 7)
+        return 1;
+    flags <<= 2;
+    if (flags != 28)
+        return 2;
+    flags >>= 3;
+    if (flags != 3)
+        return 3;
+    unsigned char byte = 1;
+    byte <<= 9;
+    if (byte != 0)
+        return 4;
+    long wide = 1;
+    wide <<= 35;
+    if (wide != 34359738368L)
+        return 5;
+    return 0;
+}
@@ -0,0 +1,6 @@
+//expect error: Bitwise operator applied to a double
+int main(void) {
+    double d = 1.0;
+    d |= 2;
+    return 0;
+}
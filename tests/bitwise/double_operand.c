@@ -0,0 +1,5 @@
+//expect error: Bitwise operator applied to a double
+int main(void) {
+    double d = 1.0;
+    return d & 1;
+}
@@ -0,0 +1,5 @@
+//expect error: Shift operands must be integers
+int main(void) {
+    int i = 1;
+    return i << 1.0;
+}
@@ -0,0 +1,21 @@
+//expect exit: 0
+int main(void) {
+    if ((12 & 10) != 8 || (12 | 10) != 14 || (12 ^ 10) != 6 || ~0 != -1)
+        return 1;
+    if ((1 << 4) != 16 || (256 >> 3) != 32)
+        return 2;
+    //Right shifts of signed values are arithmetic and of unsigned values are logical
+    if ((-16 >> 2) != -4)
+        return 3;
+    if ((4294967280U >> 2) != 1073741820U)
+        return 4;
+    long l = 1L << 40;
+    if (l >> 38 != 4)
+        return 5;
+    //Operands are converted to a common type like arithmetic operators
+    if ((-1 & 4294967295U) != 4294967295U || (1L | 4294967296L) != 4294967297L)
+        return 6;
+    if ((1034834473200L & 1095233372415L) != 1030807879920L)
+        return 7;
+    return 0;
+}
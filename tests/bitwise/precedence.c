@@ -0,0 +1,13 @@
+//expect exit: 0
+int main(void) {
+    //Shifts bind tighter than comparisons, which bind tighter than &, ^ and |
+    if ((1 | 2 ^ 3 & 4) != 3)
+        return 1;
+    if ((1 << 2 + 1) != 8)
+        return 2;
+    if ((5 & 4 == 4) != 1)
+        return 3;
+    if ((2 < 3 | 8) != 9)
+        return 4;
+    return 0;
+}
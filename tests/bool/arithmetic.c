@@ -0,0 +1,24 @@
+//expect exit: 0
+int main(void) {
+    _Bool t = 1;
+    //Arithmetic promotes to int
+    if (t + t != 2 || -t != -1 || ~t != -2)
+        return 1;
+    if (sizeof(_Bool) != 1 || sizeof t != 1 || sizeof(t + t) != 4)
+        return 2;
+    _Bool b = 0;
+    b++;
+    b++;
+    if (b != 1)
+        return 3;
+    b += 5;
+    if (b != 1)
+        return 4;
+    b -= 1;
+    if (b != 0)
+        return 5;
+    _Bool flags[4] = {1, 0, 3, 0};
+    if (flags[2] != 1 || sizeof flags != 4)
+        return 6;
+    return 0;
+}
@@ -0,0 +1,5 @@
+//expect error: Expected ';'
+int main(void) {
+    bool b = 1;
+    return b;
+}
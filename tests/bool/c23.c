@@ -0,0 +1,19 @@
+//args: -std=c23
+//expect exit: 0
+#if __STDC_VERSION__ < 202311L || !true || false
+#error C23 is not in effect
+#endif
+
+bool negate(bool b) {
+    return !b;
+}
+
+int main(void) {
+    bool b = 42;
+    if (b != true || negate(b) != false)
+        return 1;
+    //true and false have type bool
+    if (sizeof true != 1 || true + true != 2)
+        return 2;
+    return 0;
+}
@@ -0,0 +1,8 @@
+//args: -std=c23
+//expect exit: 0
+#include <stdbool.h>
+
+int main(void) {
+    bool b = true;
+    return !b;
+}
@@ -0,0 +1,28 @@
+//expect exit: 0
+_Bool global = 256;
+_Bool from_double = 0.5;
+
+_Bool to_bool(long l) {
+    return l;
+}
+
+int main(void) {
+    //Any nonzero scalar converts to 1, even when its low byte is zero
+    if (global != 1 || from_double != 1 || to_bool(4294967296L) != 1 || to_bool(0) != 0)
+        return 1;
+    _Bool b = -0.0;
+    if (b != 0)
+        return 2;
+    int x = 5;
+    b = &x;
+    if (b != 1)
+        return 3;
+    b = (_Bool) 2 + (_Bool) 3;
+    if (b != 1)
+        return 4;
+    double nan = 0.0 / 0.0;
+    b = nan;
+    if (b != 1)
+        return 5;
+    return 0;
+}
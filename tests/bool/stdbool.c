@@ -0,0 +1,17 @@
+//expect exit: 0
+#include <stdbool.h>
+
+bool is_even(int n) {
+    return n % 2 == 0;
+}
+
+int main(void) {
+    bool b = 10;
+    if (b != true || is_even(3) != false || !is_even(4))
+        return 1;
+#if true && !false
+    return 0;
+#else
+    return 2;
+#endif
+}
@@ -0,0 +1,8 @@
+//expect error: Cannot convert type for assignment
+struct point { int x; };
+
+int main(void) {
+    struct point p = { 1 };
+    _Bool b = p;
+    return b;
+}
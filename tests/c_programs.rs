@@ -0,0 +1,258 @@
+use std::fs;
+use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+//Every C file in a directory under tests/ starts with comments saying what should happen to it:
+//  //args: <flags passed to glasses>
+//  //link: <C file compiled by gcc and linked with the program>
+//  //expect exit: <exit status of the program, or 128 plus the signal that killed it like a shell reports>
+//  //expect output: <one line of the program's standard output>
+//  //expect error: <text the compiler's diagnostic contains>
+//Files without an expect comment, such as headers and link partners, are only copied alongside the tests
+#[derive(Debug)]
+struct Expectation {
+    args: Vec<String>,
+    links: Vec<String>,
+    exit: i32,
+    output: String,
+    error: Option<String>,
+}
+
+fn parse_expectation(text: &str) -> Option<Expectation> {
+    let mut expectation = Expectation { args: Vec::new(), links: Vec::new(), exit: 0, output: String::new(), error: None };
+    let mut found = false;
+
+    for line in text.lines().take_while(|line| line.starts_with("//")) {
+        if let Some(args) = line.strip_prefix("//args:") {
+            expectation.args.extend(args.split_whitespace().map(String::from));
+        } else if let Some(link) = line.strip_prefix("//link:") {
+            expectation.links.push(link.trim().to_string());
+        } else if let Some(exit) = line.strip_prefix("//expect exit:") {
+            expectation.exit = exit.trim().parse().expect("Malformed exit status");
+            found = true;
+        } else if let Some(output) = line.strip_prefix("//expect output:") {
+            expectation.output.push_str(output.strip_prefix(' ').unwrap_or(output));
+            expectation.output.push('\n');
+            found = true;
+        } else if let Some(error) = line.strip_prefix("//expect error:") {
+            expectation.error = Some(error.trim().to_string());
+            found = true;
+        }
+    }
+
+    if found { Some(expectation) } else { None }
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+    for entry in fs::read_dir(from).unwrap() {
+        let path = entry.unwrap().path();
+        let target = to.join(path.file_name().unwrap());
+        if path.is_dir() {
+            copy_dir(&path, &target);
+        } else {
+            fs::copy(&path, &target).unwrap();
+        }
+    }
+}
+
+fn run_file(file: &Path, expectation: &Expectation) -> Result<(), String> {
+    //Programs linked with gcc objects stop after writing assembly, and gcc links them here instead.
+    //The compiler runs in the test's directory so -I paths can be relative
+    let mut compiler = Command::new(env!("CARGO_BIN_EXE_glasses"));
+    compiler.current_dir(file.parent().unwrap()).args(&expectation.args);
+    if !expectation.links.is_empty() {
+        compiler.arg("--codegen");
+    }
+    let compiled = compiler.arg(file).output().unwrap();
+    let diagnostic = String::from_utf8_lossy(&compiled.stdout);
+
+    if let Some(error) = &expectation.error {
+        return match compiled.status.success() {
+            true => Err(format!("compiled, but expected an error containing \"{}\"", error)),
+            false if !diagnostic.contains(error.as_str()) =>
+                Err(format!("expected an error containing \"{}\", got:\n{}", error, diagnostic)),
+            false => Ok(()),
+        };
+    }
+    if !compiled.status.success() {
+        return Err(format!("failed to compile:\n{}", diagnostic));
+    }
+
+    let executable = file.with_extension("");
+    if !expectation.links.is_empty() {
+        let dir = file.parent().unwrap();
+        let linked = Command::new("gcc")
+            .arg(file.with_extension("s"))
+            .args(expectation.links.iter().map(|link| dir.join(link)))
+            .arg("-o")
+            .arg(&executable)
+            .output().unwrap();
+        if !linked.status.success() {
+            return Err(format!("failed to link:\n{}", String::from_utf8_lossy(&linked.stderr)));
+        }
+    }
+
+    let run = Command::new(&executable).output().unwrap();
+    let output = String::from_utf8_lossy(&run.stdout);
+    let code = run.status.code().unwrap_or_else(|| 128 + run.status.signal().unwrap_or(0));
+    match code == expectation.exit && output == expectation.output {
+        true => Ok(()),
+        false => Err(format!("expected exit {} and output {:?}, got exit {} and output {:?}",
+            expectation.exit, expectation.output, code, output)),
+    }
+}
+
+//Compiles and checks every test program in tests/<dir>, reporting all failures together
+fn run_dir(dir: &str) {
+    let source = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join(dir);
+    let work = Path::new(env!("CARGO_TARGET_TMPDIR")).join(dir);
+    let _ = fs::remove_dir_all(&work);
+    copy_dir(&source, &work);
+
+    let mut files: Vec<PathBuf> = fs::read_dir(&work).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "c"))
+        .collect();
+    files.sort();
+
+    let mut failures: Vec<String> = Vec::new();
+    let mut count = 0;
+    for file in files {
+        let text = fs::read_to_string(&file).unwrap();
+        let Some(expectation) = parse_expectation(&text) else { continue };
+        count += 1;
+        if let Err(msg) = run_file(&file, &expectation) {
+            failures.push(format!("{}: {}", file.file_name().unwrap().to_string_lossy(), msg));
+        }
+    }
+
+    assert!(count > 0, "No test programs in tests/{}", dir);
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+#[test]
+fn loops() {
+    run_dir("loops");
+}
+
+#[test]
+fn functions() {
+    run_dir("functions");
+}
+
+#[test]
+fn file_scope() {
+    run_dir("file_scope");
+}
+
+#[test]
+fn long() {
+    run_dir("long");
+}
+
+#[test]
+fn unsigned() {
+    run_dir("unsigned");
+}
+
+#[test]
+fn double() {
+    run_dir("double");
+}
+
+#[test]
+fn pointers() {
+    run_dir("pointers");
+}
+
+#[test]
+fn arrays() {
+    run_dir("arrays");
+}
+
+#[test]
+fn chars() {
+    run_dir("chars");
+}
+
+#[test]
+fn structs() {
+    run_dir("structs");
+}
+
+#[test]
+fn unions() {
+    run_dir("unions");
+}
+
+#[test]
+fn switch() {
+    run_dir("switch");
+}
+
+#[test]
+fn goto() {
+    run_dir("goto");
+}
+
+#[test]
+fn compound_assignment() {
+    run_dir("compound_assignment");
+}
+
+#[test]
+fn bitwise() {
+    run_dir("bitwise");
+}
+
+#[test]
+fn sizeof() {
+    run_dir("sizeof");
+}
+
+#[test]
+fn enums() {
+    run_dir("enums");
+}
+
+#[test]
+fn typedef() {
+    run_dir("typedef");
+}
+
+#[test]
+fn declarations() {
+    run_dir("declarations");
+}
+
+#[test]
+fn integer_literals() {
+    run_dir("integer_literals");
+}
+
+#[test]
+fn preprocessor() {
+    run_dir("preprocessor");
+}
+
+#[test]
+fn bool() {
+    run_dir("bool");
+}
+
+#[test]
+fn static_assert() {
+    run_dir("static_assert");
+}
+
+#[test]
+fn const_qualifier() {
+    run_dir("const");
+}
+
+#[test]
+fn statement_expressions() {
+    run_dir("statement_expressions");
+}
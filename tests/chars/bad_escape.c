@@ -0,0 +1,4 @@
+//expect error: Invalid escape sequence
+int main(void) {
+    return '\q';
+}
@@ -0,0 +1,20 @@
+//expect exit: 0
+char global[6] = "abc";
+char *pointer = "xyz";
+
+int main(void) {
+    char exact[3] = "abc";
+    char sized[] = "hello";
+    if (global[2] != 'c' || global[3] != 0 || global[5] != 0)
+        return 1;
+    if (exact[2] != 'c')
+        return 2;
+    if (sized[4] != 'o' || sized[5] != 0)
+        return 3;
+    if (pointer[1] != 'y' || "literal"[3] != 'e')
+        return 4;
+    sized[0] = 'j';
+    if (sized[0] != 'j')
+        return 5;
+    return 0;
+}
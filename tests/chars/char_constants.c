@@ -0,0 +1,11 @@
+//expect exit: 0
+int main(void) {
+    if ('a' != 97 || '\n' != 10 || '\0' != 0 || '\\' != 92 || '\'' != 39)
+        return 1;
+    if ('\x41' != 65 || '\101' != 65)
+        return 2;
+    //High bytes are sign extended like a plain char
+    if ('\xff' != -1)
+        return 3;
+    return 0;
+}
@@ -0,0 +1,21 @@
+//expect exit: 0
+int main(void) {
+    char c = 200;
+    signed char s = -3;
+    unsigned char u = 255;
+    if (c != -56)
+        return 1;
+    if (s * 2 != -6)
+        return 2;
+    u = u + 1;
+    if (u != 0)
+        return 3;
+    unsigned char big = 250;
+    int promoted = big + big;
+    if (promoted != 500)
+        return 4;
+    long from_char = s;
+    if (from_char != -3L)
+        return 5;
+    return 0;
+}
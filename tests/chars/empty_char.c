@@ -0,0 +1,4 @@
+//expect error: Empty character constant
+int main(void) {
+    return '';
+}
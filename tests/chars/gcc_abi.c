@@ -0,0 +1,12 @@
+//link: gcc_abi_lib.c
+//expect exit: 0
+char shift(char c, unsigned char amount);
+int length(char *s);
+
+int main(void) {
+    if (shift('a', 2) != 'c')
+        return 1;
+    if (length("four") != 4)
+        return 2;
+    return 0;
+}
@@ -0,0 +1,10 @@
+char shift(char c, unsigned char amount) {
+    return c + amount;
+}
+
+int length(char *s) {
+    int n = 0;
+    while (s[n])
+        n++;
+    return n;
+}
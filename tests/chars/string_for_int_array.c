@@ -0,0 +1,5 @@
+//expect error: Cannot initialize a non-character array with a string literal
+int main(void) {
+    int values[4] = "abc";
+    return 0;
+}
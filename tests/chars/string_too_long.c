@@ -0,0 +1,5 @@
+//expect error: Too many characters in string literal initializer
+int main(void) {
+    char word[3] = "long";
+    return 0;
+}
@@ -0,0 +1,17 @@
+//expect output: Hello, world!
+//expect output: tab	"quoted"
+int putchar(int c);
+
+int print(char *s) {
+    while (*s) {
+        putchar(*s);
+        s = s + 1;
+    }
+    return putchar('\n');
+}
+
+int main(void) {
+    print("Hello, " "world!");
+    print("tab\t\"quoted\"");
+    return 0;
+}
@@ -0,0 +1,5 @@
+//expect error: Unterminated string literal
+int main(void) {
+    char *s = "open;
+    return 0;
+}
@@ -0,0 +1,6 @@
+//expect error: Cannot assign to an array
+int main(void) {
+    int a[2] = {1, 2};
+    a += 1;
+    return 0;
+}
@@ -0,0 +1,35 @@
+//expect exit: 0
+int main(void) {
+    int i = 10;
+    i += 5;
+    i -= 3;
+    i *= 4;
+    i /= 6;
+    i %= 5;
+    if (i != 3)
+        return 1;
+    long l = 1;
+    l += 4294967296L;
+    if (l != 4294967297L)
+        return 2;
+    double d = 1.5;
+    d *= 3;
+    d -= 0.5;
+    if (d != 4.0)
+        return 3;
+    //The right side is converted to the left side's type after the operation
+    int truncated = 7;
+    truncated *= 1.5;
+    if (truncated != 10)
+        return 4;
+    unsigned char c = 250;
+    c += 10;
+    if (c != 4)
+        return 5;
+    int chained = 1;
+    int other = 2;
+    chained += other += 3;
+    if (chained != 6 || other != 5)
+        return 6;
+    return 0;
+}
@@ -0,0 +1,6 @@
+//expect error: Remainder operator applied to a double
+int main(void) {
+    double d = 5.0;
+    d %= 2;
+    return 0;
+}
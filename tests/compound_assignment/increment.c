@@ -0,0 +1,23 @@
+//expect exit: 0
+int main(void) {
+    int i = 5;
+    int post = i++;
+    int pre = ++i;
+    if (post != 5 || pre != 7 || i != 7)
+        return 1;
+    if (i-- != 7 || --i != 5)
+        return 2;
+    double d = 0.5;
+    d++;
+    if (d != 1.5 || --d != 0.5)
+        return 3;
+    char c = 127;
+    c++;
+    if (c != -128)
+        return 4;
+    unsigned long u = 0;
+    u--;
+    if (u != 18446744073709551615UL)
+        return 5;
+    return 0;
+}
@@ -0,0 +1,6 @@
+//expect error: Invalid lvalue
+int main(void) {
+    int x = 1;
+    (x + 1)++;
+    return 0;
+}
@@ -0,0 +1,41 @@
+//expect exit: 0
+struct counter {
+    int hits;
+    long total;
+};
+
+int calls = 0;
+
+int *next(int *p) {
+    calls++;
+    return p;
+}
+
+int main(void) {
+    int values[3] = {1, 2, 3};
+    int *p = values;
+    p++;
+    *p += 10;
+    p[1]++;
+    if (values[1] != 12 || values[2] != 4)
+        return 1;
+    p += 1;
+    if (*p-- != 4 || *p != 12)
+        return 2;
+    p -= 1;
+    if (p != values)
+        return 3;
+    //The left side is only evaluated once
+    *next(values) += 5;
+    (*next(values))++;
+    if (calls != 2 || values[0] != 7)
+        return 4;
+    struct counter c = { 0, 0 };
+    struct counter *cp = &c;
+    cp->hits++;
+    cp->total += 100;
+    ++c.hits;
+    if (c.hits != 2 || c.total != 100)
+        return 5;
+    return 0;
+}
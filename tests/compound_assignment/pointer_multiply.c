@@ -0,0 +1,7 @@
+//expect error: Invalid operands to compound assignment
+int main(void) {
+    int x = 1;
+    int *p = &x;
+    p *= 2;
+    return 0;
+}
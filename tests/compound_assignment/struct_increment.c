@@ -0,0 +1,8 @@
+//expect error: Invalid operand to increment or decrement
+struct point { int x; int y; };
+
+int main(void) {
+    struct point p = { 1, 2 };
+    p++;
+    return 0;
+}
@@ -0,0 +1,7 @@
+//expect error: Cannot assign to a const-qualified lvalue
+const int table[2] = {1, 2};
+
+int main(void) {
+    table[0] = 3;
+    return 0;
+}
@@ -0,0 +1,17 @@
+//expect error: Cannot assign to a structure or union with a const member
+struct inner {
+    const long key;
+};
+
+union outer {
+    struct inner items[2];
+    int raw;
+};
+
+int main(void) {
+    union outer a;
+    union outer b;
+    b.raw = 0;
+    a = b;
+    return 0;
+}
@@ -0,0 +1,12 @@
+//expect error: Cannot assign to a structure or union with a const member
+struct config {
+    const int id;
+    int uses;
+};
+
+int main(void) {
+    struct config a = { 1, 0 };
+    struct config b = { 2, 0 };
+    a = b;
+    return 0;
+}
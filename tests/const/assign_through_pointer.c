@@ -0,0 +1,7 @@
+//expect error: Cannot assign to a const-qualified lvalue
+int main(void) {
+    int x = 1;
+    const int *p = &x;
+    *p = 2;
+    return 0;
+}
@@ -0,0 +1,6 @@
+//expect error: Cannot assign to const variable limit
+int main(void) {
+    const int limit = 10;
+    limit = 11;
+    return 0;
+}
@@ -0,0 +1,11 @@
+//expect error: Cannot assign to a const-qualified lvalue
+struct config {
+    const int id;
+    int uses;
+};
+
+int main(void) {
+    struct config c = { 1, 0 };
+    c.id += 1;
+    return 0;
+}
@@ -0,0 +1,7 @@
+//expect error: Cannot assign to const variable p
+int main(void) {
+    int x = 1;
+    int *const p = &x;
+    p = 0;
+    return 0;
+}
@@ -0,0 +1,10 @@
+//expect error: Conversion discards const qualifier from pointer target type
+int change(int *p) {
+    *p = 0;
+    return 0;
+}
+
+int main(void) {
+    const int x = 1;
+    return change(&x);
+}
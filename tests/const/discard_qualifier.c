@@ -0,0 +1,6 @@
+//expect error: Conversion discards const qualifier from pointer target type
+int main(void) {
+    const int x = 1;
+    int *p = &x;
+    return *p;
+}
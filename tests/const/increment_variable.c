@@ -0,0 +1,7 @@
+//expect error: Cannot assign to const variable count
+const int count = 1;
+
+int main(void) {
+    count++;
+    return 0;
+}
@@ -0,0 +1,40 @@
+//expect exit: 0
+const int limit = 10;
+const char *const greeting = "hi";
+static const long table[3] = {1, 2, 3};
+
+struct config {
+    const int id;
+    int uses;
+};
+
+int sum(const int *values, int count) {
+    int total = 0;
+    for (int i = 0; i < count; i++)
+        total += values[i];
+    return total;
+}
+
+int main(void) {
+    int values[3] = {1, 2, 3};
+    //Qualifiers can be added to a pointer's target
+    const int *view = values;
+    if (sum(values, 3) != 6 || *view != 1 || limit != 10 || table[2] != 3 || greeting[1] != 'i')
+        return 1;
+    //A pointer to const can itself be reassigned
+    view = &limit;
+    if (*view != 10)
+        return 2;
+    int *const fixed = values;
+    *fixed = 5;
+    if (values[0] != 5)
+        return 3;
+    struct config c = { 7, 0 };
+    c.uses++;
+    if (c.id != 7 || c.uses != 1)
+        return 4;
+    const struct config copy = c;
+    if (copy.uses != 1)
+        return 5;
+    return 0;
+}
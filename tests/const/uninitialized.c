@@ -0,0 +1,5 @@
+//expect error: Const variable x is declared without an initializer
+int main(void) {
+    const int x;
+    return 0;
+}
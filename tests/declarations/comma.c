@@ -0,0 +1,24 @@
+//expect exit: 0
+int calls = 0;
+
+int tick(void) {
+    calls = calls + 1;
+    return calls;
+}
+
+int main(void) {
+    //Each operand is evaluated in order and the result is the last one
+    int x = (tick(), tick(), 10);
+    if (x != 10 || calls != 2)
+        return 1;
+    int i;
+    int j;
+    for (i = 0, j = 10; i < j; i = i + 1, j = j - 1)
+        ;
+    if (i != 5 || j != 5)
+        return 2;
+    double d = (1, 2.5);
+    if (d != 2.5)
+        return 3;
+    return 0;
+}
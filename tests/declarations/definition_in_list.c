@@ -0,0 +1,8 @@
+//expect error: Function definition in a declaration list
+int a, f(void) {
+    return 0;
+}
+
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,8 @@
+//expect error: Duplicate member x
+struct point {
+    int x, y, x;
+};
+
+int main(void) {
+    return 0;
+}
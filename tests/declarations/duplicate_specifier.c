@@ -0,0 +1,5 @@
+//expect error: Duplicate type specifier 'int'
+int main(void) {
+    int int x = 1;
+    return x;
+}
@@ -0,0 +1,5 @@
+//expect error: Invalid combination of type specifiers
+int main(void) {
+    char long x = 1;
+    return x;
+}
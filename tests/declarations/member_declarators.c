@@ -0,0 +1,32 @@
+//expect exit: 0
+//One set of specifiers can declare several members, each with its own declarator
+struct point {
+    int x, y;
+    long *next, values[2], z;
+};
+
+union number {
+    int i, j;
+    char bytes[8], c;
+};
+
+int main(void) {
+    struct point p;
+    p.x = 1;
+    p.y = 2;
+    p.values[1] = 3;
+    p.next = &p.values[1];
+    p.z = 4;
+    if (p.x + p.y + *p.next + p.z != 10)
+        return 1;
+    if (sizeof p != 40)
+        return 2;
+
+    union number n;
+    n.i = 65;
+    if (n.j != 65 || n.c != 'A' || n.bytes[0] != 'A')
+        return 3;
+    if (sizeof n != 8)
+        return 4;
+    return 0;
+}
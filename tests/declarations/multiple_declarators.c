@@ -0,0 +1,28 @@
+//expect exit: 0
+int a = 1, *pa = &a, arr[3] = {1, 2, 3};
+static long s1, s2 = 5;
+
+int twice(int x), thrice(int x);
+
+int twice(int x) {
+    return x * 2;
+}
+
+int thrice(int x) {
+    return x * 3;
+}
+
+int main(void) {
+    int x = 2, y = x + 1, *p = &y;
+    if (x != 2 || y != 3 || *p != 3)
+        return 1;
+    if (*pa != 1 || arr[2] != 3 || s1 != 0 || s2 != 5)
+        return 2;
+    if (twice(2) + thrice(2) != 10)
+        return 3;
+    for (int i = 0, j = 3; i < j; i = i + 1)
+        x = x + 1;
+    if (x != 5)
+        return 4;
+    return 0;
+}
@@ -0,0 +1,6 @@
+//expect error: Multiple storage classes in declaration specifiers
+static extern int x;
+
+int main(void) {
+    return 0;
+}
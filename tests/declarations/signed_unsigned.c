@@ -0,0 +1,5 @@
+//expect error: Both signed and unsigned in declaration specifiers
+int main(void) {
+    signed unsigned int x = 1;
+    return x;
+}
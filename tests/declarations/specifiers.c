@@ -0,0 +1,20 @@
+//expect exit: 0
+//Specifiers may appear in any order
+long unsigned int a = 18446744073709551615UL;
+int static long b = 7;
+unsigned c = 4294967295U;
+signed d = -1;
+char signed e = -2;
+int long f = 3;
+
+int main(void) {
+    if (a != 18446744073709551615UL || b != 7 || c != 4294967295U || d != -1 || e != -2 || f != 3)
+        return 1;
+    if (sizeof a != 8 || sizeof b != 8 || sizeof c != 4 || sizeof e != 1)
+        return 2;
+    unsigned long int g = 1;
+    long signed h = -5;
+    if (g != 1 || h != -5 || sizeof h != 8)
+        return 3;
+    return 0;
+}
@@ -0,0 +1,17 @@
+//expect exit: 0
+double third = 1.0 / 3.0;
+
+int main(void) {
+    double x = 2.5;
+    if (x * 4 != 10.0)
+        return 1;
+    if (third * 3 != 1.0)
+        return 2;
+    if (-x != -2.5)
+        return 3;
+    if (1e308 * 10 <= 1e308)
+        return 4;
+    if (.5 + 25e-1 != 3.)
+        return 5;
+    return 0;
+}
@@ -0,0 +1,17 @@
+//expect exit: 0
+int main(void) {
+    double zero = 0.0;
+    double nan = zero / zero;
+    //Every comparison with NaN is false except !=
+    if (nan == nan || nan < 1.0 || nan > 1.0 || nan <= 1.0 || nan >= 1.0)
+        return 1;
+    if (!(nan != nan))
+        return 2;
+    if (!nan)
+        return 3;
+    if (-0.0 != 0.0)
+        return 4;
+    if (!(1.5 < 2.5) || 0.0)
+        return 5;
+    return 0;
+}
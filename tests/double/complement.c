@@ -0,0 +1,5 @@
+//expect error: Bitwise complement applied to a double
+int main(void) {
+    double d = 5.0;
+    return ~d;
+}
@@ -0,0 +1,21 @@
+//expect exit: 0
+int main(void) {
+    double d = 3.99;
+    if ((int) d != 3)
+        return 1;
+    if ((int) -d != -3)
+        return 2;
+    unsigned long big = 18446744073709549568UL;
+    double from_unsigned = big;
+    if (from_unsigned != 18446744073709549568.0)
+        return 3;
+    if ((unsigned long) from_unsigned != big)
+        return 4;
+    if ((unsigned int) 4294967295.0 != 4294967295U)
+        return 5;
+    long l = -9007199254740993L;
+    double rounded = l;
+    if (rounded != -9007199254740992.0)
+        return 6;
+    return 0;
+}
@@ -0,0 +1,9 @@
+//link: gcc_abi_lib.c
+//expect exit: 0
+double interpolate(double from, double to, int steps, double t, long offset);
+
+int main(void) {
+    if (interpolate(1.0, 3.0, 2, 0.5, 10L) != 14.0)
+        return 1;
+    return 0;
+}
@@ -0,0 +1,3 @@
+double interpolate(double from, double to, int steps, double t, long offset) {
+    return (from + (to - from) * t) * steps + offset;
+}
@@ -0,0 +1,17 @@
+//expect exit: 0
+//Floating point arguments past the eighth are passed on the stack
+double sum(double a, double b, double c, double d, double e, double f, double g, double h, double i, double j) {
+    return a + b + c + d + e + f + g + h + i + j;
+}
+
+double mixed(int a, double b, long c, double d, unsigned int e, double f) {
+    return a + b + c + d + e + f;
+}
+
+int main(void) {
+    if (sum(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.5) != 55.5)
+        return 1;
+    if (mixed(1, 0.5, 2, 0.25, 3U, 0.25) != 7.0)
+        return 2;
+    return 0;
+}
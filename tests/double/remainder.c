@@ -0,0 +1,5 @@
+//expect error: Remainder operator applied to a double
+int main(void) {
+    double d = 5.0;
+    return d % 2;
+}
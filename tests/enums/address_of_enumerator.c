@@ -0,0 +1,7 @@
+//expect error: Cannot take the address of a non-lvalue
+enum { A };
+
+int main(void) {
+    int *p = &A;
+    return 0;
+}
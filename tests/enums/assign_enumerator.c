@@ -0,0 +1,7 @@
+//expect error: Invalid lvalue
+enum { A };
+
+int main(void) {
+    A = 1;
+    return 0;
+}
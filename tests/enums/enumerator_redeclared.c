@@ -0,0 +1,7 @@
+//expect error: Enumerator RED redeclared
+enum color { RED, GREEN };
+enum fruit { APPLE, RED };
+
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,6 @@
+//expect error: Value of enumerator NEXT is out of range for int
+enum sizes { LARGEST = 2147483647, NEXT };
+
+int main(void) {
+    return 0;
+}
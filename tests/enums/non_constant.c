@@ -0,0 +1,6 @@
+//expect error: in value of enumerator B
+int main(void) {
+    int x = 1;
+    enum values { A, B = x };
+    return 0;
+}
@@ -0,0 +1,6 @@
+//expect error: Value of enumerator BIG is out of range for int
+enum sizes { BIG = 2147483648L };
+
+int main(void) {
+    return 0;
+}
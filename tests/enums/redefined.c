@@ -0,0 +1,7 @@
+//expect error: enum color redefined
+enum color { RED };
+enum color { BLUE };
+
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,22 @@
+//expect exit: 0
+enum level { LOW = 1, HIGH = 10 };
+enum level global = HIGH;
+
+int main(void) {
+    if (global != 10)
+        return 1;
+    {
+        //An inner enumerator hides the outer one
+        enum { LOW = 100 };
+        if (LOW != 100)
+            return 2;
+        int HIGH = 20;
+        if (HIGH != 20)
+            return 3;
+    }
+    if (LOW != 1 || HIGH != 10)
+        return 4;
+    enum level later;
+    later = LOW;
+    return later - 1;
+}
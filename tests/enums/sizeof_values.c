@@ -0,0 +1,23 @@
+//expect exit: 0
+struct s {
+    long a;
+};
+
+//Enumerator values are folded once structure layouts are known
+enum { N = sizeof(struct s), NEXT, TWICE = N * 2 };
+
+int main(void) {
+    struct pair {
+        int x;
+        int y[3];
+    };
+    enum { LOCAL = sizeof(struct pair) / sizeof(int), ALIGN = _Alignof(struct pair) };
+    if (N != 8 || NEXT != 9 || TWICE != 16)
+        return 1;
+    if (LOCAL != 4 || ALIGN != 4)
+        return 2;
+    int values[N];
+    if (sizeof values != 32)
+        return 3;
+    return 0;
+}
@@ -0,0 +1,5 @@
+//expect error: enum missing is an undeclared enumeration type
+int main(void) {
+    enum missing m;
+    return 0;
+}
@@ -0,0 +1,23 @@
+//expect exit: 0
+enum color { RED, GREEN = 5, BLUE, ALPHA = BLUE * 2, LAST = -1 };
+enum { ANONYMOUS = 3 };
+
+int main(void) {
+    if (RED != 0 || GREEN != 5 || BLUE != 6 || ALPHA != 12 || LAST != -1 || ANONYMOUS != 3)
+        return 1;
+    enum color c = BLUE;
+    c = c + 1;
+    if (c != 7 || sizeof c != 4)
+        return 2;
+    //Enumerators are constants
+    int sized[GREEN];
+    if (sizeof sized != 20)
+        return 3;
+    switch (c - 1) {
+        case BLUE:
+            break;
+        default:
+            return 4;
+    }
+    return 0;
+}
@@ -0,0 +1,7 @@
+//expect error: RED redeclared as a different kind of symbol
+int RED;
+enum color { RED };
+
+int main(void) {
+    return 0;
+}
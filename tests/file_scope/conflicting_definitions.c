@@ -0,0 +1,7 @@
+//expect error: Conflicting definitions of variable x
+int x = 1;
+int x = 2;
+
+int main(void) {
+    return x;
+}
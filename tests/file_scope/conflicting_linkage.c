@@ -0,0 +1,7 @@
+//expect error: Conflicting linkage for variable x
+static int x;
+int x;
+
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,16 @@
+//expect exit: 13
+int initialized = 10;
+int zeroed;
+static int hidden = 3;
+
+int bump(void) {
+    zeroed = zeroed + 1;
+    return zeroed;
+}
+
+int main(void) {
+    if (zeroed != 0)
+        return 1;
+    bump();
+    return initialized + hidden + zeroed - 1;
+}
@@ -0,0 +1,5 @@
+//expect error: Initializer on local extern variable x
+int main(void) {
+    extern int x = 3;
+    return x;
+}
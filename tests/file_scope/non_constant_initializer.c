@@ -0,0 +1,7 @@
+//expect error: in initializer for static variable y
+int x = 1;
+int y = x + 1;
+
+int main(void) {
+    return y;
+}
@@ -0,0 +1,10 @@
+//link: shared_with_gcc_lib.c
+//expect exit: 30
+extern int from_gcc;
+int from_glasses = 20;
+int read_from_glasses(void);
+
+int main(void) {
+    from_gcc = from_gcc + 1;
+    return from_gcc + read_from_glasses() - 1;
+}
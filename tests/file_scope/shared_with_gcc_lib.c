@@ -0,0 +1,6 @@
+int from_gcc = 10;
+extern int from_glasses;
+
+int read_from_glasses(void) {
+    return from_glasses;
+}
@@ -0,0 +1,10 @@
+//expect exit: 5
+int value;
+int value;
+int value = 5;
+extern int value;
+
+int main(void) {
+    extern int value;
+    return value;
+}
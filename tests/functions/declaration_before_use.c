@@ -0,0 +1,10 @@
+//expect exit: 7
+int add(int a, int b);
+
+int main(void) {
+    return add(3, 4);
+}
+
+int add(int a, int b) {
+    return a + b;
+}
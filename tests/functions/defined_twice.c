@@ -0,0 +1,12 @@
+//expect error: Function f is defined more than once
+int f(void) {
+    return 1;
+}
+
+int f(void) {
+    return 2;
+}
+
+int main(void) {
+    return f();
+}
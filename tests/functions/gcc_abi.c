@@ -0,0 +1,16 @@
+//link: gcc_abi_lib.c
+//expect exit: 42
+int callback(int a, int b, int c, int d, int e, int f, int g, int h) {
+    return a * 1 + b * 2 + c * 3 + d * 4 + e * 5 + f * 6 + g * 7 + h * 8;
+}
+
+int combine(int a, int b, int c, int d, int e, int f, int g, int h);
+int call_back(void);
+
+int main(void) {
+    if (call_back() != 204)
+        return 1;
+    if (combine(1, 2, 3, 4, 5, 6, 7, 8) != 8)
+        return 2;
+    return 42;
+}
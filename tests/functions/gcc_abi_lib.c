@@ -0,0 +1,9 @@
+int callback(int a, int b, int c, int d, int e, int f, int g, int h);
+
+int combine(int a, int b, int c, int d, int e, int f, int g, int h) {
+    return a + b + c + d + e + f + g + h - 28;
+}
+
+int call_back(void) {
+    return callback(1, 2, 3, 4, 5, 6, 7, 8);
+}
@@ -0,0 +1,7 @@
+//expect error: Incompatible declarations of function f
+int f(int a);
+int f(int a, int b);
+
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,9 @@
+//expect output: hi
+int putchar(int c);
+
+int main(void) {
+    putchar(104);
+    putchar(105);
+    putchar(10);
+    return 0;
+}
@@ -0,0 +1,15 @@
+//expect exit: 45
+//Arguments past the sixth are passed on the stack
+int sum(int a, int b, int c, int d, int e, int f, int g, int h, int i) {
+    return a + b + c + d + e + f + g + h + i;
+}
+
+int weighted(int a, int b, int c, int d, int e, int f, int g, int h) {
+    return a - b + c - d + e - f + g * h;
+}
+
+int main(void) {
+    if (weighted(1, 2, 3, 4, 5, 6, 7, 8) != 53)
+        return 1;
+    return sum(1, 2, 3, 4, 5, 6, 7, 8, 9);
+}
@@ -0,0 +1,7 @@
+//expect error: inner is a nested function definition
+int main(void) {
+    int inner(void) {
+        return 1;
+    }
+    return inner();
+}
@@ -0,0 +1,4 @@
+//expect error: missing is an undeclared function
+int main(void) {
+    return missing(1);
+}
@@ -0,0 +1,8 @@
+//expect error: Function add called with the wrong number of arguments
+int add(int a, int b) {
+    return a + b;
+}
+
+int main(void) {
+    return add(1);
+}
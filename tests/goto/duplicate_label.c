@@ -0,0 +1,7 @@
+//expect error: Duplicate label here in function main
+int main(void) {
+here:
+    ;
+here:
+    return 0;
+}
@@ -0,0 +1,29 @@
+//expect exit: 0
+//Labels belong to their function, so the same name can be used in each one
+int first(void) {
+    goto done;
+    return 1;
+done:
+    return 10;
+}
+
+int second(void) {
+    int n = 0;
+    for (int i = 0; i < 3; i = i + 1) {
+        if (i == 1)
+            goto done;
+        n = n + 1;
+    }
+done:
+    return n;
+}
+
+int main(void) {
+    if (first() != 10 || second() != 1)
+        return 1;
+    //A label and a variable may share a name
+    int label = 3;
+    goto label;
+label:
+    return label - 3;
+}
@@ -0,0 +1,13 @@
+//expect exit: 0
+int main(void) {
+    int count = 0;
+    goto middle;
+    while (count < 5) {
+        count = count + 10;
+    middle:
+        count = count + 1;
+    }
+    if (count != 12)
+        return 1;
+    return 0;
+}
@@ -0,0 +1,24 @@
+//expect exit: 0
+int main(void) {
+    int i = 0;
+    int sum = 0;
+top:
+    sum = sum + i;
+    i = i + 1;
+    if (i < 5)
+        goto top;
+    if (sum != 10)
+        return 1;
+    goto skip;
+    return 2;
+skip:
+    {
+        goto inner;
+        sum = 0;
+    inner:
+        sum = sum + 1;
+    }
+    if (sum != 11)
+        return 3;
+    return 0;
+}
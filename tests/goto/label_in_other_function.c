@@ -0,0 +1,9 @@
+//expect error: goto to undefined label elsewhere
+int other(void) {
+elsewhere:
+    return 1;
+}
+
+int main(void) {
+    goto elsewhere;
+}
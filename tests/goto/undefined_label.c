@@ -0,0 +1,5 @@
+//expect error: goto to undefined label nowhere
+int main(void) {
+    goto nowhere;
+    return 0;
+}
@@ -0,0 +1,10 @@
+//expect exit: 0
+int main(void) {
+    if (0x1F != 31 || 0XabC != 2748 || 017 != 15 || 0 != 00 || 0b101 != 5 || 0B11 != 3)
+        return 1;
+    if (0x7fffffffffffffff != 9223372036854775807L)
+        return 2;
+    if (0777777777777777777777 != 9223372036854775807L)
+        return 3;
+    return 0;
+}
@@ -0,0 +1,4 @@
+//expect error: Malformed integer constant
+int main(void) {
+    return 0x;
+}
@@ -0,0 +1,4 @@
+//expect error: Invalid suffix "lL" on integer constant
+int main(void) {
+    return 1lL;
+}
@@ -0,0 +1,4 @@
+//expect error: Invalid digit in octal constant
+int main(void) {
+    return 019;
+}
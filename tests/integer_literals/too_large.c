@@ -0,0 +1,5 @@
+//expect error: Integer constant too large
+int main(void) {
+    long x = 18446744073709551616;
+    return 0;
+}
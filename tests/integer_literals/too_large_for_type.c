@@ -0,0 +1,5 @@
+//expect error: Integer constant too large for its type
+int main(void) {
+    long x = 9223372036854775808;
+    return 0;
+}
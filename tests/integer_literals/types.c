@@ -0,0 +1,18 @@
+//expect exit: 0
+int main(void) {
+    //Decimal constants that do not fit int become long, never unsigned
+    if (sizeof 2147483647 != 4 || sizeof 2147483648 != 8 || -2147483648 > 0)
+        return 1;
+    //Hexadecimal and octal constants can become unsigned
+    if (sizeof 0xFFFFFFFF != 4 || -1 < 0xFFFFFFFF || sizeof 037777777777 != 4)
+        return 2;
+    if (sizeof 0x100000000 != 8 || 0x8000000000000000 < 0 || sizeof 0xFFFFFFFFFFFFFFFF != 8)
+        return 3;
+    if (sizeof 1u != 4 || sizeof 1l != 8 || sizeof 1ll != 8 || sizeof 4294967296u != 8)
+        return 4;
+    if (-1 < 1u || -1 < 1ul || -1L < 1Lu || -1 > 1LL)
+        return 5;
+    if (18446744073709551615ULL != 0xFFFFFFFFFFFFFFFF || 1uLL != 1 || 2llU != 2)
+        return 6;
+    return 0;
+}
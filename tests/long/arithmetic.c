@@ -0,0 +1,19 @@
+//expect exit: 0
+long big = 4294967296L;
+
+long mul(long a, long b) {
+    return a * b;
+}
+
+int main(void) {
+    long x = big * 3;
+    if (x != 12884901888L)
+        return 1;
+    if (x / big != 3)
+        return 2;
+    if (mul(100000, 100000) != 10000000000L)
+        return 3;
+    if (-x % 5 != -3)
+        return 4;
+    return 0;
+}
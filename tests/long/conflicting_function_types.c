@@ -0,0 +1,7 @@
+//expect error: Incompatible declarations of function f
+int f(int a);
+long f(int a);
+
+int main(void) {
+    return 0;
+}
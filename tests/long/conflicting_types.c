@@ -0,0 +1,7 @@
+//expect error: Conflicting types for variable x
+int x;
+long x;
+
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,26 @@
+//expect exit: 0
+int truncate(long l) {
+    return (int) l;
+}
+
+long extend(int i) {
+    return i;
+}
+
+int main(void) {
+    if (truncate(4294967301L) != 5)
+        return 1;
+    if (extend(-1) != -1L)
+        return 2;
+    long l = 2147483647;
+    l = l + 1;
+    if (l != 2147483648L)
+        return 3;
+    int i = l;
+    if (i != -2147483647 - 1)
+        return 4;
+    //Mixed operands are converted to long before the operation
+    if (1 + 4294967296L != 4294967297L)
+        return 5;
+    return 0;
+}
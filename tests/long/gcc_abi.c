@@ -0,0 +1,11 @@
+//link: gcc_abi_lib.c
+//expect exit: 0
+long scale(long value, int factor);
+
+int main(void) {
+    if (scale(3000000000L, 3) != 9000000000L)
+        return 1;
+    if (scale(-5L, -2) != 10L)
+        return 2;
+    return 0;
+}
@@ -0,0 +1,3 @@
+long scale(long value, int factor) {
+    return value * factor;
+}
@@ -0,0 +1,18 @@
+//expect exit: 25
+int main(void) {
+    int sum = 0;
+    for (int i = 0; i < 100; i = i + 1) {
+        if (i % 2 == 0)
+            continue;
+        if (i > 9)
+            break;
+        sum = sum + i;
+    }
+    int n = 0;
+    do {
+        n = n + 1;
+        if (n < 3)
+            continue;
+    } while (n < 5);
+    return sum + n - 5;
+}
@@ -0,0 +1,5 @@
+//expect error: break statement outside of a loop or switch
+int main(void) {
+    break;
+    return 0;
+}
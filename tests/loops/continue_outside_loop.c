@@ -0,0 +1,6 @@
+//expect error: continue statement outside of a loop
+int main(void) {
+    if (1)
+        continue;
+    return 0;
+}
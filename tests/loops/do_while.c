@@ -0,0 +1,9 @@
+//expect exit: 1
+int main(void) {
+    int count = 0;
+    //The body runs once even though the condition is false from the start
+    do {
+        count = count + 1;
+    } while (0);
+    return count;
+}
@@ -0,0 +1,15 @@
+//expect exit: 30
+int main(void) {
+    int sum = 0;
+    for (int i = 0; i < 5; i = i + 1) {
+        for (int j = 0; j < 3; j = j + 1)
+            sum = sum + 2;
+    }
+    int k;
+    for (k = 0; ; k = k + 1)
+        if (k == 0)
+            break;
+    for (;;)
+        break;
+    return sum + k;
+}
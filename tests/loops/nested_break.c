@@ -0,0 +1,16 @@
+//expect exit: 12
+int main(void) {
+    int total = 0;
+    int i = 0;
+    while (i < 4) {
+        int j = 0;
+        while (1) {
+            j = j + 1;
+            if (j == 3)
+                break;
+        }
+        total = total + j;
+        i = i + 1;
+    }
+    return total;
+}
@@ -0,0 +1,10 @@
+//expect exit: 55
+int main(void) {
+    int i = 0;
+    int sum = 0;
+    while (i < 10) {
+        i = i + 1;
+        sum = sum + i;
+    }
+    return sum;
+}
@@ -0,0 +1,13 @@
+//expect exit: 0
+int main(void) {
+    int x = 1;
+    int *p = &x;
+    *p = 7;
+    if (x != 7)
+        return 1;
+    int **pp = &p;
+    **pp = 9;
+    if (x != 9 || *p != 9)
+        return 2;
+    return 0;
+}
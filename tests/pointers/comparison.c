@@ -0,0 +1,18 @@
+//expect exit: 0
+int main(void) {
+    int a = 0;
+    int b = 0;
+    int *pa = &a;
+    int *null = 0;
+    if (pa != &a || pa == &b)
+        return 1;
+    if (null || !pa)
+        return 2;
+    //Any integer constant expression with value zero is a null pointer constant
+    if (null != (0) || null != 1 - 1)
+        return 3;
+    int *chosen = a ? pa : 0;
+    if (chosen)
+        return 4;
+    return 0;
+}
@@ -0,0 +1,5 @@
+//expect error: Cannot dereference a non-pointer
+int main(void) {
+    int x = 1;
+    return *x;
+}
@@ -0,0 +1,12 @@
+//link: gcc_abi_lib.c
+//expect exit: 0
+long *pick(long *a, long *b, int first);
+
+int main(void) {
+    long x = 1;
+    long y = 2;
+    *pick(&x, &y, 0) = 10;
+    if (x != 1 || y != 10)
+        return 1;
+    return 0;
+}
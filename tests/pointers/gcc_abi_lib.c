@@ -0,0 +1,3 @@
+long *pick(long *a, long *b, int first) {
+    return first ? a : b;
+}
@@ -0,0 +1,6 @@
+//expect error: Expressions have incompatible pointer types
+int main(void) {
+    int x = 1;
+    long y = 2;
+    return &x == &y;
+}
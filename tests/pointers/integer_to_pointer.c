@@ -0,0 +1,6 @@
+//expect error: Cannot convert type for assignment
+int main(void) {
+    int x = 1;
+    int *p = x;
+    return 0;
+}
@@ -0,0 +1,6 @@
+//expect error: in initializer for static variable p
+int main(void) {
+    int local = 1;
+    static int *p = &local;
+    return 0;
+}
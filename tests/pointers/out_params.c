@@ -0,0 +1,20 @@
+//expect exit: 0
+int divide(long a, long b, long *quotient, long *remainder) {
+    *quotient = a / b;
+    *remainder = a % b;
+    return 1;
+}
+
+int main(void) {
+    long q = 0;
+    long r = 0;
+    divide(47, 5, &q, &r);
+    if (q != 9 || r != 2)
+        return 1;
+    double d = 1.5;
+    double *dp = &d;
+    *dp = *dp * 2;
+    if (d != 3.0)
+        return 2;
+    return 0;
+}
@@ -0,0 +1,6 @@
+//expect error: Cannot cast between a pointer and a double
+int main(void) {
+    int x = 1;
+    double d = (double) &x;
+    return 0;
+}
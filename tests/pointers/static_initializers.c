@@ -0,0 +1,16 @@
+//expect exit: 0
+int target = 5;
+int *address = &target;
+static long other = 3;
+long *other_address = &other;
+int *null = 0;
+
+int main(void) {
+    static int *local = &target;
+    if (*address != 5 || *other_address != 3 || null)
+        return 1;
+    *local = 6;
+    if (target != 6)
+        return 2;
+    return 0;
+}
@@ -0,0 +1,21 @@
+//expect exit: 0
+#include <stddef.h>
+#include <stdarg.h>
+#include <stdbool.h>
+
+struct record {
+    char tag;
+    long value;
+};
+
+int main(void) {
+    size_t size = sizeof(struct record);
+    ptrdiff_t diff = -1;
+    int *p = NULL;
+    bool b = 7;
+    if (size != 16 || diff >= 0 || p || b != true || false)
+        return 1;
+    if (offsetof(struct record, value) != 8)
+        return 2;
+    return 0;
+}
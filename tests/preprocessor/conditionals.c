@@ -0,0 +1,37 @@
+//expect exit: 0
+#define FEATURE 2
+
+#if FEATURE == 1
+int feature = 1;
+#elif FEATURE == 2 && defined(FEATURE)
+int feature = 2;
+#else
+int feature = 3;
+#endif
+
+#ifdef MISSING
+#error MISSING should not be defined
+#endif
+
+#ifndef MISSING
+int missing = 0;
+#endif
+
+//Undefined names are 0, and an unsigned operand makes the other one unsigned as in C
+#if UNDEFINED_NAME || (1 << 4) != 16 || 10 / 3 != 3 || -7 % 3 != -1 || -1 < 0u || -1 / 2u < 2
+int broken = 1;
+#else
+int broken = 0;
+#endif
+
+#if 0
+This text is skipped, even with a stray ' quote
+#endif
+
+int main(void) {
+#if defined FEATURE && __STDC_VERSION__ >= 201112L && __x86_64__
+    return feature - 2 + missing + broken;
+#else
+    return 1;
+#endif
+}
@@ -0,0 +1,6 @@
+//expect error: Division by zero in #if expression
+#if 1 / 0
+#endif
+int main(void) {
+    return 0;
+}
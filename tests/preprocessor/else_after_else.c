@@ -0,0 +1,8 @@
+//expect error: #else after #else
+#if 1
+#else
+#else
+#endif
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,5 @@
+//expect error: #endif without #if
+#endif
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,5 @@
+//expect error: #error stop here
+#error stop here
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,6 @@
+//args: -Iinclude
+//expect error: broken.h:2: #if with no expression
+#include <broken.h>
+int main(void) {
+    return 0;
+}
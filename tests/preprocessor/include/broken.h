@@ -0,0 +1,3 @@
+int ok;
+#if
+#endif
@@ -0,0 +1,5 @@
+#ifndef CONFIG_H
+#define CONFIG_H
+#define SCALE 3
+int scale(int x);
+#endif
@@ -0,0 +1,16 @@
+//args: -I lib -Iinclude
+//expect exit: 0
+#include "local.h"
+#include <config.h>
+#include "config.h"
+#include <once.h>
+#include "lib/once.h"
+
+int scale(int x) {
+    return x * SCALE;
+}
+
+int main(void) {
+    struct once o = { LOCAL };
+    return scale(o.value) - 12;
+}
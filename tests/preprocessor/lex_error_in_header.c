@@ -0,0 +1,6 @@
+//args: -Iinclude
+//expect error: stray.h:2: Unexpected Token: @
+#include <stray.h>
+int main(void) {
+    return 0;
+}
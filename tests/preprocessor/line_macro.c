@@ -0,0 +1,10 @@
+//expect exit: 0
+int main(void) {
+    /* a comment
+       over lines */
+    if (__LINE__ != 5)
+        return 1;
+    if (__FILE__[0] == 0)
+        return 2;
+    return 0;
+}
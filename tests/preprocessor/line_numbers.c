@@ -0,0 +1,12 @@
+//expect error: line_numbers.c:11: Expression Expected
+/* A comment
+   that spans
+   several lines */
+#define UNUSED 1
+
+int main(void) {
+    int x = 1; /* another
+    comment */
+    int y = __LINE__;
+    return x +;
+}
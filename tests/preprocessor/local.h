@@ -0,0 +1,2 @@
+#include "include/config.h"
+#define LOCAL 4
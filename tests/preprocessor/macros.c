@@ -0,0 +1,25 @@
+//expect exit: 0
+#define TEN 10
+#define SQUARE(x) ((x) * (x))
+#define ADD(a, b) (a + b)
+#define EMPTY
+#define NESTED SQUARE(TEN)
+#define SELF SELF
+#define MAX(a, b) ((a) > (b) ? (a) : (b))
+
+int main(void) {
+    if (SQUARE(TEN) != 100 || ADD(1, 2) * 2 != 6 || NESTED != 100)
+        return 1;
+    int SELF = 3;
+    if (SELF EMPTY != 3)
+        return 2;
+    if (MAX(ADD(1, 1), SQUARE(2)) != 4)
+        return 3;
+#undef TEN
+#define TEN 11
+    if (TEN != 11)
+        return 4;
+    //A function-like macro name without arguments is left alone
+    int SQUARE = 5;
+    return SQUARE - 5;
+}
@@ -0,0 +1,5 @@
+//expect error: missing_header.h: No such file or directory
+#include "missing_header.h"
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,7 @@
+//args: -Iinclude
+//expect error: parse_error_after_header.c:7: Expected ';'
+#include <config.h>
+int main(void) {
+    int x = 1;
+    return x
+}
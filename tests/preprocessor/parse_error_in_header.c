@@ -0,0 +1,6 @@
+//args: -Iinclude
+//expect error: syntax.h:1: Expression Expected
+#include <syntax.h>
+int main(void) {
+    return helper();
+}
@@ -0,0 +1,5 @@
+//expect error: '##' cannot appear at either end of a macro expansion
+#define CAT(a) a ##
+int main(void) {
+    return 0;
+}
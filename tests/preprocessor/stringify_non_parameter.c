@@ -0,0 +1,5 @@
+//expect error: '#' is not followed by a macro parameter
+#define STR(x) #y
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,35 @@
+//expect output: x + 1
+//expect output: "quoted" 'c'
+int putchar(int c);
+
+#define STR(x) #x
+#define XSTR(x) STR(x)
+#define CAT(a, b) a ## b
+#define VALUE 42
+#define SUM(...) add(__VA_ARGS__)
+#define FIRST(first, ...) first
+
+int print(char *s) {
+    while (*s) {
+        putchar(*s);
+        s = s + 1;
+    }
+    return putchar('\n');
+}
+
+int add(int a, int b, int c) {
+    return a + b + c;
+}
+
+int main(void) {
+    print(STR(x   +   1));
+    print(STR("quoted" 'c'));
+    int CAT(my, var) = 3;
+    if (myvar != 3 || CAT(1, 2) != 12)
+        return 1;
+    if (XSTR(VALUE)[0] != '4' || STR(VALUE)[0] != 'V')
+        return 2;
+    if (SUM(1, 2, 3) != 6 || FIRST(7, 8, 9) != 7)
+        return 3;
+    return 0;
+}
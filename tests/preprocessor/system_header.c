@@ -0,0 +1,8 @@
+//expect exit: 0
+#include <limits.h>
+
+int main(void) {
+    if (INT_MAX != 2147483647 || CHAR_BIT != 8 || LONG_MIN >= 0)
+        return 1;
+    return 0;
+}
@@ -0,0 +1,13 @@
+//expect exit: 0
+struct padded {
+    char c;
+    double d;
+};
+
+int main(void) {
+    if (_Alignof(char) != 1 || _Alignof(int) != 4 || _Alignof(long) != 8 || _Alignof(double) != 8)
+        return 1;
+    if (_Alignof(struct padded) != 8 || _Alignof(char[7]) != 1 || _Alignof(int[3]) != 4)
+        return 2;
+    return 0;
+}
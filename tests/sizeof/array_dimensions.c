@@ -0,0 +1,30 @@
+//expect exit: 0
+struct s {
+    int a;
+    long b;
+};
+
+//Dimensions are folded once structure layouts and variable types are known
+char by_struct[sizeof(struct s)];
+int x;
+int by_variable[sizeof x];
+long by_array[sizeof by_struct / sizeof(char)][_Alignof(struct s)];
+
+int main(void) {
+    double d;
+    char local[sizeof d + sizeof(struct s)];
+    struct s pair[2];
+    int counts[sizeof pair / sizeof pair[0]];
+
+    if (sizeof by_struct != 16)
+        return 1;
+    if (sizeof by_variable != 16)
+        return 2;
+    if (sizeof by_array != 16 * 8 * 8)
+        return 3;
+    if (sizeof local != 24)
+        return 4;
+    if (sizeof counts != 8)
+        return 5;
+    return 0;
+}
@@ -0,0 +1,31 @@
+//expect exit: 0
+struct s {
+    long a;
+    char b;
+};
+
+int classify(unsigned long size) {
+    switch (size) {
+        case sizeof(int):
+            return 1;
+        case sizeof(struct s):
+            return 2;
+        case sizeof(struct s) * 2 + _Alignof(struct s):
+            return 3;
+        default:
+            return 0;
+    }
+}
+
+int main(void) {
+    struct s value;
+    if (classify(4) != 1)
+        return 1;
+    if (classify(sizeof value) != 2)
+        return 2;
+    if (classify(40) != 3)
+        return 3;
+    if (classify(8) != 0)
+        return 4;
+    return 0;
+}
@@ -0,0 +1,30 @@
+//expect exit: 0
+int calls = 0;
+
+int side_effect(void) {
+    calls = calls + 1;
+    return 1;
+}
+
+//Sizes are constants, so they work in array sizes and static initializers
+long table[sizeof(int) * 2];
+unsigned long size = sizeof table;
+
+int sum(int values[10]) {
+    //An array parameter is a pointer
+    return sizeof values;
+}
+
+int main(void) {
+    int x = 0;
+    if (sizeof(side_effect()) != 4 || sizeof x++ != 4)
+        return 1;
+    //The operand is not evaluated
+    if (calls != 0 || x != 0)
+        return 2;
+    if (size != 64)
+        return 3;
+    if (sum(0) != 8)
+        return 4;
+    return 0;
+}
@@ -0,0 +1,6 @@
+//expect error: Function f used as a variable
+int f(void);
+
+int main(void) {
+    return sizeof f;
+}
@@ -0,0 +1,6 @@
+//expect error: Cannot take the size of an incomplete type
+extern int values[];
+
+int main(void) {
+    return sizeof values;
+}
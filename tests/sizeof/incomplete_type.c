@@ -0,0 +1,6 @@
+//expect error: Cannot take the size of an incomplete type
+struct unknown;
+
+int main(void) {
+    return sizeof(struct unknown);
+}
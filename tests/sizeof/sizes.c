@@ -0,0 +1,27 @@
+//expect exit: 0
+struct padded {
+    char c;
+    double d;
+    int i;
+};
+
+union mixed {
+    char bytes[9];
+    long l;
+};
+
+int main(void) {
+    if (sizeof(char) != 1 || sizeof(int) != 4 || sizeof(long) != 8 || sizeof(double) != 8 || sizeof(int *) != 8)
+        return 1;
+    if (sizeof(struct padded) != 24 || sizeof(union mixed) != 16)
+        return 2;
+    int array[10];
+    if (sizeof array != 40 || sizeof array[0] != 4 || sizeof(long[3][2]) != 48)
+        return 3;
+    if (sizeof "hello" != 6)
+        return 4;
+    //sizeof yields an unsigned long
+    if (sizeof(int) - 5 < 0)
+        return 5;
+    return 0;
+}
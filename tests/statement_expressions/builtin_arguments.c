@@ -0,0 +1,4 @@
+//expect error: __builtin_expect called with the wrong number of arguments
+int main(void) {
+    return __builtin_expect(1);
+}
@@ -0,0 +1,23 @@
+//expect exit: 0
+int global = 1;
+
+int classify(int n) {
+    switch (n) {
+        case 0:
+            return 10;
+        case 1:
+            return 11;
+    }
+    __builtin_unreachable();
+}
+
+int main(void) {
+    if (__builtin_expect(global == 1, 1) != 1 || __builtin_expect(7, 0) != 7)
+        return 1;
+    //Only constant expressions are reported as constant
+    if (!__builtin_constant_p(3 * 4) || __builtin_constant_p(global) || !__builtin_constant_p(sizeof(long)))
+        return 2;
+    if (classify(1) != 11)
+        return 3;
+    return 0;
+}
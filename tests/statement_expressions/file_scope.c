@@ -0,0 +1,6 @@
+//expect error: Statement expression outside of a function
+int x = ({ 1; });
+
+int main(void) {
+    return x;
+}
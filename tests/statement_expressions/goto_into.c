@@ -0,0 +1,6 @@
+//expect error: goto inside jumps into a statement expression
+int main(void) {
+    goto inside;
+    int x = ({ inside: 1; });
+    return x;
+}
@@ -0,0 +1,18 @@
+//expect exit: 0
+int main(void) {
+    //Jumps within a statement expression and out of one are allowed
+    int x = ({
+        int i = 0;
+    again:
+        i++;
+        if (i < 3)
+            goto again;
+        i;
+    });
+    if (x != 3)
+        return 1;
+    int y = ({ if (x == 3) goto out; 5; });
+    return 2;
+out:
+    return 0;
+}
@@ -0,0 +1,6 @@
+//expect error: Incompatible types in conditional expression
+int main(void) {
+    int x = 1;
+    x ? ({ if (x) x = 2; }) : 3;
+    return 0;
+}
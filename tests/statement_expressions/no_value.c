@@ -0,0 +1,9 @@
+//expect exit: 3
+int main(void) {
+    int x = 0;
+    //A statement expression without a trailing expression can only be discarded
+    ({ x = 3; if (x) x = x; });
+    ({ });
+    x ? ({ if (x) x = 3; }) : __builtin_trap();
+    return x;
+}
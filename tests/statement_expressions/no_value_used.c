@@ -0,0 +1,5 @@
+//expect error: Expression without a value used as a value
+int main(void) {
+    int x = ({ x = 1; if (x) x = 2; });
+    return x;
+}
@@ -0,0 +1,9 @@
+//expect error: Cannot convert type for assignment
+struct point { int x; };
+
+int main(void) {
+    struct point p = { 1 };
+    //A return inside a statement expression converts to the function's return type
+    ({ return p; });
+    return 0;
+}
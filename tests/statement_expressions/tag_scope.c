@@ -0,0 +1,5 @@
+//expect error: union t redeclared as a different kind of tag
+int main(void) {
+    //The cast declares struct t in the statement expression's own block
+    return ({ (struct t *)0; union t { int a; }; 0; });
+}
@@ -0,0 +1,6 @@
+//expect exit: 132
+//__builtin_trap stops the program with an illegal instruction, which is signal 4
+int main(void) {
+    __builtin_trap();
+    return 0;
+}
@@ -0,0 +1,4 @@
+//expect error: Expression without a value used as a value
+int main(void) {
+    return __builtin_trap();
+}
@@ -0,0 +1,31 @@
+//expect exit: 0
+#define MAX(a, b) ({ long _a = (a); long _b = (b); _a > _b ? _a : _b; })
+
+int calls = 0;
+
+int next(void) {
+    calls++;
+    return calls;
+}
+
+int main(void) {
+    //The value is that of the last expression statement
+    int x = ({ int y = 4; y * 2; });
+    if (x != 8)
+        return 1;
+    //Each macro argument is evaluated once
+    if (MAX(next(), 0) != 1 || calls != 1)
+        return 2;
+    double d = ({ 1; 2.5; });
+    if (d != 2.5)
+        return 3;
+    int nested = ({ int a = 1; ({ int b = a + 1; b * 10; }) + a; });
+    if (nested != 21)
+        return 4;
+    //A statement expression can contain loops and return from the function
+    int sum = ({ int total = 0; for (int i = 0; i < 5; i++) total += i; total; });
+    if (sum != 10)
+        return 5;
+    ({ if (sum == 10) return 0; });
+    return 6;
+}
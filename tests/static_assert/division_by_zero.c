@@ -0,0 +1,6 @@
+//expect error: Division by zero in static assertion
+_Static_assert(1 / 0, "division by zero");
+
+int main(void) {
+    return 0;
+}
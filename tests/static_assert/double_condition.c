@@ -0,0 +1,6 @@
+//expect error: Non-integer constant expression in static assertion
+_Static_assert(1.5, "not an integer");
+
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,5 @@
+//expect error: failed.c:3: Static assertion failed: int is too small
+int main(void) {
+    _Static_assert(sizeof(int) == 8, "int is too small");
+    return 0;
+}
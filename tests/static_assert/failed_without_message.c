@@ -0,0 +1,6 @@
+//expect error: failed_without_message.c:2: Static assertion failed
+_Static_assert(2 + 2 == 5);
+
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,6 @@
+//expect error: Static assertion in for loop initializer
+int main(void) {
+    for (_Static_assert(1, "no"); ; )
+        break;
+    return 0;
+}
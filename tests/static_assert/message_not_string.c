@@ -0,0 +1,6 @@
+//expect error: Expected string literal in static assertion
+_Static_assert(1, 2);
+
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,6 @@
+//expect error: non_constant.c:4: Non-constant expression in static assertion
+int main(void) {
+    int x = 1;
+    _Static_assert(x == 1, "x is a variable");
+    return 0;
+}
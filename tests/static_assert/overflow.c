@@ -0,0 +1,6 @@
+//expect error: Integer overflow in static assertion
+_Static_assert(2147483647 + 1 > 0, "signed overflow is not a constant");
+
+int main(void) {
+    return 0;
+}
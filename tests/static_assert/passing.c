@@ -0,0 +1,27 @@
+//expect exit: 0
+struct record {
+    char tag;
+    long value;
+};
+
+enum { COUNT = 4 };
+
+_Static_assert(sizeof(struct record) == 16, "record is padded to its alignment");
+_Static_assert(_Alignof(struct record) == 8, "record is aligned to its long");
+_Static_assert(COUNT * 2 == 8 && COUNT > 0, "enumerators are constants");
+_Static_assert((char) 300 == 44, "casts truncate");
+_Static_assert(1u - 2 == 4294967295u, "unsigned arithmetic wraps");
+_Static_assert(-1 < 0 ? 1 : 0, "conditional expressions fold");
+_Static_assert(1);
+_Static_assert('a' == 97 && (int) 2.9 == 2, "character and double constants fold");
+_Static_assert(sizeof(int[COUNT]) == 16, "arrays can be sized by enumerators");
+
+int main(void) {
+    _Static_assert(sizeof(long) == 8, "long is 64 bits");
+    int x = 0;
+    for (int i = 0; i < 3; i++) {
+        _Static_assert(sizeof x == 4, "block scope assertions see local declarations");
+        x = x + i;
+    }
+    return x - 3;
+}
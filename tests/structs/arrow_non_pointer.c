@@ -0,0 +1,7 @@
+//expect error: Member x accessed through a non-pointer
+struct point { int x; int y; };
+
+int main(void) {
+    struct point p = { 1, 2 };
+    return p->x;
+}
@@ -0,0 +1,29 @@
+//expect exit: 0
+struct pair {
+    long a;
+    char b;
+};
+
+struct pair global = { 5, 'g' };
+
+struct pair swap(struct pair p) {
+    struct pair result = { p.b, (char) p.a };
+    return result;
+}
+
+int main(void) {
+    struct pair copy = global;
+    copy.a = 6;
+    if (global.a != 5 || copy.b != 'g')
+        return 1;
+    struct pair swapped = swap(copy);
+    if (swapped.a != 'g' || swapped.b != 6)
+        return 2;
+    if (swap(global).b != 5)
+        return 3;
+    struct pair items[2];
+    items[1] = global;
+    if (items[1].a != 5)
+        return 4;
+    return 0;
+}
@@ -0,0 +1,6 @@
+//expect error: Duplicate member x
+struct point { int x; long x; };
+
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,101 @@
+//link: gcc_abi_lib.c
+//expect exit: 0
+struct i4 { int a; };
+struct i8 { int a; int b; };
+struct i12 { int a; int b; int c; };
+struct c3 { char a; char b; char c; };
+struct c7 { char a[7]; };
+struct dd { double x; double y; };
+struct di { double x; int y; };
+struct id { int y; double x; };
+struct big { long a; long b; long c; };
+struct nest { struct c3 c; int s; double d; };
+struct arr { char c[10]; int i; };
+
+struct i4 f_i4(struct i4 s);
+struct i8 f_i8(struct i8 s);
+struct i12 f_i12(struct i12 s);
+struct c3 f_c3(struct c3 s);
+struct c7 f_c7(struct c7 s);
+struct dd f_dd(struct dd s);
+struct di f_di(struct di s);
+struct id f_id(struct id s);
+struct big f_big(struct big s);
+struct nest f_nest(struct nest s);
+struct arr f_arr(struct arr s);
+long many(int a, int b, int c, int d, int e, struct i8 s, struct dd x, struct big y, struct c3 z, double w, struct i12 t);
+struct dd ddmany(double a, double b, double c, double d, double e, double f, double g, struct dd s, double h);
+int call_glasses(void);
+
+long spill(int a, int b, int c, int d, int e, struct i12 s, int f) {
+    return a + b + c + d + e + s.a * 10 + s.b * 100 + s.c * 1000 + f * 10000;
+}
+
+//Structures bigger than 16 bytes are returned through a hidden pointer, including from recursive calls
+struct big fib(struct big s, int n) {
+    struct big r;
+    if (n == 0)
+        return s;
+    r.a = s.b;
+    r.b = s.a + s.b;
+    r.c = s.c + 1;
+    return fib(r, n - 1);
+}
+
+struct di swap_di(struct di s) {
+    struct di r = { s.y, s.x };
+    return r;
+}
+
+struct c3 rotate(struct c3 s) {
+    struct c3 r = { s.b, s.c, s.a };
+    return r;
+}
+
+static struct c3 gc3 = { 1, 2, 3 };
+
+int main(void) {
+    struct i4 a = { 21 };
+    struct i8 b = { 1, 2 };
+    struct i12 c = { 1, 2, 3 };
+    struct c7 e = { { 1, 2, 3, 4, 5, 6, 7 } };
+    struct dd f = { 1.5, 2.5 };
+    struct di g = { 1.5, 2 };
+    struct id h = { 3, 10.0 };
+    struct big i = { 1, 2, 3 };
+    struct nest j = { { 1, 2, 3 }, 4, 5.0 };
+    struct arr l = { "abcdefghi", 9 };
+    struct c3 d;
+    if (f_i4(a).a != 42) return 1;
+    b = f_i8(b);
+    if (b.a != 2 || b.b != 1) return 2;
+    c = f_i12(c);
+    if (c.a != 1 || c.c != 6) return 3;
+    d = f_c3(gc3);
+    if (d.a != 2 || d.b != 4 || d.c != 6) return 4;
+    e = f_c7(e);
+    for (int n = 0; n < 7; n = n + 1)
+        if (e.a[n] != 2 * n + 1) return 5;
+    f = f_dd(f);
+    if (f.x != 5.0 || f.y != 2.0) return 6;
+    g = f_di(g);
+    if (g.x != 3.5 || g.y != 6) return 7;
+    h = f_id(h);
+    if (h.x != 7.0 || h.y != 10) return 8;
+    struct big *p = &i;
+    *p = f_big(i);
+    if (i.a != 3 || i.b != 3 || i.c != 1) return 9;
+    j = f_nest(j);
+    if (j.c.a != 1 || j.c.b != 3 || j.c.c != 3 || j.s != 8 || j.d != 2.5) return 10;
+    l = f_arr(l);
+    if (l.c[9] != 'a' || l.i != 10 || l.c[8] != 'i') return 11;
+    struct i8 s = { 1, 2 };
+    struct dd x = { 3.0, 4.0 };
+    struct big y = { 10, 20, 30 };
+    struct c3 z = { 1, 1, 1 };
+    struct i12 t = { 5, 5, 5 };
+    if (many(1, 2, 3, 4, 5, s, x, y, z, 100.0, t) != 15 + 10 + 200 + 7000 + 60 + 3 + 100 + 15) return 12;
+    struct dd r = ddmany(1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, x, 1.0);
+    if (r.x != 10.0 || r.y != 5.0) return 13;
+    return 10 * call_glasses();
+}
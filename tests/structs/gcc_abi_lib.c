@@ -0,0 +1,55 @@
+struct i4 { int a; };
+struct i8 { int a; int b; };
+struct i12 { int a; int b; int c; };
+struct c3 { char a; char b; char c; };
+struct c7 { char a[7]; };
+struct dd { double x; double y; };
+struct di { double x; int y; };
+struct id { int y; double x; };
+struct big { long a; long b; long c; };
+struct nest { struct c3 c; int s; double d; };
+struct arr { char c[10]; int i; };
+
+struct i4 f_i4(struct i4 s) { s.a = s.a * 2; return s; }
+struct i8 f_i8(struct i8 s) { struct i8 r = { s.b, s.a }; return r; }
+struct i12 f_i12(struct i12 s) { s.c = s.a + s.b + s.c; return s; }
+struct c3 f_c3(struct c3 s) { s.a = s.a + 1; s.b = s.b + 2; s.c = s.c + 3; return s; }
+struct c7 f_c7(struct c7 s) { for (int i = 0; i < 7; i++) s.a[i] = s.a[i] + i; return s; }
+struct dd f_dd(struct dd s) { struct dd r = { s.y * 2.0, s.x + 0.5 }; return r; }
+struct di f_di(struct di s) { s.x = s.x + s.y; s.y = s.y * 3; return s; }
+struct id f_id(struct id s) { s.x = s.x - s.y; s.y = s.y + 7; return s; }
+struct big f_big(struct big s) { struct big r = { s.c, s.b + 1, s.a }; return r; }
+struct nest f_nest(struct nest s) { s.c.b = s.c.b + 1; s.s = s.s * 2; s.d = s.d / 2.0; return s; }
+struct arr f_arr(struct arr s) { s.c[9] = s.c[0]; s.i = s.i + 1; return s; }
+
+//Six integer registers run out, so later structures go on the stack
+long many(int a, int b, int c, int d, int e, struct i8 s, struct dd x, struct big y, struct c3 z, double w, struct i12 t) {
+    return a + b + c + d + e + s.a * 10 + s.b * 100 + (long)(x.x + x.y) * 1000 + y.a + y.b + y.c + z.a + z.b + z.c
+        + (long)w + t.a + t.b + t.c;
+}
+
+struct dd ddmany(double a, double b, double c, double d, double e, double f, double g, struct dd s, double h) {
+    struct dd r = { a + b + c + d + e + f + g + s.x, s.y + h };
+    return r;
+}
+
+//Calls back into the functions glasses compiled
+long spill(int a, int b, int c, int d, int e, struct i12 s, int f);
+struct big fib(struct big s, int n);
+struct di swap_di(struct di s);
+struct c3 rotate(struct c3 s);
+
+int call_glasses(void) {
+    struct i12 s = { 1, 2, 3 };
+    struct big start = { 0, 1, 0 };
+    struct di d = { 2.5, 4 };
+    struct c3 c = { 1, 2, 3 };
+    if (spill(1, 1, 1, 1, 1, s, 4) != 5 + 10 + 200 + 3000 + 40000) return 1;
+    struct big out = fib(start, 10);
+    if (out.a != 55 || out.b != 89 || out.c != 10) return 2;
+    d = swap_di(d);
+    if (d.x != 4.0 || d.y != 2) return 3;
+    c = rotate(c);
+    if (c.a != 2 || c.b != 3 || c.c != 1) return 4;
+    return 0;
+}
@@ -0,0 +1,7 @@
+//expect error: Variable s has an incomplete type
+struct unknown;
+
+int main(void) {
+    struct unknown s;
+    return 0;
+}
@@ -0,0 +1,10 @@
+//expect error: Function f has a parameter or return value of incomplete type
+struct unknown;
+
+int f(struct unknown s) {
+    return 0;
+}
+
+int main(void) {
+    return 0;
+}
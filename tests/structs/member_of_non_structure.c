@@ -0,0 +1,5 @@
+//expect error: Member x accessed on a non-structure
+int main(void) {
+    int i = 0;
+    return i.x;
+}
@@ -0,0 +1,29 @@
+//expect exit: 0
+struct point {
+    int x;
+    int y;
+};
+
+struct shape {
+    char kind;
+    struct point corners[2];
+    double scale;
+    struct shape *next;
+};
+
+int main(void) {
+    struct shape first = { 'r', { { 1, 2 }, { 3, 4 } }, 1.5, 0 };
+    struct shape second;
+    second.kind = 's';
+    second.corners[0].x = 10;
+    second.next = &first;
+    if (second.next->corners[1].y != 4 || second.next->scale != 1.5)
+        return 1;
+    struct shape *p = &second;
+    p->corners[1] = p->next->corners[0];
+    if (second.corners[1].x != 1 || second.corners[1].y != 2)
+        return 2;
+    if (first.next || first.kind != 'r')
+        return 3;
+    return 0;
+}
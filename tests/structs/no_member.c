@@ -0,0 +1,7 @@
+//expect error: has no member named z
+struct point { int x; int y; };
+
+int main(void) {
+    struct point p = { 1, 2 };
+    return p.z;
+}
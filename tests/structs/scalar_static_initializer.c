@@ -0,0 +1,7 @@
+//expect error: Cannot initialize a structure or union with a scalar
+struct point { int x; int y; };
+struct point p = 3;
+
+int main(void) {
+    return 0;
+}
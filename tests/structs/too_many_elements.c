@@ -0,0 +1,7 @@
+//expect error: Too many elements in structure initializer
+struct point { int x; int y; };
+
+int main(void) {
+    struct point p = { 1, 2, 3 };
+    return 0;
+}
@@ -0,0 +1,4 @@
+//expect error: case label outside of a switch
+int main(void) {
+    case 1: return 1;
+}
@@ -0,0 +1,24 @@
+//expect exit: 0
+//Dense case values are lowered to a jump table
+int classify(int n) {
+    switch (n) {
+        case 0: return 10;
+        case 1: return 11;
+        case 2: return 12;
+        case 3: return 13;
+        case 4: return 14;
+        case 6: return 16;
+        default: return -1;
+    }
+}
+
+int main(void) {
+    for (int i = 0; i < 7; i = i + 1) {
+        int expected = i == 5 ? -1 : 10 + i;
+        if (classify(i) != expected)
+            return i + 1;
+    }
+    if (classify(-1) != -1 || classify(7) != -1 || classify(2147483647) != -1)
+        return 20;
+    return 0;
+}
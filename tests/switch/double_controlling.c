@@ -0,0 +1,7 @@
+//expect error: Switch controlling expression must have an integer type
+int main(void) {
+    switch (1.5) {
+        case 1: return 1;
+    }
+    return 0;
+}
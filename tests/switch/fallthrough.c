@@ -0,0 +1,22 @@
+//expect exit: 0
+int count(int n) {
+    int total = 0;
+    switch (n) {
+        case 3:
+            total = total + 100;
+        case 2:
+            total = total + 10;
+        default:
+            total = total + 1;
+            break;
+        case 9:
+            total = 9;
+    }
+    return total;
+}
+
+int main(void) {
+    if (count(3) != 111 || count(2) != 11 || count(5) != 1 || count(9) != 9)
+        return 1;
+    return 0;
+}
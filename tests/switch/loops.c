@@ -0,0 +1,36 @@
+//expect exit: 0
+int main(void) {
+    int evens = 0;
+    int odds = 0;
+    for (int i = 0; i < 10; i = i + 1) {
+        switch (i % 2) {
+            case 0:
+                evens = evens + 1;
+                //continue applies to the enclosing loop
+                continue;
+            case 1:
+                odds = odds + 1;
+                break;
+        }
+        if (i == 7)
+            break;
+    }
+    if (evens != 4 || odds != 4)
+        return 1;
+    char c = 'b';
+    switch (c) {
+        case 'a':
+            return 2;
+        case 'b': {
+            unsigned int u = 3;
+            switch (u) {
+                case 3U:
+                    break;
+                default:
+                    return 3;
+            }
+            break;
+        }
+    }
+    return 0;
+}
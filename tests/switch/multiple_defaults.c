@@ -0,0 +1,7 @@
+//expect error: Multiple default labels in one switch
+int main(void) {
+    switch (1) {
+        default: return 1;
+        default: return 2;
+    }
+}
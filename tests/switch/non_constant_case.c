@@ -0,0 +1,8 @@
+//expect error: in case value
+int main(void) {
+    int x = 1;
+    switch (x) {
+        case x: return 1;
+    }
+    return 0;
+}
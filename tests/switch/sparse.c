@@ -0,0 +1,19 @@
+//expect exit: 0
+int classify(long n) {
+    switch (n) {
+        case -1000000: return 1;
+        case 7: return 2;
+        case 4294967296L: return 3;
+        case 99999: return 4;
+    }
+    return 0;
+}
+
+int main(void) {
+    if (classify(-1000000) != 1 || classify(7) != 2 || classify(4294967296L) != 3 || classify(99999) != 4)
+        return 1;
+    //A long value that only matches a case after truncation must not match
+    if (classify(4294967303L) != 0 || classify(8) != 0)
+        return 2;
+    return 0;
+}
@@ -0,0 +1,24 @@
+//expect exit: 0
+typedef int T;
+
+int main(void) {
+    //With T a type name this declares a pointer instead of multiplying
+    T * x;
+    T y = 3;
+    x = &y;
+    if (*x != 3)
+        return 1;
+    //A cast to a type name and a parenthesized variable
+    long z = (T) 4294967297L;
+    if (z != 1)
+        return 2;
+    {
+        //A variable declared with the typedef's name hides it in this block
+        int T = 6;
+        int product = T * y;
+        if (product != 18 || (T) != 6)
+            return 3;
+    }
+    T after = 5;
+    return after - 5;
+}
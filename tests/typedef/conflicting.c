@@ -0,0 +1,7 @@
+//expect error: Conflicting types for typedef T
+typedef int T;
+typedef long T;
+
+int main(void) {
+    return 0;
+}
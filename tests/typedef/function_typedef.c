@@ -0,0 +1,6 @@
+//expect error: Function typedefs are not supported
+typedef int fn(void);
+
+int main(void) {
+    return sizeof(fn);
+}
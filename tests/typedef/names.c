@@ -0,0 +1,29 @@
+//expect exit: 0
+typedef long wide;
+typedef wide *wide_ptr;
+typedef struct point { int x; int y; } point;
+typedef int triple[3];
+typedef unsigned char byte;
+
+wide_ptr first(wide *values) {
+    return values;
+}
+
+int main(void) {
+    wide values[2] = {4294967296L, 2};
+    if (*first(values) != 4294967296L)
+        return 1;
+    point p = { 1, 2 };
+    struct point q = p;
+    if (q.y != 2)
+        return 2;
+    triple t = {1, 2, 3};
+    if (sizeof t != 12 || t[2] != 3)
+        return 3;
+    byte b = (byte) 300;
+    if (b != 44 || sizeof(byte) != 1)
+        return 4;
+    //A typedef can be repeated if it names the same type
+    typedef long wide;
+    return 0;
+}
@@ -0,0 +1,6 @@
+//expect error: T is a type name, not a variable
+typedef int T;
+
+int main(void) {
+    return T;
+}
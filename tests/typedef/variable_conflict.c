@@ -0,0 +1,7 @@
+//expect error: T redeclared as a different kind of symbol
+int T;
+typedef int T;
+
+int main(void) {
+    return 0;
+}
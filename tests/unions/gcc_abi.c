@@ -0,0 +1,16 @@
+//link: gcc_abi_lib.c
+//expect exit: 0
+union u {
+    double d;
+    long l;
+};
+
+union u f_u(union u s);
+
+int main(void) {
+    union u k;
+    k.l = 41;
+    if (f_u(k).l != 42)
+        return 1;
+    return 0;
+}
@@ -0,0 +1,9 @@
+union u {
+    double d;
+    long l;
+};
+
+union u f_u(union u s) {
+    s.l = s.l + 1;
+    return s;
+}
@@ -0,0 +1,27 @@
+//expect exit: 0
+union number {
+    double d;
+    long l;
+};
+
+struct tagged {
+    int kind;
+    union number value;
+};
+
+//Only the first member is initialized
+union number global = { 2.5 };
+struct tagged table[2] = { { 1, { 1.5 } }, { 2 } };
+
+int main(void) {
+    union number local = { 4.0 };
+    if (global.d != 2.5 || local.d != 4.0)
+        return 1;
+    if (table[0].value.d != 1.5 || table[1].value.l != 0)
+        return 2;
+    union number copy = local;
+    copy.l = 1;
+    if (local.d != 4.0)
+        return 3;
+    return 0;
+}
@@ -0,0 +1,11 @@
+//expect error: has no member named f
+union number {
+    double d;
+    long l;
+};
+
+int main(void) {
+    union number n;
+    n.f = 1;
+    return 0;
+}
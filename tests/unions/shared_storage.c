@@ -0,0 +1,20 @@
+//expect exit: 0
+union value {
+    long l;
+    int i;
+    char bytes[8];
+    double d;
+};
+
+int main(void) {
+    union value v;
+    v.l = 0;
+    v.i = 258;
+    //Little-endian storage puts the low byte first
+    if (v.bytes[0] != 2 || v.bytes[1] != 1 || v.bytes[4] != 0)
+        return 1;
+    v.d = 1.0;
+    if (v.l != 4607182418800017408L)
+        return 2;
+    return 0;
+}
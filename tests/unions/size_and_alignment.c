@@ -0,0 +1,25 @@
+//expect exit: 0
+union small {
+    char c[5];
+    int i;
+};
+
+struct holder {
+    char tag;
+    union small u;
+    char after;
+};
+
+union small array[3];
+
+int main(void) {
+    //The union is as big as its largest member, rounded up to its alignment
+    if ((long) &array[1] - (long) &array[0] != 8)
+        return 1;
+    struct holder h;
+    if ((long) &h.u - (long) &h != 4)
+        return 2;
+    if ((long) &h.after - (long) &h != 12)
+        return 3;
+    return 0;
+}
@@ -0,0 +1,7 @@
+//expect error: shape is not a union tag
+struct shape { int sides; };
+
+int main(void) {
+    union shape s;
+    return 0;
+}
@@ -0,0 +1,7 @@
+//expect error: union shape redeclared as a different kind of tag
+struct shape { int sides; };
+union shape { int sides; double area; };
+
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,10 @@
+//expect error: Too many elements in union initializer
+union number {
+    double d;
+    long l;
+};
+
+int main(void) {
+    union number n = { 1.0, 2 };
+    return 0;
+}
@@ -0,0 +1,15 @@
+//expect exit: 0
+int main(void) {
+    unsigned int big = 3000000000U;
+    if (big < 5U)
+        return 1;
+    //-1 converts to the largest unsigned int, so it is not less than 1
+    if (-1 < 1U)
+        return 2;
+    unsigned long ul = 9223372036854775808UL;
+    if (!(ul > 100UL))
+        return 3;
+    if (ul >= 18446744073709551615UL)
+        return 4;
+    return 0;
+}
@@ -0,0 +1,7 @@
+//expect error: Conflicting types for variable x
+unsigned int x;
+int x;
+
+int main(void) {
+    return 0;
+}
@@ -0,0 +1,20 @@
+//expect exit: 0
+int main(void) {
+    unsigned int u = 4294967295U;
+    long l = u;
+    if (l != 4294967295L)
+        return 1;
+    int i = -1;
+    unsigned long from_int = i;
+    if (from_int != 18446744073709551615UL)
+        return 2;
+    unsigned int wrapped = u + 1;
+    if (wrapped != 0)
+        return 3;
+    unsigned long zero_extended = (unsigned int) -2;
+    if (zero_extended != 4294967294UL)
+        return 4;
+    if ((int) 2147483648U != -2147483647 - 1)
+        return 5;
+    return 0;
+}
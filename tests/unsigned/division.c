@@ -0,0 +1,14 @@
+//expect exit: 0
+int main(void) {
+    unsigned int u = 4294967295U;
+    if (u / 2 != 2147483647U)
+        return 1;
+    if (u % 10 != 5)
+        return 2;
+    unsigned long ul = 18446744073709551615UL;
+    if (ul / 3 != 6148914691236517205UL)
+        return 3;
+    if (ul % 7 != 1)
+        return 4;
+    return 0;
+}
@@ -0,0 +1,12 @@
+//link: gcc_abi_lib.c
+//expect exit: 0
+unsigned long half(unsigned long value);
+unsigned int wrap(unsigned int value);
+
+int main(void) {
+    if (half(18446744073709551614UL) != 9223372036854775807UL)
+        return 1;
+    if (wrap(4294967295U) != 0U)
+        return 2;
+    return 0;
+}
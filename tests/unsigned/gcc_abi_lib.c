@@ -0,0 +1,7 @@
+unsigned long half(unsigned long value) {
+    return value / 2;
+}
+
+unsigned int wrap(unsigned int value) {
+    return value + 1;
+}